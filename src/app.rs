@@ -9,6 +9,17 @@ fn add_input(cmd: Command) -> Command {
     )
 }
 
+fn add_inputs(cmd: Command) -> Command {
+    add_input(cmd).mut_arg("input", |arg| {
+        arg.help("Input files")
+            .long_help(
+                "Input files, read one after the other.  If not present, standard input will \
+                 be used (a single dash can be used for it too)",
+            )
+            .multiple_values(true)
+    })
+}
+
 fn add_input_as_option(cmd: Command) -> Command {
     cmd.arg(
         Arg::new("input")
@@ -62,6 +73,73 @@ the named one will be used).
     )
 }
 
+fn add_field(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("field")
+            .long("field")
+            .help("Use the nth (0-based) whitespace-separated field of lines as input values")
+            .conflicts_with("regex")
+            .takes_value(true),
+    )
+}
+
+fn add_csv_column(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("csv-column")
+            .long("csv-column")
+            .help("Parse input as CSV with a header row, using this column (name or 0-based index)")
+            .conflicts_with_all(&["regex", "field"])
+            .takes_value(true),
+    )
+}
+
+fn add_json_path(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("json-path")
+            .long("json-path")
+            .help("Parse input lines as JSON, using the number at this dotted path (like `a.b`)")
+            .conflicts_with_all(&["regex", "field", "csv-column"])
+            .takes_value(true),
+    )
+}
+
+fn add_fixed_field(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("fixed-field")
+            .long("fixed-field")
+            .value_names(&["START", "END"])
+            .number_of_values(2)
+            .help("Use the chars from START (0-based) to END (excluded) of lines as input values")
+            .conflicts_with_all(&["regex", "field", "csv-column", "json-path"])
+            .takes_value(true),
+    )
+}
+
+fn add_split_all(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("split-all")
+            .long("split-all")
+            .help("Use every whitespace-separated number of lines as input values")
+            .conflicts_with_all(&["regex", "field", "csv-column", "json-path", "fixed-field"])
+            .takes_value(false),
+    )
+}
+
+fn add_timezone(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("timezone")
+            .long("timezone")
+            .help("Shift timestamps to this timezone (like '+02:00' or 'Europe/Madrid')")
+            .long_help(
+                "Shift timestamps to this timezone, either a fixed offset (like '+02:00') or an \
+                 IANA timezone name (like 'Europe/Madrid').  Timestamps with no explicit offset \
+                 are taken as UTC.  Bucket labels use the offset in effect at the earliest \
+                 timestamp, so they are not shifted by DST transitions within the input.",
+            )
+            .takes_value(true),
+    )
+}
+
 fn add_non_capturing_regex(cmd: Command) -> Command {
     cmd.arg(
         Arg::new("regex")
@@ -77,7 +155,7 @@ fn add_width(cmd: Command) -> Command {
         Arg::new("width")
             .long("width")
             .short('w')
-            .help("Use this many characters as terminal width")
+            .help("Use this many characters as terminal width (0 for the default)")
             .default_value("110")
             .takes_value(true),
     )
@@ -114,13 +192,454 @@ fn add_log_scale(cmd: Command) -> Command {
     )
 }
 
+fn add_geometric(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("geometric")
+            .long("geometric")
+            .help("Use buckets growing geometrically from min to max (positive values only)")
+            .conflicts_with("log-scale")
+            .takes_value(false),
+    )
+}
+
+fn add_cumulative(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("cumulative")
+            .long("cumulative")
+            .help("Display in every bucket the count of values up to its upper bound")
+            .takes_value(false),
+    )
+}
+
+fn add_hide_empty(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("hide-empty")
+            .long("hide-empty")
+            .help("Collapse runs of empty buckets into a single line")
+            .takes_value(false),
+    )
+}
+
+fn add_partial_blocks(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("partial-blocks")
+            .long("partial-blocks")
+            .help("Draw bars with blocks of an eighth of a char of resolution")
+            .takes_value(false),
+    )
+}
+
+fn add_percent(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("percent")
+            .long("percent")
+            .help("Show the share of the total count of every bucket")
+            .takes_value(false),
+    )
+}
+
+fn add_density(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("density")
+            .long("density")
+            .help("Show the density of every bucket (bars add up to an area of 1) instead of its count")
+            .conflicts_with("cumulative")
+            .takes_value(false),
+    )
+}
+
+fn add_quantile_markers(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("markers")
+            .long("markers")
+            .help("Mark the median, p90 and p99 in the bars of the buckets holding them")
+            .takes_value(false),
+    )
+}
+
+fn add_reverse(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("reverse")
+            .long("reverse")
+            .help("Display buckets from the highest range to the lowest")
+            .takes_value(false),
+    )
+}
+
+fn add_shape(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("shape")
+            .long("shape")
+            .help("Show the skewness and kurtosis of input data")
+            .takes_value(false),
+    )
+}
+
+fn add_mad(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("mad")
+            .long("mad")
+            .help("Show the median absolute deviation of input data")
+            .takes_value(false),
+    )
+}
+
+fn add_mean_ci(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("mean-ci")
+            .long("mean-ci")
+            .value_name("LEVEL")
+            .help("Show the confidence interval for the mean at that level (like 0.95)")
+            .takes_value(true),
+    )
+}
+
+fn add_nice(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("nice")
+            .long("nice")
+            .help("Round bucket edges to multiples of 1, 2, 2.5 or 5 times a power of ten")
+            .conflicts_with_all(&["log-scale", "geometric"])
+            .takes_value(false),
+    )
+}
+
+fn add_clip(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("clip")
+            .long("clip")
+            .value_names(&["LOW", "HIGH"])
+            .number_of_values(2)
+            .help("Split the range between those percentiles and count the rest as out of range")
+            .conflicts_with_all(&["geometric", "weighted", "stream"])
+            .takes_value(true),
+    )
+}
+
+fn add_integer(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("integer")
+            .long("integer")
+            .help("Take values as integers, for buckets with whole-number edges")
+            .conflicts_with_all(&["log-scale", "geometric", "nice"])
+            .takes_value(false),
+    )
+}
+
+fn add_interval_notation(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("interval-notation")
+            .long("interval-notation")
+            .help("Print bucket ranges like [1, 2), telling which edges they include")
+            .takes_value(false),
+    )
+}
+
+fn add_human_counts(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("human-counts")
+            .long("human-counts")
+            .help("Display counts of 1000 or more with a k, M, G... suffix")
+            .takes_value(false),
+    )
+}
+
+fn add_min_bar_len(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("min-bar-len")
+            .long("min-bar-len")
+            .help("Draw bars of non-empty buckets with at least that many chars")
+            .default_value("0")
+            .takes_value(true),
+    )
+}
+
+fn add_hide_legend(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("hide-legend")
+            .long("hide-legend")
+            .help("Do not display what bars represent")
+            .takes_value(false),
+    )
+}
+
+fn add_hide_stats(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("hide-stats")
+            .long("hide-stats")
+            .help("Do not display the stats of data above buckets")
+            .takes_value(false),
+    )
+}
+
+fn add_threshold(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("threshold")
+            .long("threshold")
+            .help("Display the share of values at most this one, and divide buckets by it")
+            .takes_value(true),
+    )
+}
+
+fn add_max_rows(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("max-rows")
+            .long("max-rows")
+            .help("Merge adjacent buckets (by 2, 4, ...) when there are more than this many")
+            .takes_value(true),
+    )
+}
+
+fn add_highlight(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("highlight")
+            .long("highlight")
+            .help("Mark the row of the bucket holding that value (can be repeated)")
+            .takes_value(true)
+            .multiple_occurrences(true),
+    )
+}
+
+fn add_vertical(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("vertical")
+            .long("vertical")
+            .help("Draw buckets as columns growing upward")
+            .takes_value(false),
+    )
+    .arg(
+        Arg::new("height")
+            .long("height")
+            .short('H')
+            .help("Use that many rows for the highest column")
+            .requires("vertical")
+            .default_value("10")
+            .takes_value(true),
+    )
+}
+
+fn add_zero_axis(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("zero-axis")
+            .long("zero-axis")
+            .help("Draw bars of negative buckets leftward from a central axis for zero")
+            .takes_value(false),
+    )
+}
+
+fn add_intensity(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("intensity")
+            .long("intensity")
+            .help("Without colors, draw bars with glyphs as dark as they are long (░▒▓█)")
+            .takes_value(false),
+    )
+}
+
+fn add_theme(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("theme")
+            .long("theme")
+            .help("Use this set of colors for drawing the chart")
+            .possible_values(["default", "monochrome", "high-contrast"])
+            .default_value("default")
+            .takes_value(true),
+    )
+}
+
+fn add_output_format(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("output-format")
+            .long("output-format")
+            .help("Print charts for humans, or as tab-separated `start end count` lines")
+            .possible_values(["human", "tsv"])
+            .default_value("human")
+            .takes_value(true),
+    )
+}
+
+fn add_scientific(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("scientific")
+            .long("scientific")
+            .value_name("THRESHOLD")
+            .help("Use scientific notation for bucket edges at least this big (1e6 by default)")
+            .min_values(0)
+            .require_equals(true)
+            .default_missing_value("1e6")
+            .takes_value(true),
+    )
+}
+
+/// Adds the args setting how numbers in input lines are written: grouping
+/// separators, decimal mark, durations, radix and units to strip
+fn add_number_format(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("strip-separators")
+            .long("strip-separators")
+            .value_name("CHAR")
+            .help("Remove this grouping char (a comma by default) from numbers before parsing them")
+            .min_values(0)
+            .require_equals(true)
+            .default_missing_value(",")
+            .takes_value(true),
+    )
+    .arg(
+        Arg::new("decimal")
+            .long("decimal")
+            .value_name("CHAR")
+            .help("Use this char as decimal point when parsing numbers")
+            .takes_value(true),
+    )
+    .arg(
+        Arg::new("decimal-comma")
+            .long("decimal-comma")
+            .help("Use the comma as decimal point when parsing numbers")
+            .conflicts_with("decimal")
+            .takes_value(false),
+    )
+    .arg(
+        Arg::new("durations")
+            .long("durations")
+            .help("Parse values as durations (like 1m30s or 250ms), in seconds")
+            .conflicts_with_all(&["strip-separators", "decimal", "decimal-comma"])
+            .takes_value(false),
+    )
+    .arg(
+        Arg::new("radix")
+            .long("radix")
+            .value_name("RADIX")
+            .help("Parse values as integers in this radix (like 16 for 0x1f)")
+            .conflicts_with_all(&["decimal", "decimal-comma", "durations"])
+            .takes_value(true),
+    )
+    .arg(
+        Arg::new("strip-units")
+            .long("strip-units")
+            .help("Remove trailing chars that are not digits (like ms in 123ms) from values")
+            .conflicts_with("durations")
+            .takes_value(false),
+    )
+}
+
+fn add_sample(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("sample")
+            .long("sample")
+            .value_name("SIZE")
+            .help("Use a random sample of this many input values (for inputs too big for memory)")
+            .takes_value(true),
+    )
+    .arg(
+        Arg::new("seed")
+            .long("seed")
+            .help("Seed for the random numbers used for sampling, for reproducible output")
+            .requires("sample")
+            .takes_value(true),
+    )
+}
+
+fn add_comment_char(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("comment-char")
+            .long("comment-char")
+            .value_name("CHAR")
+            .help("Ignore lines starting with this char (blank lines are always ignored)")
+            .takes_value(true),
+    )
+}
+
+fn add_progress(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("progress")
+            .long("progress")
+            .help("Report the number of lines read so far into stderr (if it is a terminal)")
+            .takes_value(false),
+    )
+}
+
+fn add_skip_lines(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("skip-header")
+            .long("skip-header")
+            .value_name("LINES")
+            .help("Ignore this many lines at the start of input")
+            .takes_value(true),
+    )
+    .arg(
+        Arg::new("skip-footer")
+            .long("skip-footer")
+            .value_name("LINES")
+            .help("Ignore this many lines at the end of input (they are held in memory)")
+            .takes_value(true),
+    )
+}
+
+fn add_value_selectors(cmd: Command) -> Command {
+    let cmd = add_json_path(add_csv_column(add_field(add_regex(cmd))));
+    let cmd = add_split_all(add_fixed_field(cmd));
+    add_progress(add_skip_lines(add_comment_char(add_sample(
+        add_number_format(cmd),
+    ))))
+}
+
+fn add_log_bars(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("log-bars")
+            .long("log-bars")
+            .help("Use a logarithmic scale for the length of the bars")
+            .takes_value(false),
+    )
+}
+
 pub fn get_app() -> Command<'static> {
     let mut hist = Command::new("hist")
         .version(clap::crate_version!())
         .about("Plot an histogram from input values");
-    hist = add_input(add_regex(add_width(add_min_max(add_precision(
-        add_intervals(add_log_scale(hist)),
+    hist = add_hide_empty(add_cumulative(add_log_bars(add_geometric(add_log_scale(
+        hist,
+    )))));
+    hist = add_reverse(add_shape(add_scientific(add_percent(add_partial_blocks(
+        hist,
     )))));
+    hist = add_output_format(add_quantile_markers(add_density(hist)));
+    hist = add_nice(add_vertical(add_zero_axis(add_intensity(add_theme(hist)))));
+    hist = add_mean_ci(add_highlight(add_min_bar_len(add_human_counts(hist))));
+    hist = add_max_rows(add_integer(add_interval_notation(add_clip(hist))));
+    hist = add_mad(add_hide_stats(add_hide_legend(add_threshold(hist))));
+    hist = add_value_selectors(hist)
+        .arg(
+            Arg::new("weighted")
+                .long("weighted")
+                .help("Read input lines as pre-aggregated `value count` pairs")
+                .conflicts_with_all(&[
+                    "regex",
+                    "field",
+                    "csv-column",
+                    "json-path",
+                    "fixed-field",
+                    "split-all",
+                    "sample",
+                ])
+                .takes_value(false),
+        )
+        .arg(
+            Arg::new("stream")
+                .long("stream")
+                .help("Bucket values while reading them, without holding them in memory")
+                .requires_all(&["min", "max"])
+                .conflicts_with_all(&["weighted", "sample", "geometric"])
+                .takes_value(false),
+        );
+    hist = add_inputs(add_width(add_min_max(add_precision(add_intervals(hist))))).mut_arg(
+        "intervals",
+        |arg| {
+            arg.long_help(
+                "Use no more than this amount of buckets to classify data.  Use 'auto' for \
+             choosing it from the spread of data (Freedman–Diaconis rule)",
+            )
+        },
+    );
 
     let mut plot = Command::new("plot")
         .version(clap::crate_version!())
@@ -133,7 +652,37 @@ pub fn get_app() -> Command<'static> {
                 .default_value("40")
                 .takes_value(true),
         );
-    plot = add_input(add_regex(add_width(add_min_max(add_precision(plot)))));
+    plot = plot.arg(
+        Arg::new("sparkline")
+            .long("sparkline")
+            .help("Print the plot as a single line of text")
+            .takes_value(false),
+    );
+    plot = add_inputs(add_width(add_min_max(add_precision(add_value_selectors(
+        plot,
+    )))));
+
+    let mut boxplot = Command::new("boxplot")
+        .version(clap::crate_version!())
+        .about("Plot a box-and-whisker plot from input values");
+    boxplot = add_inputs(add_width(add_min_max(add_precision(add_value_selectors(
+        boxplot,
+    )))));
+
+    let mut ecdf = Command::new("ecdf")
+        .version(clap::crate_version!())
+        .about("Plot the empirical cumulative distribution of input values")
+        .arg(
+            Arg::new("height")
+                .long("height")
+                .short('H')
+                .help("Use that many `rows` for the plot")
+                .default_value("20")
+                .takes_value(true),
+        );
+    ecdf = add_inputs(add_width(add_min_max(add_precision(add_value_selectors(
+        ecdf,
+    )))));
 
     let mut matches = Command::new("matches")
         .version(clap::crate_version!())
@@ -158,6 +707,34 @@ pub fn get_app() -> Command<'static> {
                     .help("Use this string formatting")
                     .takes_value(true),
             )
+            .arg(
+                Arg::new("epoch-unit")
+                    .long("epoch-unit")
+                    .help("Parse timestamps as Unix epochs in this unit")
+                    .possible_values(["seconds", "millis"])
+                    .conflicts_with("format")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::new("interval")
+                    .long("interval")
+                    .help("Use buckets of this fixed duration, aligned to it (example: '1h')")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::new("group-by")
+                    .long("group-by")
+                    .help("Count timestamps per hour of day or day of week, across dates")
+                    .possible_values(["hour", "weekday"])
+                    .conflicts_with_all(&["interval", "moving-average"])
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::new("moving-average")
+                    .long("moving-average")
+                    .help("Show the moving average of counts over this many buckets")
+                    .takes_value(true),
+            )
             .arg(
                 Arg::new("duration")
                     .long("duration")
@@ -167,7 +744,9 @@ pub fn get_app() -> Command<'static> {
             .arg(Arg::new("early-stop").long("early-stop").help(
                 "If duration flag is used, assume monotonic times and stop as soon as possible",
             ));
-    timehist = add_input(add_width(add_non_capturing_regex(add_intervals(timehist))));
+    timehist = add_output_format(add_input(add_width(add_non_capturing_regex(
+        add_intervals(add_timezone(timehist)),
+    ))));
 
     let mut splittimehist = Command::new("split-timehist")
         .version(clap::crate_version!())
@@ -179,7 +758,7 @@ pub fn get_app() -> Command<'static> {
                 .help("Use this string formatting")
                 .takes_value(true),
         );
-    splittimehist = add_input_as_option(add_width(add_intervals(splittimehist))).arg(
+    splittimehist = add_input_as_option(add_width(add_intervals(add_timezone(splittimehist)))).arg(
         Arg::new("match")
             .help("Count matches for those strings")
             .required(true)
@@ -199,6 +778,19 @@ pub fn get_app() -> Command<'static> {
             .takes_value(true),
     );
 
+    let auto = Command::new("auto")
+        .version(clap::crate_version!())
+        .about("Plot an histogram of input numbers or timestamps, detecting which ones it holds")
+        .arg(
+            Arg::new("input")
+                .help("Input file")
+                .long_help(
+                    "Input file.  Its first lines are read twice, so standard input cannot be \
+                     used",
+                )
+                .required(true),
+        );
+
     Command::new("lowcharts")
         .author(clap::crate_authors!())
         .version(clap::crate_version!())
@@ -223,10 +815,13 @@ pub fn get_app() -> Command<'static> {
         )
         .subcommand(hist)
         .subcommand(plot)
+        .subcommand(boxplot)
+        .subcommand(ecdf)
         .subcommand(matches)
         .subcommand(timehist)
         .subcommand(splittimehist)
         .subcommand(common_terms)
+        .subcommand(auto)
 }
 
 #[cfg(test)]
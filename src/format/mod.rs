@@ -1,12 +1,44 @@
 use std::fmt;
+use std::io;
 use std::ops::Range;
 
-use yansi::Color::{Blue, Green, Red};
+use yansi::Color::{self, Blue, Green, Red};
 use yansi::Paint;
 
 // Units-based suffixes for human formatting.
 const UNITS: &[&str] = &["", " K", " M", " G", " T", " P", " E", " Z", " Y"];
-pub static BAR_CHAR: &str = "∎";
+// SI suffixes for compact formatting of counts.
+const SI_SUFFIXES: &[&str] = &["", "k", "M", "G", "T", "P", "E"];
+pub const BAR_CHAR: char = '∎';
+pub const FULL_BLOCK: char = '█';
+// Blocks filling from one eighth to seven eighths of a char cell.
+const PARTIAL_BLOCKS: &[char] = &['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+/// Formats a count with a SI suffix and a decimal digit (like `1.2M`) if it
+/// is 1000 or more, or as an exact integer otherwise.
+pub fn format_si(count: usize) -> String {
+    let mut value = count as f64;
+    let mut suffix = 0;
+    // Values rounding up to 1000.0 go to next suffix
+    while value >= 999.95 && suffix + 1 < SI_SUFFIXES.len() {
+        value /= 1000.0;
+        suffix += 1;
+    }
+    match suffix {
+        0 => count.to_string(),
+        _ => format!("{:.1}{}", value, SI_SUFFIXES[suffix]),
+    }
+}
+
+/// Glyphs for increasing intensities, for conveying them without colors.
+pub const INTENSITY_CHARS: &[char] = &['░', '▒', '▓', '█'];
+
+/// Returns the index in `INTENSITY_CHARS` of the glyph for `value`, when the
+/// highest value is `top`: values up to a quarter of `top` get the lightest
+/// glyph, and values above three quarters of it the darkest one.
+pub fn intensity_level(value: usize, top: usize) -> usize {
+    let levels = INTENSITY_CHARS.len();
+    (value * levels).div_ceil(top.max(1)).clamp(1, levels) - 1
+}
 
 #[derive(Debug)]
 pub struct F64Formatter {
@@ -16,6 +48,9 @@ pub struct F64Formatter {
     divisor: u8,
     /// Suffix (typycally units) to be printed after number
     suffix: String,
+    /// If present, numbers whose magnitude is at least the threshold are
+    /// printed in scientific notation with the given decimals
+    scientific: Option<(f64, usize)>,
 }
 
 impl F64Formatter {
@@ -25,6 +60,7 @@ impl F64Formatter {
             decimals,
             divisor: 0,
             suffix: String::new(),
+            scientific: None,
         }
     }
 
@@ -41,6 +77,7 @@ impl F64Formatter {
                 decimals,
                 divisor,
                 suffix,
+                scientific: None,
             };
         }
         let log = difference.abs().log10() as i64;
@@ -55,10 +92,24 @@ impl F64Formatter {
             decimals,
             divisor,
             suffix,
+            scientific: None,
         }
     }
 
+    /// Makes the formatter print numbers whose magnitude is at least
+    /// `threshold` in scientific notation (like `1.235e9`), with `decimals`
+    /// decimal digits.
+    pub fn with_scientific(mut self, threshold: f64, decimals: usize) -> Self {
+        self.scientific = Some((threshold, decimals));
+        self
+    }
+
     pub fn format(&self, number: f64) -> String {
+        if let Some((threshold, decimals)) = self.scientific {
+            if number.abs() >= threshold {
+                return format!("{:.*e}", decimals, number);
+            }
+        }
         format!(
             "{:.*}{}",
             self.decimals,
@@ -68,20 +119,159 @@ impl F64Formatter {
     }
 }
 
+/// Adapter for writing formatted output into an `io::Write` target.  As
+/// `fmt::Error` carries no information, the last I/O error is kept aside.
+pub struct IoWriter<'a, W: io::Write> {
+    inner: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<'a, W: io::Write> IoWriter<'a, W> {
+    pub fn new(inner: &'a mut W) -> Self {
+        Self { inner, error: None }
+    }
+
+    /// Returns the I/O error that made writing fail (or a generic one if
+    /// the failure was a formatting one).
+    pub fn into_error(self) -> io::Error {
+        self.error
+            .unwrap_or_else(|| io::Error::other("formatter error"))
+    }
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
 pub struct HorizontalScale {
     /// How many units are represented by a char
     scale: usize,
+    /// If present, bars are drawn in logarithmic scale, using this many chars
+    /// per unit of `ln(units + 1)`
+    log_factor: Option<f64>,
+    /// If true, bars are drawn with blocks, using a partial block for the
+    /// fractional remainder of their length
+    partial_blocks: bool,
+    /// Char used for drawing bars
+    glyph: char,
+    /// Color used for drawing bars
+    color: Color,
+    /// Color used for the numbers in the legend
+    legend_color: Color,
+    /// Minimum length of the bars of non-zero units
+    min_bar_len: usize,
 }
 
 impl HorizontalScale {
     pub fn new(scale: usize) -> Self {
         Self {
             scale: 1.max(scale),
+            log_factor: None,
+            partial_blocks: false,
+            glyph: BAR_CHAR,
+            color: Red,
+            legend_color: Blue,
+            min_bar_len: 0,
+        }
+    }
+
+    /// Initializes a `HorizontalScale` where bar length grows with the
+    /// logarithm of the units, and where `top` units are drawn with a bar of
+    /// `max_len` chars.
+    pub fn new_logarithmic(top: usize, max_len: usize) -> Self {
+        let log_top = (top as f64).ln_1p();
+        Self {
+            log_factor: Some(if log_top > 0.0 {
+                max_len as f64 / log_top
+            } else {
+                0.0
+            }),
+            ..Self::new(1)
+        }
+    }
+
+    /// Sets whether bars will be drawn with sub-character resolution, using
+    /// block glyphs for the fractional part of their length.  If colors are
+    /// disabled, lengths are rounded to the nearest full block instead.
+    pub fn with_partial_blocks(mut self, partial_blocks: bool) -> Self {
+        self.partial_blocks = partial_blocks;
+        self
+    }
+
+    /// Sets the char and color to be used for drawing bars (the char is
+    /// ignored when drawing bars with partial blocks).
+    pub fn with_glyph(mut self, glyph: char, color: Color) -> Self {
+        self.glyph = glyph;
+        self.color = color;
+        self
+    }
+
+    /// Sets the color to be used for the numbers in the legend.
+    pub fn with_legend_color(mut self, color: Color) -> Self {
+        self.legend_color = color;
+        self
+    }
+
+    /// Sets the minimum length of bars for non-zero units, so that they are
+    /// told apart from zero ones even if they are short for the scale.
+    pub fn with_min_bar_len(mut self, min_bar_len: usize) -> Self {
+        self.min_bar_len = min_bar_len;
+        self
+    }
+
+    // Returns the minimum length of the bar of `units`
+    fn get_min_bar_len(&self, units: usize) -> usize {
+        if units > 0 {
+            self.min_bar_len
+        } else {
+            0
         }
     }
 
     pub fn get_bar(&self, units: usize) -> Paint<String> {
-        Red.paint(format!("{:∎<width$}", "", width = units / self.scale))
+        let bar = if !self.partial_blocks {
+            self.glyph.to_string().repeat(self.get_bar_len(units))
+        } else if Paint::is_enabled() {
+            self.get_partial_bar(units)
+        } else {
+            FULL_BLOCK
+                .to_string()
+                .repeat(self.get_fractional_bar_len(units).round() as usize)
+        };
+        self.color.paint(bar)
+    }
+
+    fn get_bar_len(&self, units: usize) -> usize {
+        let len = match self.log_factor {
+            Some(factor) => ((units as f64).ln_1p() * factor).round() as usize,
+            None => units / self.scale,
+        };
+        len.max(self.get_min_bar_len(units))
+    }
+
+    fn get_fractional_bar_len(&self, units: usize) -> f64 {
+        let len = match self.log_factor {
+            Some(factor) => (units as f64).ln_1p() * factor,
+            None => units as f64 / self.scale as f64,
+        };
+        len.max(self.get_min_bar_len(units) as f64)
+    }
+
+    /// Returns a bar made of full blocks, plus a partial block representing
+    /// the remainder of its length (rounded to eighths of a char).
+    fn get_partial_bar(&self, units: usize) -> String {
+        let eighths = (self.get_fractional_bar_len(units) * 8.0).round() as usize;
+        let mut bar = FULL_BLOCK.to_string().repeat(eighths / 8);
+        let remainder = eighths % 8;
+        if remainder > 0 {
+            bar.push(PARTIAL_BLOCKS[remainder - 1]);
+        }
+        bar
     }
 
     pub fn get_count(&self, units: usize, width: usize) -> Paint<String> {
@@ -95,11 +285,23 @@ impl HorizontalScale {
 
 impl fmt::Display for HorizontalScale {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let bar_char = if self.partial_blocks {
+            FULL_BLOCK
+        } else {
+            self.glyph
+        };
+        if self.log_factor.is_some() {
+            return writeln!(
+                formatter,
+                "Bars of {} use a logarithmic scale (length proportional to ln(count + 1))",
+                self.color.paint(bar_char),
+            );
+        }
         writeln!(
             formatter,
             "Each {} represents a count of {}",
-            Red.paint(BAR_CHAR),
-            Blue.paint(self.scale.to_string()),
+            self.color.paint(bar_char),
+            self.legend_color.paint(self.scale.to_string()),
         )
     }
 }
@@ -109,6 +311,28 @@ mod tests {
     use super::*;
     use yansi::Paint;
 
+    #[test]
+    fn test_format_si() {
+        assert_eq!(format_si(0), "0");
+        assert_eq!(format_si(999), "999");
+        assert_eq!(format_si(1000), "1.0k");
+        assert_eq!(format_si(12_345), "12.3k");
+        assert_eq!(format_si(999_960), "1.0M");
+        assert_eq!(format_si(1_234_567), "1.2M");
+        assert_eq!(format_si(5_000_000_000), "5.0G");
+    }
+
+    #[test]
+    fn test_intensity_level() {
+        assert_eq!(intensity_level(0, 8), 0);
+        assert_eq!(intensity_level(2, 8), 0);
+        assert_eq!(intensity_level(3, 8), 1);
+        assert_eq!(intensity_level(6, 8), 2);
+        assert_eq!(intensity_level(7, 8), 3);
+        assert_eq!(intensity_level(8, 8), 3);
+        assert_eq!(intensity_level(0, 0), 0);
+    }
+
     #[test]
     fn test_basic_format() {
         assert_eq!(F64Formatter::new(0).format(1000.0), "1000");
@@ -117,6 +341,17 @@ mod tests {
         assert_eq!(F64Formatter::new(10).format(3.0), "3.0000000000");
     }
 
+    #[test]
+    fn test_scientific_format() {
+        let formatter = F64Formatter::new(3).with_scientific(1e6, 3);
+        assert_eq!(formatter.format(1234567890.0), "1.235e9");
+        assert_eq!(formatter.format(-1e6), "-1.000e6");
+        assert_eq!(formatter.format(123456.0), "123456.000");
+        let formatter = F64Formatter::new_with_range(0.0..1e12).with_scientific(1e9, 1);
+        assert_eq!(formatter.format(2.5e11), "2.5e11");
+        assert_eq!(formatter.format(2.5e8), "0 G");
+    }
+
     #[test]
     fn test_human_format_from_zero() {
         assert_eq!(F64Formatter::new_with_range(0.0..2.0).format(1.12), "1.120");
@@ -227,6 +462,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_horizontal_scale_logarithmic() {
+        Paint::disable();
+        let scale = HorizontalScale::new_logarithmic(99, 20);
+        assert_eq!(
+            format!("{scale}"),
+            format!(
+                "Bars of {BAR_CHAR} use a logarithmic scale (length proportional to ln(count + 1))\n"
+            )
+        );
+        assert_eq!(scale.get_bar_len(0), 0);
+        assert_eq!(scale.get_bar_len(9), 10);
+        assert_eq!(scale.get_bar_len(99), 20);
+        assert_eq!(HorizontalScale::new_logarithmic(0, 20).get_bar_len(0), 0);
+    }
+
+    #[test]
+    fn test_horizontal_scale_partial_blocks() {
+        let scale = HorizontalScale::new(4).with_partial_blocks(true);
+        // 10 / 4 = 2.5 chars
+        assert_eq!(scale.get_partial_bar(10), "██▌");
+        // 9 / 4 = 2.25 chars
+        assert_eq!(scale.get_partial_bar(9), "██▎");
+        assert_eq!(scale.get_partial_bar(8), "██");
+        assert_eq!(scale.get_partial_bar(0), "");
+        let scale = HorizontalScale::new_logarithmic(99, 20).with_partial_blocks(true);
+        // ln(5) / ln(100) * 20 = 6.99 chars
+        assert_eq!(scale.get_partial_bar(4), "███████");
+    }
+
+    #[test]
+    fn test_horizontal_scale_glyph() {
+        let scale = HorizontalScale::new(10).with_glyph('#', Color::Cyan);
+        assert_eq!(scale.get_bar(30), Color::Cyan.paint("###".to_string()));
+        Paint::disable();
+        assert_eq!(
+            format!("{scale}"),
+            "Each # represents a count of 10\n".to_string()
+        );
+    }
+
+    #[test]
+    fn test_horizontal_scale_min_bar_len() {
+        let scale = HorizontalScale::new(100).with_min_bar_len(1);
+        assert_eq!(scale.get_bar(1), Red.paint("∎".to_string()));
+        assert_eq!(scale.get_bar(0), Red.paint(String::new()));
+        assert_eq!(scale.get_bar(300), Red.paint("∎∎∎".to_string()));
+        let scale = scale.with_partial_blocks(true);
+        assert_eq!(scale.get_partial_bar(1), "█");
+    }
+
     #[test]
     fn test_horizontal_scale_count() {
         let scale = HorizontalScale::new(10);
@@ -16,6 +16,12 @@
 //! print!("{}", histogram);
 //! ```
 
+#[macro_use]
+extern crate derive_builder;
+#[macro_use]
+extern crate log;
+
 mod format;
 pub mod plot;
+pub mod read;
 pub mod stats;
@@ -1,21 +1,23 @@
 mod app;
-mod format;
-mod plot;
-mod read;
-mod stats;
 
 use std::env;
 
-#[macro_use]
-extern crate derive_builder;
 #[macro_use]
 extern crate log;
 use chrono::Duration;
 use clap::ArgMatches;
+use lowcharts::{plot, read};
 use regex::Regex;
 use simplelog::{ColorChoice, ConfigBuilder, LevelFilter, TermLogger, TerminalMode};
 use yansi::Paint;
 
+/// Exit code used when input does not hold any data point (1 is used for other
+/// errors in data and 2 for wrong arguments)
+const NO_DATA_EXIT_CODE: i32 = 3;
+
+/// Number of lines looked at for detecting the kind of values of an input
+const SNIFF_LINES: usize = 10;
+
 /// True if vec has al least 'min' elements
 fn assert_data<T>(vec: &[T], min: usize) -> bool {
     if vec.len() < min {
@@ -24,8 +26,93 @@ fn assert_data<T>(vec: &[T], min: usize) -> bool {
     vec.len() >= min
 }
 
+/// Logs that input had no data points to plot, returning the exit code for
+/// that case
+fn no_data() -> i32 {
+    error!("No data points found");
+    NO_DATA_EXIT_CODE
+}
+
+/// Logs that a reader found no value to plot, returning the exit code for
+/// that case: telling apart input with no line parsed as a number, likely in
+/// a wrong format, from empty input
+fn no_values(reader: &read::DataReader) -> i32 {
+    if reader.nothing_parsed() {
+        error!(
+            "0 of {} lines parsed as numbers; wrong column or format?",
+            reader.lines()
+        );
+        return 1;
+    }
+    no_data()
+}
+
+/// Logs that a reader found no timestamp to plot among the `lines` it read,
+/// returning the exit code for that case (like `no_values` does for numbers)
+fn no_timestamps(lines: usize) -> i32 {
+    if lines > 0 {
+        error!(
+            "0 of {} lines parsed as timestamps; wrong format or matches?",
+            lines
+        );
+        return 1;
+    }
+    no_data()
+}
+
+/// Returns the width to use for the output: the one given in the command line
+/// or, failing that, the one of the terminal.  Defaults to 110 when output is
+/// not a terminal, so that output written to files is stable.
+fn get_width(matches: &ArgMatches) -> usize {
+    let width = matches.value_of_t("width").unwrap();
+    // A width of zero stands for the default
+    if width > 0 && matches.occurrences_of("width") > 0 {
+        return width;
+    }
+    plot::detect_width().unwrap_or(if width > 0 { width } else { 110 })
+}
+
+/// Reads values from input paths, for plots not depending on their order
+#[cfg(not(feature = "parallel"))]
+fn read_unordered(reader: &read::DataReader, paths: &[&str]) -> Vec<f64> {
+    reader.read_files(paths)
+}
+
+/// Reads values from input paths concurrently, for plots not depending on
+/// their order
+#[cfg(feature = "parallel")]
+fn read_unordered(reader: &read::DataReader, paths: &[&str]) -> Vec<f64> {
+    reader.read_files_parallel(paths)
+}
+
+/// Warns about input lines that have been skipped by a reader
+fn report_skipped(matches: &ArgMatches, reader: &read::DataReader) {
+    if reader.misses() > 0 {
+        warn!("{} lines did not match the regex", reader.misses());
+    }
+    let columnar = ["field", "csv-column", "json-path", "fixed-field"]
+        .iter()
+        .any(|arg| matches.is_present(arg));
+    if columnar && reader.errors() > 0 {
+        warn!("{} lines did not have a numeric field", reader.errors());
+    }
+    if reader.non_finite() > 0 {
+        warn!("{} non-finite values were dropped", reader.non_finite());
+    }
+    if reader.excluded() > 0 {
+        info!("{} values out of range were excluded", reader.excluded());
+    }
+    if matches.is_present("sample") {
+        debug!("Using a sample of a total of {} values", reader.total());
+    }
+}
+
 /// Sets up color choices and verbosity in the two libraries used for output:
 /// simplelog and yansi
+///
+/// In "auto" mode colors are disabled when the NO_COLOR environment variable is
+/// set to a non empty value (see https://no-color.org/), when the terminal is
+/// dumb or when stdout is not a terminal.
 fn configure_output(option: &str, verbose: bool) {
     let mut color_choice = ColorChoice::Auto;
     match option {
@@ -33,6 +120,10 @@ fn configure_output(option: &str, verbose: bool) {
             Paint::disable();
             color_choice = ColorChoice::Never;
         }
+        "auto" if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) => {
+            Paint::disable();
+            color_choice = ColorChoice::Never;
+        }
         "auto" => match env::var("TERM") {
             Ok(value) if value == "dumb" => Paint::disable(),
             _ => {
@@ -96,7 +187,210 @@ fn get_float_reader(matches: &ArgMatches) -> Result<read::DataReader, ()> {
             }
         };
     }
-    Ok(builder.build().unwrap())
+    if matches.is_present("field") {
+        match matches.value_of_t("field") {
+            Ok(n) => {
+                builder.field(n);
+            }
+            _ => {
+                error!("Field should be a non-negative integer");
+                return Err(());
+            }
+        };
+    }
+    if let Some(column) = matches.value_of("csv-column") {
+        builder.csv_column(column);
+    }
+    if let Some(path) = matches.value_of("json-path") {
+        builder.json_path(path);
+    }
+    if matches.is_present("fixed-field") {
+        match matches.values_of_t::<usize>("fixed-field").as_deref() {
+            Ok([start, end]) => {
+                builder.fixed_field(*start, *end);
+            }
+            _ => {
+                error!("Offsets of the fixed field should be non-negative integers");
+                return Err(());
+            }
+        };
+    }
+    builder.split_all(matches.is_present("split-all"));
+    if matches.is_present("strip-separators") {
+        match matches.value_of_t("strip-separators") {
+            Ok(c) => {
+                builder.strip_separators(c);
+            }
+            _ => {
+                error!("Separator should be a single char");
+                return Err(());
+            }
+        };
+    }
+    if matches.is_present("decimal") {
+        match matches.value_of_t("decimal") {
+            Ok(c) => {
+                builder.decimal(c);
+            }
+            _ => {
+                error!("Decimal point should be a single char");
+                return Err(());
+            }
+        };
+    }
+    builder.decimal_comma(matches.is_present("decimal-comma"));
+    builder.parse_duration(matches.is_present("durations"));
+    builder.strip_units(matches.is_present("strip-units"));
+    if matches.is_present("radix") {
+        match matches.value_of_t("radix") {
+            Ok(radix) => {
+                builder.radix(radix);
+            }
+            _ => {
+                error!("Radix should be a positive integer");
+                return Err(());
+            }
+        };
+    }
+    // Only hist supports weighted input
+    builder.weighted(matches.try_contains_id("weighted").unwrap_or(false));
+    if matches.is_present("comment-char") {
+        match matches.value_of_t("comment-char") {
+            Ok(c) => {
+                builder.comment_char(c);
+            }
+            _ => {
+                error!("Comment char should be a single char");
+                return Err(());
+            }
+        };
+    }
+    for (arg, description) in [
+        ("sample", "Sample size"),
+        ("seed", "Seed"),
+        ("skip-header", "Lines to skip"),
+        ("skip-footer", "Lines to skip"),
+    ] {
+        if matches.is_present(arg) && matches.value_of_t::<u64>(arg).is_err() {
+            error!("{} should be a non-negative integer", description);
+            return Err(());
+        }
+    }
+    if matches.is_present("sample") {
+        builder.sample(matches.value_of_t("sample").unwrap());
+    }
+    if matches.is_present("seed") {
+        builder.seed(matches.value_of_t("seed").unwrap());
+    }
+    if matches.is_present("skip-header") {
+        builder.skip_header(matches.value_of_t("skip-header").unwrap());
+    }
+    if matches.is_present("skip-footer") {
+        builder.skip_footer(matches.value_of_t("skip-footer").unwrap());
+    }
+    builder.progress(matches.is_present("progress"));
+    builder.build().map_err(|err| error!("{}", err))
+}
+
+/// Returns the histogram options set in the command line
+fn get_histogram_options(matches: &ArgMatches) -> Result<plot::HistogramOptions, ()> {
+    let mut options = plot::HistogramOptions::default();
+    let precision_arg: i32 = matches.value_of_t("precision").unwrap();
+    if precision_arg >= 0 {
+        options.precision = Some(precision_arg as usize);
+    };
+    options.log_scale = matches.is_present("log-scale");
+    options.cumulative = matches.is_present("cumulative");
+    options.hide_empty = matches.is_present("hide-empty");
+    options.partial_blocks = matches.is_present("partial-blocks");
+    options.show_percent = matches.is_present("percent");
+    options.show_shape = matches.is_present("shape");
+    options.show_mad = matches.is_present("mad");
+    options.reverse = matches.is_present("reverse");
+    options.density = matches.is_present("density");
+    options.quantile_markers = matches.is_present("markers");
+    options.intensity = matches.is_present("intensity");
+    options.zero_axis = matches.is_present("zero-axis");
+    options.nice = matches.is_present("nice");
+    options.human_counts = matches.is_present("human-counts");
+    options.interval_notation = matches.is_present("interval-notation");
+    options.integer = matches.is_present("integer");
+    options.hide_legend = matches.is_present("hide-legend");
+    options.hide_stats = matches.is_present("hide-stats");
+    match matches.value_of_t("min-bar-len") {
+        Ok(min_bar_len) => options.min_bar_len = min_bar_len,
+        Err(_) => {
+            error!("Minimum bar length should be a non-negative integer");
+            return Err(());
+        }
+    }
+    if matches.is_present("threshold") {
+        match matches.value_of_t("threshold") {
+            Ok(value) => options.threshold = Some(value),
+            Err(_) => {
+                error!("Threshold should be a number");
+                return Err(());
+            }
+        }
+    }
+    if matches.is_present("max-rows") {
+        match matches.value_of_t::<usize>("max-rows") {
+            Ok(rows) if rows > 0 => options.max_rows = Some(rows),
+            _ => {
+                error!("Maximum number of rows should be a positive integer");
+                return Err(());
+            }
+        }
+    }
+    if matches.is_present("mean-ci") {
+        match matches.value_of_t::<f64>("mean-ci") {
+            Ok(level) if level > 0.0 && level < 1.0 => options.mean_ci = Some(level),
+            _ => {
+                error!("Confidence level should be a number between 0 and 1");
+                return Err(());
+            }
+        }
+    }
+    if matches.is_present("highlight") {
+        match matches.values_of_t("highlight") {
+            Ok(values) => options.highlight = values,
+            Err(_) => {
+                error!("Highlighted values should be numbers");
+                return Err(());
+            }
+        }
+    }
+    if matches.is_present("vertical") {
+        options.orientation = plot::Orientation::Vertical;
+        match matches.value_of_t("height") {
+            Ok(height) if height > 0 => options.height = height,
+            _ => {
+                error!("Height should be a positive integer");
+                return Err(());
+            }
+        }
+    }
+    if matches.is_present("log-bars") {
+        options.bar_scale = plot::BarScale::Log;
+    }
+    options.theme = match matches.value_of("theme") {
+        Some("monochrome") => plot::Theme::monochrome(),
+        Some("high-contrast") => plot::Theme::high_contrast(),
+        _ => plot::Theme::default(),
+    };
+    if matches.value_of("intervals") != Some("auto") {
+        options.intervals = matches.value_of_t("intervals").unwrap();
+    }
+    if matches.is_present("scientific") {
+        match matches.value_of_t("scientific") {
+            Ok(threshold) => options.scientific = Some(threshold),
+            _ => {
+                error!("Scientific notation threshold should be a number");
+                return Err(());
+            }
+        }
+    }
+    Ok(options)
 }
 
 /// Implements the hist cli-subcommand
@@ -105,20 +399,141 @@ fn histogram(matches: &ArgMatches) -> i32 {
         Ok(r) => r,
         _ => return 2,
     };
-    let mut vec = reader.read(matches.value_of("input").unwrap());
-    if !assert_data(&vec, 1) {
-        return 1;
+    if matches.is_present("weighted") {
+        return weighted_histogram(matches, &reader);
     }
-    let mut options = plot::HistogramOptions::default();
-    let precision_arg: i32 = matches.value_of_t("precision").unwrap();
-    if precision_arg > 0 {
-        options.precision = Some(precision_arg as usize);
+    if matches.is_present("stream") {
+        return streamed_histogram(matches, &reader);
+    }
+    let paths: Vec<&str> = matches.values_of("input").unwrap().collect();
+    let mut vec = read_unordered(&reader, &paths);
+    report_skipped(matches, &reader);
+    if vec.is_empty() {
+        return no_values(&reader);
+    }
+    let options = match get_histogram_options(matches) {
+        Ok(o) => o,
+        _ => return 2,
     };
-    options.log_scale = matches.is_present("log-scale");
-    options.intervals = matches.value_of_t("intervals").unwrap();
-    let width = matches.value_of_t("width").unwrap();
-    let histogram = plot::Histogram::new(&mut vec, options);
-    print!("{histogram:width$}");
+    let histogram = if matches.is_present("clip") {
+        let (low, high) = match matches.values_of_t::<f64>("clip").as_deref() {
+            Ok([low, high]) => (*low, *high),
+            _ => {
+                error!("Percentiles for clipping should be numbers");
+                return 2;
+            }
+        };
+        if matches.value_of("intervals") == Some("auto") {
+            error!("Automatic number of intervals is not supported when clipping");
+            return 2;
+        }
+        match plot::Histogram::auto_clipped(&mut vec, low, high, options) {
+            Ok(h) => h,
+            Err(err) => {
+                error!("{}", err);
+                return 2;
+            }
+        }
+    } else if matches.value_of("intervals") == Some("auto") {
+        if matches.is_present("geometric") {
+            error!("Automatic number of intervals is not supported with geometric buckets");
+            return 2;
+        }
+        plot::Histogram::auto(&mut vec, options)
+    } else if matches.is_present("geometric") {
+        match plot::Histogram::new_logarithmic(&mut vec, options) {
+            Ok(h) => h,
+            Err(err) => {
+                error!("{}", err);
+                return 1;
+            }
+        }
+    } else {
+        plot::Histogram::new(&mut vec, options)
+    };
+    let histogram = if reader.total() > vec.len() {
+        histogram.with_sampled_from(reader.total())
+    } else {
+        histogram
+    };
+    print_histogram(matches, &histogram);
+    0
+}
+
+/// Prints an histogram in the output format given in the command line
+fn print_histogram(matches: &ArgMatches, histogram: &plot::Histogram) {
+    match matches.value_of_t("output-format").unwrap() {
+        plot::OutputFormat::Human => {
+            let width = get_width(matches);
+            print!("{histogram:width$}");
+        }
+        plot::OutputFormat::Tsv => print!("{}", histogram.to_tsv()),
+    }
+}
+
+/// Implements the hist cli-subcommand for buckets between the given minimum
+/// and maximum, filled as values are read
+fn streamed_histogram(matches: &ArgMatches, reader: &read::DataReader) -> i32 {
+    if matches.value_of("intervals") == Some("auto") {
+        error!("Automatic number of intervals is not supported when streaming");
+        return 2;
+    }
+    let options = match get_histogram_options(matches) {
+        Ok(o) => o,
+        _ => return 2,
+    };
+    let range = matches.value_of_t("min").unwrap()..matches.value_of_t("max").unwrap();
+    let paths: Vec<&str> = matches.values_of("input").unwrap().collect();
+    let histogram = plot::Histogram::from_reader(reader, &paths, range, &options);
+    report_skipped(matches, reader);
+    if histogram.is_empty() {
+        return no_values(reader);
+    }
+    print_histogram(matches, &histogram);
+    0
+}
+
+/// Implements the hist cli-subcommand for `value weight` input lines
+fn weighted_histogram(matches: &ArgMatches, reader: &read::DataReader) -> i32 {
+    if matches.value_of("intervals") == Some("auto") {
+        error!("Automatic number of intervals is not supported with weighted input");
+        return 2;
+    }
+    let paths: Vec<&str> = matches.values_of("input").unwrap().collect();
+    let pairs = reader.read_weighted(&paths);
+    report_skipped(matches, reader);
+    if pairs.is_empty() {
+        return no_values(reader);
+    }
+    let mut options = match get_histogram_options(matches) {
+        Ok(o) => o,
+        _ => return 2,
+    };
+    options.intervals = options.intervals.max(1);
+    let mut stats = lowcharts::stats::StatsBuilder::new(options.precision);
+    for (n, weight) in pairs.iter() {
+        stats.push_weighted(*n, *weight);
+    }
+    let stats = stats.finish();
+    let mut histogram = if matches.is_present("geometric") {
+        match plot::Histogram::new_logarithmic_with_stats(stats, &options) {
+            Ok(h) => h,
+            Err(err) => {
+                error!("{}", err);
+                return 1;
+            }
+        }
+    } else {
+        if options.log_scale {
+            error!("Logarithmic scale is not supported with weighted input");
+            return 2;
+        }
+        plot::Histogram::new_with_stats(stats, &options)
+    };
+    for (n, weight) in pairs {
+        histogram.add_weighted(n, weight);
+    }
+    print_histogram(matches, &histogram);
     0
 }
 
@@ -128,9 +543,11 @@ fn plot(matches: &ArgMatches) -> i32 {
         Ok(r) => r,
         _ => return 2,
     };
-    let vec = reader.read(matches.value_of("input").unwrap());
-    if !assert_data(&vec, 1) {
-        return 1;
+    let paths: Vec<&str> = matches.values_of("input").unwrap().collect();
+    let vec = reader.read_files(&paths);
+    report_skipped(matches, &reader);
+    if vec.is_empty() {
+        return no_values(&reader);
     }
     let precision_arg: i32 = matches.value_of_t("precision").unwrap();
     let precision = if precision_arg < 0 {
@@ -140,26 +557,78 @@ fn plot(matches: &ArgMatches) -> i32 {
     };
     let plot = plot::XyPlot::new(
         &vec,
-        matches.value_of_t("width").unwrap(),
+        get_width(matches),
         matches.value_of_t("height").unwrap(),
         precision,
     );
-    print!("{plot}");
+    if matches.is_present("sparkline") {
+        println!("{}", plot.sparkline());
+    } else {
+        print!("{plot}");
+    }
+    0
+}
+
+/// Implements the boxplot cli-subcommand
+fn boxplot(matches: &ArgMatches) -> i32 {
+    let reader = match get_float_reader(matches) {
+        Ok(r) => r,
+        _ => return 2,
+    };
+    let paths: Vec<&str> = matches.values_of("input").unwrap().collect();
+    let mut vec = read_unordered(&reader, &paths);
+    report_skipped(matches, &reader);
+    if vec.is_empty() {
+        return no_values(&reader);
+    }
+    let precision_arg: i32 = matches.value_of_t("precision").unwrap();
+    let precision = if precision_arg < 0 {
+        None
+    } else {
+        Some(precision_arg as usize)
+    };
+    let width: usize = get_width(matches);
+    let boxplot = plot::BoxPlot::new(&mut vec, precision);
+    print!("{boxplot:width$}");
+    0
+}
+
+/// Implements the ecdf cli-subcommand
+fn ecdf(matches: &ArgMatches) -> i32 {
+    let reader = match get_float_reader(matches) {
+        Ok(r) => r,
+        _ => return 2,
+    };
+    let paths: Vec<&str> = matches.values_of("input").unwrap().collect();
+    let mut vec = read_unordered(&reader, &paths);
+    report_skipped(matches, &reader);
+    if vec.is_empty() {
+        return no_values(&reader);
+    }
+    let precision_arg: i32 = matches.value_of_t("precision").unwrap();
+    let precision = if precision_arg < 0 {
+        None
+    } else {
+        Some(precision_arg as usize)
+    };
+    let width: usize = get_width(matches);
+    let ecdf = plot::Ecdf::new(&mut vec, matches.value_of_t("height").unwrap(), precision);
+    print!("{ecdf:width$}");
     0
 }
 
 /// Implements the matches cli-subcommand
 fn matchbar(matches: &ArgMatches) -> i32 {
     let reader = read::DataReader::default();
-    let width = matches.value_of_t("width").unwrap();
-    print!(
-        "{:width$}",
-        reader.read_matches(
-            matches.value_of("input").unwrap(),
-            matches.values_of("match").unwrap().collect()
-        ),
-        width = width
+    let width = get_width(matches);
+    let bar = reader.read_matches(
+        matches.value_of("input").unwrap(),
+        matches.values_of("match").unwrap().collect(),
     );
+    if reader.lines() == 0 {
+        return no_data();
+    }
+    print!("{bar:width$}");
     0
 }
 
@@ -180,17 +649,21 @@ fn common_terms(matches: &ArgMatches) -> i32 {
         builder.regex(Regex::new("(.*)").unwrap());
     };
     let reader = builder.build().unwrap();
-    let width = matches.value_of_t("width").unwrap();
+    let width = get_width(matches);
     let lines = matches.value_of_t("lines").unwrap();
     if lines < 1 {
         error!("You should specify a potitive number of lines");
         return 2;
     };
-    print!(
-        "{:width$}",
-        reader.read_terms(matches.value_of("input").unwrap(), lines),
-        width = width
-    );
+    let terms = reader.read_terms(matches.value_of("input").unwrap(), lines);
+    if terms.terms.is_empty() {
+        if reader.lines() > 0 {
+            error!("0 of {} lines matched the regex", reader.lines());
+            return 1;
+        }
+        return no_data();
+    }
+    print!("{terms:width$}");
     0
 }
 
@@ -211,7 +684,28 @@ fn timehist(matches: &ArgMatches) -> i32 {
     if let Some(as_str) = matches.value_of("format") {
         builder.ts_format(as_str.to_string());
     }
+    match matches.value_of("epoch-unit") {
+        Some("seconds") => {
+            builder.epoch_unit(read::EpochUnit::Seconds);
+        }
+        Some("millis") => {
+            builder.epoch_unit(read::EpochUnit::Millis);
+        }
+        _ => (),
+    };
+    if let Some(as_str) = matches.value_of("timezone") {
+        match as_str.parse() {
+            Ok(tz) => builder.timezone(tz),
+            Err(err) => {
+                error!("{}", err);
+                return 2;
+            }
+        };
+    }
     builder.early_stop(matches.is_present("early-stop"));
+    if let Some(unit) = matches.value_of("group-by") {
+        builder.group_by(unit.parse().unwrap());
+    }
     if let Some(duration) = matches.value_of("duration") {
         match parse_duration(duration) {
             Ok(d) => builder.duration(d),
@@ -221,13 +715,54 @@ fn timehist(matches: &ArgMatches) -> i32 {
             }
         };
     };
-    let width = matches.value_of_t("width").unwrap();
+    if let Some(interval) = matches.value_of("interval") {
+        match parse_duration(interval) {
+            Ok(d) => builder.interval(d),
+            Err(err) => {
+                error!("Failed to parse interval {}: {}", interval, err);
+                return 2;
+            }
+        };
+    };
+    let width = get_width(matches);
     let reader = builder.build().unwrap();
     let vec = reader.read(matches.value_of("input").unwrap());
-    if assert_data(&vec, 2) {
-        let timehist = plot::TimeHistogram::new(matches.value_of_t("intervals").unwrap(), &vec);
-        print!("{timehist:width$}");
+    if matches.is_present("format") && reader.skipped() > 0 {
+        warn!(
+            "{} lines did not match the timestamp format",
+            reader.skipped()
+        );
+    }
+    if vec.is_empty() {
+        return no_timestamps(reader.lines());
+    }
+    if matches.is_present("group-by") {
+        let bars = reader.group(&vec);
+        print!("{bars:width$}");
+        return 0;
+    }
+    if !assert_data(&vec, 2) {
+        return 0;
+    }
+    let timehist = match reader.histogram(matches.value_of_t("intervals").unwrap(), &vec) {
+        Ok(timehist) => timehist,
+        Err(err) => {
+            error!("{}", err);
+            return 2;
+        }
     };
+    let timehist = match matches.value_of_t::<usize>("moving-average") {
+        Ok(window) => timehist.with_moving_average(window),
+        Err(_) if matches.is_present("moving-average") => {
+            error!("Moving average window should be a positive integer");
+            return 2;
+        }
+        Err(_) => timehist,
+    };
+    match matches.value_of_t("output-format").unwrap() {
+        plot::OutputFormat::Human => print!("{timehist:width$}"),
+        plot::OutputFormat::Tsv => print!("{}", timehist.to_tsv()),
+    }
     0
 }
 
@@ -248,10 +783,22 @@ fn splittime(matches: &ArgMatches) -> i32 {
     if let Some(as_str) = matches.value_of("format") {
         builder.ts_format(as_str.to_string());
     }
+    if let Some(as_str) = matches.value_of("timezone") {
+        match as_str.parse() {
+            Ok(tz) => builder.timezone(tz),
+            Err(err) => {
+                error!("{}", err);
+                return 2;
+            }
+        };
+    }
     builder.matches(string_list.iter().map(|s| s.to_string()).collect());
-    let width = matches.value_of_t("width").unwrap();
+    let width = get_width(matches);
     let reader = builder.build().unwrap();
     let vec = reader.read(matches.value_of("input").unwrap());
+    if vec.is_empty() {
+        return no_timestamps(reader.lines());
+    }
     if assert_data(&vec, 2) {
         let timehist = plot::SplitTimeHistogram::new(
             matches.value_of_t("intervals").unwrap(),
@@ -263,6 +810,37 @@ fn splittime(matches: &ArgMatches) -> i32 {
     0
 }
 
+/// Implements the auto cli-subcommand, plotting input with the hist or the
+/// timehist cli-subcommands depending on the kind of values it holds
+fn auto(matches: &ArgMatches) -> i32 {
+    let path = matches.value_of("input").unwrap();
+    if path == "-" {
+        error!("Detecting the kind of input needs a file, use hist or timehist for stdin");
+        return 2;
+    }
+    let sniff = read::sniff(path, SNIFF_LINES);
+    let subcommand = match sniff.kind {
+        read::InputKind::Numbers => "hist",
+        read::InputKind::Timestamps => "timehist",
+    };
+    info!(
+        "Input holds {}, plotting it as `lowcharts {} {}` would",
+        sniff.kind, subcommand, path
+    );
+    if sniff.is_mixed() {
+        warn!(
+            "{} of the first {} lines hold timestamps, those will be skipped",
+            sniff.timestamps, sniff.lines
+        );
+    }
+    let matches = app::get_app().get_matches_from(["lowcharts", subcommand, path]);
+    let (_, subcommand_matches) = matches.subcommand().unwrap();
+    match sniff.kind {
+        read::InputKind::Numbers => histogram(subcommand_matches),
+        read::InputKind::Timestamps => timehist(subcommand_matches),
+    }
+}
+
 fn main() {
     let matches = app::get_app().get_matches();
     configure_output(
@@ -272,10 +850,13 @@ fn main() {
     std::process::exit(match matches.subcommand() {
         Some(("hist", subcommand_matches)) => histogram(subcommand_matches),
         Some(("plot", subcommand_matches)) => plot(subcommand_matches),
+        Some(("boxplot", subcommand_matches)) => boxplot(subcommand_matches),
+        Some(("ecdf", subcommand_matches)) => ecdf(subcommand_matches),
         Some(("matches", subcommand_matches)) => matchbar(subcommand_matches),
         Some(("timehist", subcommand_matches)) => timehist(subcommand_matches),
         Some(("common-terms", subcommand_matches)) => common_terms(subcommand_matches),
         Some(("split-timehist", subcommand_matches)) => splittime(subcommand_matches),
+        Some(("auto", subcommand_matches)) => auto(subcommand_matches),
         _ => unreachable!("Invalid subcommand"),
     });
 }
@@ -320,6 +901,28 @@ mod tests {
         assert_eq!("blue", display);
     }
 
+    #[test]
+    #[serial]
+    fn test_output_no_color() {
+        Paint::enable();
+        env::set_var("NO_COLOR", "1");
+        configure_output("auto", false);
+        let display = format!("{}", Blue.paint("blue"));
+        env::remove_var("NO_COLOR");
+        assert_eq!("blue", display);
+    }
+
+    #[test]
+    #[serial]
+    fn test_output_yes_overrides_no_color() {
+        Paint::enable();
+        env::set_var("NO_COLOR", "1");
+        configure_output("yes", false);
+        let display = format!("{}", Blue.paint("blue"));
+        env::remove_var("NO_COLOR");
+        assert_eq!("\u{1b}[34mblue\u{1b}[0m", display);
+    }
+
     #[test]
     fn test_duration() {
         assert_eq!(
@@ -0,0 +1,193 @@
+use std::fmt;
+
+use yansi::Color::{Blue, Red};
+
+use crate::format::F64Formatter;
+use crate::plot::display_width;
+use crate::stats::Stats;
+
+const OUTLIER_CHAR: char = '•';
+const WHISKER_CHAR: char = '─';
+const BOX_CHAR: char = '▒';
+const MEDIAN_CHAR: char = '┃';
+
+#[derive(Debug)]
+/// A struct holding data to plot a horizontal box-and-whisker plot.
+///
+/// Whiskers extend to the farthest values within 1.5 times the inter-quartile
+/// range from the box, values beyond them are drawn as outliers.
+pub struct BoxPlot {
+    stats: Stats,
+    q1: f64,
+    median: f64,
+    q3: f64,
+    whisker_low: f64,
+    whisker_high: f64,
+    outliers: Vec<f64>,
+    precision: Option<usize>,
+}
+
+impl BoxPlot {
+    /// Creates a BoxPlot from a vector of numerical data.
+    ///
+    /// `precision` is an Option with the number of decimals to display.  If
+    /// "None" is used, human units will be used, with an heuristic based on the
+    /// input data for deciding the units and the decimal places.
+    ///
+    /// NaN and infinite values are left out, as they are of the stats.
+    pub fn new(vec: &mut [f64], precision: Option<usize>) -> Self {
+        let stats = Stats::new(vec, precision);
+        let q1 = stats.percentile(25.0);
+        let median = stats.median();
+        let q3 = stats.percentile(75.0);
+        let iqr = q3 - q1;
+        let fence = (q1 - 1.5 * iqr)..=(q3 + 1.5 * iqr);
+        let mut whisker_low = q1;
+        let mut whisker_high = q3;
+        let mut outliers = Vec::new();
+        for x in vec.iter().filter(|x| x.is_finite()) {
+            if fence.contains(x) {
+                whisker_low = whisker_low.min(*x);
+                whisker_high = whisker_high.max(*x);
+            } else {
+                outliers.push(*x);
+            }
+        }
+        Self {
+            stats,
+            q1,
+            median,
+            q3,
+            whisker_low,
+            whisker_high,
+            outliers,
+            precision,
+        }
+    }
+
+    /// Returns the values drawn as outliers.
+    pub fn outliers(&self) -> &[f64] {
+        &self.outliers
+    }
+
+    // Returns the column where a value is drawn in a plot of `width` columns
+    fn column(&self, value: f64, width: usize) -> usize {
+        let range = self.stats.max - self.stats.min;
+        if range <= 0.0 {
+            return 0;
+        }
+        let column = ((value - self.stats.min) / range * (width - 1) as f64).round() as usize;
+        column.min(width - 1)
+    }
+
+    fn get_line(&self, width: usize) -> String {
+        let mut line = vec![' '; width];
+        let (low, high) = (
+            self.column(self.whisker_low, width),
+            self.column(self.whisker_high, width),
+        );
+        let (q1, q3) = (self.column(self.q1, width), self.column(self.q3, width));
+        line[low..=high].fill(WHISKER_CHAR);
+        line[q1..=q3].fill(BOX_CHAR);
+        line[low] = '├';
+        line[high] = '┤';
+        line[self.column(self.median, width)] = MEDIAN_CHAR;
+        for outlier in self.outliers.iter() {
+            line[self.column(*outlier, width)] = OUTLIER_CHAR;
+        }
+        line.into_iter().collect()
+    }
+}
+
+impl fmt::Display for BoxPlot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = display_width(f, 80);
+        let formatter = match self.precision {
+            None => F64Formatter::new_with_range(self.stats.min..self.stats.max),
+            Some(n) => F64Formatter::new(n),
+        };
+        writeln!(
+            f,
+            "Samples = {len}; Min = {min}; Max = {max}; Outliers = {outliers}",
+            len = Blue.paint(self.stats.samples.to_string()),
+            min = Blue.paint(formatter.format(self.stats.min)),
+            max = Blue.paint(formatter.format(self.stats.max)),
+            outliers = Blue.paint(self.outliers.len().to_string()),
+        )?;
+        writeln!(
+            f,
+            "Whiskers = {low} .. {high}; Q1 = {q1}; Median = {median}; Q3 = {q3}",
+            low = Blue.paint(formatter.format(self.whisker_low)),
+            high = Blue.paint(formatter.format(self.whisker_high)),
+            q1 = Blue.paint(formatter.format(self.q1)),
+            median = Blue.paint(formatter.format(self.median)),
+            q3 = Blue.paint(formatter.format(self.q3)),
+        )?;
+        writeln!(f, "{}", Red.paint(self.get_line(width)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+    use yansi::Paint;
+
+    #[test]
+    fn basic_test() {
+        let mut vec: Vec<f64> = (0..=20).map(|x| x as f64).collect();
+        vec.push(100.0);
+        let boxplot = BoxPlot::new(&mut vec, Some(1));
+        assert_float_eq!(boxplot.q1, 5.25, rmax <= f64::EPSILON);
+        assert_float_eq!(boxplot.median, 10.5, rmax <= f64::EPSILON);
+        assert_float_eq!(boxplot.q3, 15.75, rmax <= f64::EPSILON);
+        assert_float_eq!(boxplot.whisker_low, 0.0, rmax <= f64::EPSILON);
+        assert_float_eq!(boxplot.whisker_high, 20.0, rmax <= f64::EPSILON);
+        assert_eq!(boxplot.outliers(), [100.0]);
+    }
+
+    #[test]
+    fn display_test() {
+        let mut vec = [0.0, 2.0, 4.0, 5.0, 6.0, 8.0, 10.0];
+        let boxplot = BoxPlot::new(&mut vec, Some(1));
+        Paint::disable();
+        let display = format!("{boxplot:11}");
+        assert!(display.contains("Samples = 7; Min = 0.0; Max = 10.0; Outliers = 0\n"));
+        assert!(display.contains("Whiskers = 0.0 .. 10.0; Q1 = 3.0; Median = 5.0; Q3 = 7.0\n"));
+        let line = display.lines().last().unwrap();
+        assert_eq!(line, "├──▒▒┃▒▒──┤");
+        assert_eq!(line.chars().position(|c| c == MEDIAN_CHAR), Some(5));
+    }
+
+    #[test]
+    fn display_test_outliers() {
+        let mut vec = [1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 5.0, 20.0];
+        let boxplot = BoxPlot::new(&mut vec, Some(1));
+        Paint::disable();
+        let display = format!("{boxplot:20}");
+        let line = display.lines().last().unwrap();
+        assert_eq!(line, "├▒┃▒┤              •");
+    }
+
+    #[test]
+    fn single_value() {
+        let boxplot = BoxPlot::new(&mut [3.0, 3.0], None);
+        Paint::disable();
+        let display = format!("{boxplot:5}");
+        assert_eq!(display.lines().last().unwrap(), "┃    ");
+    }
+
+    #[test]
+    fn non_finite_test() {
+        let mut vec = [1.0, f64::NAN, 2.0, f64::INFINITY, 3.0, f64::NEG_INFINITY];
+        let boxplot = BoxPlot::new(&mut vec, Some(1));
+        assert!(boxplot.outliers().is_empty());
+        assert_float_eq!(boxplot.whisker_low, 1.0, rmax <= f64::EPSILON);
+        assert_float_eq!(boxplot.whisker_high, 3.0, rmax <= f64::EPSILON);
+        assert_eq!(boxplot.column(f64::INFINITY, 20), 19);
+        Paint::disable();
+        let display = format!("{boxplot:20}");
+        assert!(display.contains("Samples = 3; Min = 1.0; Max = 3.0; Outliers = 0\n"));
+        assert_eq!(display.lines().last().unwrap().chars().count(), 20);
+    }
+}
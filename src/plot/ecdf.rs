@@ -0,0 +1,146 @@
+use std::fmt;
+
+use yansi::Color::Blue;
+
+use crate::format::F64Formatter;
+use crate::plot::display_width;
+use crate::plot::xy::print_line;
+use crate::stats::Stats;
+
+#[derive(Debug)]
+/// A struct holding data to plot the empirical cumulative distribution
+/// function (ECDF) of a set of numerical values: the fraction of values that
+/// are less or equal than every value in the range of input data.
+pub struct Ecdf {
+    sorted: Vec<f64>,
+    stats: Stats,
+    height: usize,
+    precision: Option<usize>,
+}
+
+impl Ecdf {
+    /// Creates an Ecdf from a vector of numerical data.
+    ///
+    /// `height` is the number of "rows" to display.  The number of "columns"
+    /// depends on the width used for the display.
+    ///
+    /// `precision` is an Option with the number of decimals to display.  If
+    /// "None" is used, human units will be used, with an heuristic based on the
+    /// input data for deciding the units and the decimal places.
+    pub fn new(vec: &mut [f64], height: usize, precision: Option<usize>) -> Self {
+        let stats = Stats::new(vec, precision);
+        Self {
+            sorted: vec.iter().copied().filter(|x| x.is_finite()).collect(),
+            stats,
+            height: height.max(1),
+            precision,
+        }
+    }
+
+    /// Returns the fraction of input values that are less or equal than `x`.
+    pub fn fraction(&self, x: f64) -> f64 {
+        self.sorted.partition_point(|v| *v <= x) as f64 / self.sorted.len() as f64
+    }
+
+    // Returns the value at every one of `columns` columns
+    fn column_values(&self, columns: usize) -> Vec<f64> {
+        let step = match columns {
+            1 => 0.0,
+            _ => (self.stats.max - self.stats.min) / (columns - 1) as f64,
+        };
+        (0..columns)
+            .map(|i| step.mul_add(i as f64, self.stats.min))
+            .collect()
+    }
+
+    // Returns the first column whose fraction reaches `q`
+    fn crossing(&self, values: &[f64], q: f64) -> usize {
+        values
+            .iter()
+            .position(|x| self.fraction(*x) >= q)
+            .unwrap_or(values.len() - 1)
+    }
+}
+
+impl fmt::Display for Ecdf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let formatter = match self.precision {
+            None => F64Formatter::new_with_range(self.stats.min..self.stats.max),
+            Some(n) => F64Formatter::new(n),
+        };
+        let y_formatter = F64Formatter::new(2);
+        let y_width = 4;
+        // 3 chars are used for the brackets and space around labels
+        let columns = display_width(f, 80).saturating_sub(y_width + 3).max(1);
+        let values = self.column_values(columns);
+        let fractions: Vec<f64> = values.iter().map(|x| self.fraction(*x)).collect();
+        writeln!(
+            f,
+            "Samples = {len}; Min = {min}; Max = {max}",
+            len = Blue.paint(self.stats.samples.to_string()),
+            min = Blue.paint(formatter.format(self.stats.min)),
+            max = Blue.paint(formatter.format(self.stats.max)),
+        )?;
+        let step = 1.0 / self.height as f64;
+        let mut y_axis: Vec<f64> = (0..self.height).map(|y| y as f64 * step).collect();
+        y_axis.reverse();
+        print_line(
+            f,
+            &fractions,
+            y_axis[0]..f64::INFINITY,
+            y_width,
+            &y_formatter,
+        )?;
+        for y in y_axis.windows(2) {
+            print_line(f, &fractions, y[1]..y[0], y_width, &y_formatter)?;
+        }
+        let mut marks = vec![' '; columns];
+        for q in [0.5, 0.9, 0.99] {
+            marks[self.crossing(&values, q)] = '^';
+        }
+        writeln!(
+            f,
+            "{:width$}{}",
+            "",
+            marks.into_iter().collect::<String>(),
+            width = y_width + 3
+        )?;
+        writeln!(
+            f,
+            "Marks at p50 = {p50}; p90 = {p90}; p99 = {p99}",
+            p50 = Blue.paint(formatter.format(self.stats.median())),
+            p90 = Blue.paint(formatter.format(self.stats.percentile(90.0))),
+            p99 = Blue.paint(formatter.format(self.stats.percentile(99.0))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+    use yansi::Paint;
+
+    #[test]
+    fn fraction_test() {
+        let ecdf = Ecdf::new(&mut [3.0, 1.0, 2.0, 2.0], 4, None);
+        assert_float_eq!(ecdf.fraction(0.0), 0.0, rmax <= f64::EPSILON);
+        assert_float_eq!(ecdf.fraction(1.0), 0.25, rmax <= f64::EPSILON);
+        assert_float_eq!(ecdf.fraction(1.5), 0.25, rmax <= f64::EPSILON);
+        assert_float_eq!(ecdf.fraction(2.0), 0.75, rmax <= f64::EPSILON);
+        assert_float_eq!(ecdf.fraction(3.0), 1.0, rmax <= f64::EPSILON);
+    }
+
+    #[test]
+    fn display_test() {
+        let mut vec: Vec<f64> = (0..10).map(|x| x as f64).collect();
+        let ecdf = Ecdf::new(&mut vec, 2, Some(1));
+        Paint::disable();
+        let display = format!("{ecdf:17}");
+        assert!(display.contains("Samples = 10; Min = 0.0; Max = 9.0\n"));
+        assert!(display.contains("[0.50]     ●●●●●●\n"));
+        assert!(display.contains("[0.00] ●●●●      \n"));
+        assert!(display.contains("\n           ^   ^^\n"));
+        assert!(display.contains("Marks at p50 = 4.5; p90 = 8.1; p99 = 8.9\n"));
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt;
+
+use yansi::Color::{self, Blue, Green, Red, Yellow};
+use yansi::Paint;
+
+use crate::format::{intensity_level, F64Formatter, INTENSITY_CHARS};
+use crate::plot::display_width;
+use crate::stats::Stats;
+
+// Colors for increasing densities
+const DENSITY_COLORS: &[Color] = &[Blue, Green, Yellow, Red];
+
+#[derive(Debug)]
+/// A struct holding data to plot a heatmap of (x, y) pairs: both axes are
+/// split in buckets, and every cell of the resulting grid is shaded according
+/// to the number of pairs in it.
+pub struct Heatmap {
+    points: Vec<(f64, f64)>,
+    x_stats: Stats,
+    y_stats: Stats,
+    rows: usize,
+    precision: Option<usize>,
+}
+
+impl Heatmap {
+    /// Creates a Heatmap from a vector of (x, y) pairs.
+    ///
+    /// `rows` is the number of buckets for the y axis.  The number of buckets
+    /// for the x axis depends on the width used for the display.
+    ///
+    /// `precision` is an Option with the number of decimals to display.  If
+    /// "None" is used, human units will be used, with an heuristic based on the
+    /// input data for deciding the units and the decimal places.
+    pub fn new(points: &[(f64, f64)], rows: usize, precision: Option<usize>) -> Self {
+        let mut xs: Vec<f64> = points.iter().map(|p| p.0).collect();
+        let mut ys: Vec<f64> = points.iter().map(|p| p.1).collect();
+        Self {
+            points: points.to_vec(),
+            x_stats: Stats::new(&mut xs, precision),
+            y_stats: Stats::new(&mut ys, precision),
+            rows: rows.max(1),
+            precision,
+        }
+    }
+
+    /// Returns the counts of pairs in a grid of `columns` x `rows` cells,
+    /// indexed by row (from lowest y values) and then by column.
+    pub fn grid(&self, columns: usize) -> Vec<Vec<usize>> {
+        let columns = columns.max(1);
+        let mut grid = vec![vec![0; columns]; self.rows];
+        for (x, y) in self.points.iter() {
+            let column = find_slot(*x, &self.x_stats, columns);
+            let row = find_slot(*y, &self.y_stats, self.rows);
+            grid[row][column] += 1;
+        }
+        grid
+    }
+}
+
+// Returns the bucket where a value falls when splitting the range of values
+// in `buckets` buckets of the same size.
+fn find_slot(value: f64, stats: &Stats, buckets: usize) -> usize {
+    let range = stats.max - stats.min;
+    if range <= 0.0 {
+        return 0;
+    }
+    (((value - stats.min) / range * buckets as f64) as usize).min(buckets - 1)
+}
+
+impl fmt::Display for Heatmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (formatter, x_formatter) = match self.precision {
+            None => (
+                F64Formatter::new_with_range(self.y_stats.min..self.y_stats.max),
+                F64Formatter::new_with_range(self.x_stats.min..self.x_stats.max),
+            ),
+            Some(n) => (F64Formatter::new(n), F64Formatter::new(n)),
+        };
+        let step = (self.y_stats.max - self.y_stats.min) / self.rows as f64;
+        let labels: Vec<String> = (0..self.rows)
+            .map(|row| formatter.format(step.mul_add(row as f64, self.y_stats.min)))
+            .collect();
+        let label_width = labels.iter().map(|l| l.len()).max().unwrap_or(1);
+        // 3 chars are used for the brackets and space around labels
+        let columns = display_width(f, 80).saturating_sub(label_width + 3).max(1);
+        let grid = self.grid(columns);
+        let top = grid.iter().flatten().copied().max().unwrap_or(0);
+        writeln!(
+            f,
+            "Pairs = {len}; X = {x_min} .. {x_max}; Max count per cell = {top}",
+            len = Blue.paint(self.points.len()),
+            x_min = Blue.paint(x_formatter.format(self.x_stats.min)),
+            x_max = Blue.paint(x_formatter.format(self.x_stats.max)),
+            top = Blue.paint(top),
+        )?;
+        for (row, label) in grid.iter().zip(labels.iter()).rev() {
+            let cells: String = row.iter().map(|count| shade(*count, top)).collect();
+            writeln!(
+                f,
+                "[{}] {}",
+                Blue.paint(format!("{:>width$}", label, width = label_width)),
+                cells
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Returns the representation of a cell with `count` pairs, in a grid where
+// the cell with most pairs has `top` of them.
+fn shade(count: usize, top: usize) -> String {
+    if count == 0 {
+        return String::from(" ");
+    }
+    let level = intensity_level(count, top);
+    if Paint::is_enabled() {
+        DENSITY_COLORS[level].paint('█').to_string()
+    } else {
+        INTENSITY_CHARS[level].to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_test() {
+        let points = [
+            (0.0, 0.0),
+            (0.1, 0.2),
+            (1.0, 1.0),
+            (2.0, 4.0),
+            (3.9, 4.0),
+            (4.0, 4.0),
+        ];
+        let heatmap = Heatmap::new(&points, 2, None);
+        let grid = heatmap.grid(4);
+        assert_eq!(grid, vec![vec![2, 1, 0, 0], vec![0, 0, 1, 2]]);
+    }
+
+    #[test]
+    fn display_test() {
+        let mut points = vec![(0.0, 0.0); 4];
+        points.extend([(1.0, 0.0), (2.0, 10.0), (2.0, 10.0), (3.0, 5.0)]);
+        let heatmap = Heatmap::new(&points, 2, Some(1));
+        Paint::disable();
+        let display = format!("{heatmap:11}");
+        assert!(display.contains("Pairs = 8; X = 0.0 .. 3.0; Max count per cell = 4\n"));
+        assert!(display.contains("[5.0]    ▒░\n"));
+        assert!(display.contains("[0.0] █░   \n"));
+    }
+
+    #[test]
+    fn display_ranges_test() {
+        let heatmap = Heatmap::new(&[(1.6e9, 0.001), (1.6e9 + 100.0, 0.005)], 2, None);
+        Paint::disable();
+        let display = format!("{heatmap:40}");
+        assert!(display.contains("X = 1600000000.00 .. 1600000100.00;"));
+        assert!(display.contains("[0.00100] █"));
+    }
+
+    #[test]
+    fn single_point() {
+        let heatmap = Heatmap::new(&[(1.0, 1.0)], 3, None);
+        assert_eq!(heatmap.grid(2), vec![vec![1, 0], vec![0, 0], vec![0, 0]]);
+    }
+}
@@ -1,12 +1,42 @@
 use std::fmt;
+use std::io;
 use std::ops::Range;
 
-use yansi::Color::Blue;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use yansi::Color::{self, Cyan, Magenta, Red, Yellow};
+use yansi::Paint;
 
-use crate::format::{F64Formatter, HorizontalScale};
-use crate::stats::Stats;
+use crate::format::{
+    format_si, intensity_level, F64Formatter, HorizontalScale, IoWriter, BAR_CHAR, INTENSITY_CHARS,
+};
+use crate::plot::{display_width, Theme};
+use crate::read::DataReader;
+use crate::stats::{quantile, Stats, StatsBuilder};
 
-#[derive(Debug)]
+// Char drawn for the zero axis of histograms centered on zero.
+const ZERO_AXIS_CHAR: char = '┆';
+// Color of the chars marking quantiles in bars.
+const MARKER_COLOR: Color = Cyan;
+// Char and color marking the rows of highlighted buckets.
+const HIGHLIGHT_CHAR: char = '◀';
+const HIGHLIGHT_COLOR: Color = Magenta;
+// Colors of the bars of the two histograms of a comparison.
+const COMPARISON_COLORS: [Color; 2] = [Red, Yellow];
+// Mantissas of the steps of histograms with nice bucket edges.
+const NICE_MANTISSAS: [f64; 4] = [1.0, 2.0, 2.5, 5.0];
+// Cap for the number of buckets chosen automatically.
+const MAX_AUTO_INTERVALS: usize = 100;
+// Length of the longest bar in markdown tables.
+const MARKDOWN_BAR_LEN: usize = 50;
+// Space (in pixels) reserved for axis labels in SVG charts.
+const SVG_LEFT_MARGIN: usize = 50;
+const SVG_BOTTOM_MARGIN: usize = 30;
+const SVG_TOP_MARGIN: usize = 10;
+
+/// Colors used for the bars of SVG histograms, unless a palette is provided.
+pub const DEFAULT_SVG_PALETTE: &[&str] = &["#d62728"];
+
+#[derive(Debug, serde::Serialize)]
 /// A struct that represents a bucket of an histogram.
 struct Bucket {
     range: Range<f64>,
@@ -18,25 +48,98 @@ impl Bucket {
         Self { range, count: 0 }
     }
 
-    fn inc(&mut self) {
-        self.count += 1;
+    fn inc(&mut self, weight: usize) {
+        self.count += weight;
+    }
+
+    // Returns the count divided by the total count and the bucket width
+    fn density(&self, total: usize) -> f64 {
+        let width = self.range.end - self.range.start;
+        if total == 0 || width <= 0.0 {
+            return 0.0;
+        }
+        self.count as f64 / (total as f64 * width)
+    }
+}
+
+// Returns `k` times `mantissa` times 10 to the `exponent`, dividing by powers
+// of ten when possible so that multiples of steps like 0.1 are exact.
+fn nice_multiple(k: f64, mantissa: f64, exponent: i32) -> f64 {
+    if exponent >= 0 {
+        k * mantissa * 10_f64.powi(exponent)
+    } else {
+        k * mantissa / 10_f64.powi(-exponent)
+    }
+}
+
+// Formats a density with 4 significant digits
+fn format_density(density: f64) -> String {
+    if density <= 0.0 || !density.is_finite() {
+        return String::from("0");
+    }
+    let decimals = (3 - density.log10().floor() as i32).max(0) as usize;
+    format!("{density:.decimals$}")
+}
+
+/// The error returned when merging histograms with different buckets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeError(String);
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cannot merge histograms: {}", self.0)
     }
 }
 
+impl std::error::Error for MergeError {}
+
 /// A struct representing the options to build an histogram.
 pub struct Histogram {
     vec: Vec<Bucket>,
     step: f64,
     // Maximum of all bucket counts
     top: usize,
+    // Sum of all bucket counts
+    total: usize,
+    // Number of values that were below or above the range of the buckets
+    underflow: usize,
+    overflow: usize,
+    // Lowest of the values below the range of the buckets (infinity if none)
+    underflow_min: f64,
     last: usize,
     stats: Stats,
-    log_scale: bool,
-    precision: Option<usize>, // If None, then human friendly display will be used
+    // If true, bucket ranges grow geometrically and `step` is the logarithm
+    // of the ratio between consecutive bucket edges
+    geometric: bool,
+    // Number of input values, if data is a sample of them
+    sampled_from: Option<usize>,
+    options: HistogramOptions,
+}
+
+/// The scale used for drawing the length of the bars of an histogram.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BarScale {
+    /// Bar length is proportional to the count of the bucket.
+    #[default]
+    Linear,
+    /// Bar length is proportional to `ln(count + 1)`, so that buckets with
+    /// small counts are still visible next to a dominant one.
+    Log,
+}
+
+/// The orientation of the bars of an histogram.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// Every bucket is a row, with a bar growing rightward.
+    #[default]
+    Horizontal,
+    /// Every bucket is a column growing upward, with bucket edges printed
+    /// beneath.
+    Vertical,
 }
 
 /// A struct holding data to plot a Histogram of numerical data.
-#[derive(Default)]
+#[derive(Clone)]
 pub struct HistogramOptions {
     /// `intervals` is the number of histogram buckets to display (capped to the
     /// length of input data).
@@ -47,6 +150,144 @@ pub struct HistogramOptions {
     /// "None" is used, human units will be used, with an heuristic based on the
     /// input data for deciding the units and the decimal places.
     pub precision: Option<usize>,
+    /// Scale used for the length of the bars (linear by default).
+    pub bar_scale: BarScale,
+    /// If true, every bucket displays the count of values in it and in any
+    /// lower bucket (so the last one displays the total count).
+    pub cumulative: bool,
+    /// If true, buckets with no values are not displayed; every run of them
+    /// is collapsed into a single line.
+    pub hide_empty: bool,
+    /// If true, bars are drawn using block glyphs with a resolution of an
+    /// eighth of a char (rounded to full blocks if colors are disabled).
+    pub partial_blocks: bool,
+    /// Char used for drawing the bars (`∎` by default).
+    pub glyph: char,
+    /// Colors used for drawing the histogram.
+    pub theme: Theme,
+    /// If true, display the share of the total count of every bucket.
+    pub show_percent: bool,
+    /// If present, bucket edges whose magnitude is at least this are printed
+    /// in scientific notation (with `precision` decimals, or 3 by default).
+    pub scientific: Option<f64>,
+    /// If true, skewness and kurtosis are displayed along with other stats.
+    pub show_shape: bool,
+    /// If true, the median absolute deviation is displayed along with other
+    /// stats.
+    pub show_mad: bool,
+    /// If present, the confidence interval for the mean at this level (like
+    /// 0.95) is displayed along with other stats.
+    pub mean_ci: Option<f64>,
+    /// If true, buckets are displayed from the highest range to the lowest.
+    pub reverse: bool,
+    /// If true, every bucket displays its density (its count divided by the
+    /// total count and by its width) instead of its count, so that the area
+    /// of bars adds up to 1.  `cumulative` is ignored in this mode.
+    pub density: bool,
+    /// If true, the median, p90 and p99 are marked in the bars of the
+    /// buckets holding them, replacing the char at a position proportional
+    /// to where they fall in the range of the bucket.
+    pub quantile_markers: bool,
+    /// If true and colors are disabled, bars are drawn with glyphs of
+    /// increasing intensity (`░▒▓█`) according to how close their length is
+    /// to the one of the longest bar.
+    pub intensity: bool,
+    /// If true, the space for bars is split by a vertical axis for zero:
+    /// bars of buckets with no positive values extend leftward from it, and
+    /// the rest of them rightward.
+    pub zero_axis: bool,
+    /// Orientation of the bars (horizontal by default).  Vertical columns
+    /// only display counts (cumulative ones, if `cumulative` is set), and
+    /// options on how bars are drawn are ignored for them.
+    pub orientation: Orientation,
+    /// Number of rows of the highest column, for vertical histograms.
+    pub height: usize,
+    /// If true, bucket edges are multiples of a "nice" step (1, 2, 2.5 or 5
+    /// times a power of ten), so buckets may cover a wider range than data
+    /// and be less than `intervals`.  Ignored with logarithmic scale.
+    pub nice: bool,
+    /// If true, counts of 1000 or more are displayed with a SI suffix (like
+    /// `1.2M`) instead of exactly.  Exports (like `to_tsv`) are not affected.
+    pub human_counts: bool,
+    /// Minimum length of the bars of non-empty buckets, so that they can be
+    /// told apart from empty ones even when they are short for the scale.
+    /// Zero (the default) keeps bar lengths strictly proportional.
+    pub min_bar_len: usize,
+    /// Values whose buckets are highlighted, by marking their rows.  Values
+    /// out of the range of the data are ignored.
+    pub highlight: Vec<f64>,
+    /// If true (the default), the last bucket includes its upper edge, so
+    /// that it holds the maximum.  Otherwise values on that edge are tallied
+    /// as overflow, like the ones above it.
+    pub closed_last: bool,
+    /// If true, the ranges of buckets are displayed in interval notation
+    /// (like `[1.0, 2.0)`), telling apart the edges they include from the
+    /// ones they do not.
+    pub interval_notation: bool,
+    /// If true, values are taken as integers (rounding them down), and
+    /// buckets split them in groups of the same number of integers, so that
+    /// edges are whole numbers and values on them always fall in the same
+    /// bucket.  Ignored with logarithmic scale.
+    pub integer: bool,
+    /// If present and not zero, the width used for the display, overriding
+    /// the one of the formatter (110 by default), so that the output does
+    /// not depend on where it is displayed.
+    pub width: Option<usize>,
+    /// If present, the maximum number of rows of buckets displayed: when
+    /// there are more buckets, every row merges 2, 4, 8 (or more) adjacent
+    /// ones, adding up their counts, so that they fit.  Only the display is
+    /// affected, and vertical columns are never merged.
+    pub max_rows: Option<usize>,
+    /// If present, a target value (like the one of a SLA): the share of
+    /// values at most it is displayed, and a divider is drawn after the
+    /// bucket holding it, between passing and failing buckets.
+    pub threshold: Option<f64>,
+    /// If true, the legend on what bars represent (and on merged buckets) is
+    /// not displayed.
+    pub hide_legend: bool,
+    /// If true, the stats of data (and the counts of values out of range) are
+    /// not displayed above buckets, for a bare chart.
+    pub hide_stats: bool,
+}
+
+impl Default for HistogramOptions {
+    fn default() -> Self {
+        Self {
+            intervals: 0,
+            log_scale: false,
+            precision: None,
+            bar_scale: BarScale::Linear,
+            cumulative: false,
+            hide_empty: false,
+            partial_blocks: false,
+            glyph: BAR_CHAR,
+            theme: Theme::default(),
+            show_percent: false,
+            scientific: None,
+            show_shape: false,
+            show_mad: false,
+            mean_ci: None,
+            reverse: false,
+            density: false,
+            quantile_markers: false,
+            intensity: false,
+            zero_axis: false,
+            orientation: Orientation::Horizontal,
+            height: 10,
+            nice: false,
+            human_counts: false,
+            min_bar_len: 0,
+            highlight: Vec::new(),
+            closed_last: true,
+            interval_notation: false,
+            integer: false,
+            width: None,
+            max_rows: None,
+            threshold: None,
+            hide_legend: false,
+            hide_stats: false,
+        }
+    }
 }
 
 impl Histogram {
@@ -54,12 +295,92 @@ impl Histogram {
     ///
     /// `options` is a `HistogramOptions` struct with the preferences to create
     /// histogram.
-    pub fn new(vec: &mut [f64], mut options: HistogramOptions) -> Self {
-        let mut stats = Stats::new(vec, options.precision);
+    pub fn new(vec: &mut [f64], options: HistogramOptions) -> Self {
+        let stats = Stats::new(vec, options.precision);
+        Self::new_with_data(vec, stats, options)
+    }
+
+    /// Creates a Histogram from a vector of numerical data, choosing the
+    /// number of buckets automatically.
+    ///
+    /// Bucket width follows the Freedman–Diaconis rule (twice the
+    /// interquartile range divided by the cube root of the number of values),
+    /// falling back to Sturges' rule (`log2(n) + 1` buckets) when the
+    /// interquartile range is zero.  The number of buckets is capped to 100,
+    /// and `options.intervals` is ignored.  The chosen step is available via
+    /// the `step` method.
+    pub fn auto(vec: &mut [f64], mut options: HistogramOptions) -> Self {
+        // Stats::new sorts the data, as needed by auto_intervals
+        let stats = Stats::new(vec, options.precision);
+        options.intervals = Self::auto_intervals(vec, &stats);
+        let histogram = Self::new_with_data(vec, stats, options);
+        debug!(
+            "Using {} buckets with a step of {}",
+            histogram.vec.len(),
+            histogram.step
+        );
+        histogram
+    }
+
+    /// Creates a Histogram from a vector of numerical data, whose buckets
+    /// only split the range between the `low_pct` and `high_pct` percentiles
+    /// (in the `[0, 100]` range) of the data, so that outliers do not squash
+    /// the bulk of it into a few buckets.  Values out of that range are
+    /// tallied as underflow or overflow, and the stats are those of the rest.
+    ///
+    /// An error is returned if `low_pct` is not lower than `high_pct`, or if
+    /// any of them is out of range.
+    pub fn auto_clipped(
+        vec: &mut [f64],
+        low_pct: f64,
+        high_pct: f64,
+        mut options: HistogramOptions,
+    ) -> Result<Self, String> {
+        if !(0.0..high_pct).contains(&low_pct) || high_pct > 100.0 {
+            return Err(format!(
+                "Invalid percentiles for clipping: {} and {}",
+                low_pct, high_pct
+            ));
+        }
+        // Stats::new sorts the data, so that the kept values are a slice
+        let stats = Stats::new(vec, options.precision);
+        if stats.samples == 0 {
+            return Ok(Self::new_with_data(vec, stats, options));
+        }
+        let range = stats.percentile(low_pct)..stats.percentile(high_pct);
+        // Non-finite values are sorted to the ends, and never kept
+        let first = vec.iter().position(|x| *x >= range.start).unwrap_or(0);
+        let last = vec.iter().rposition(|x| *x <= range.end).unwrap_or(0);
+        let kept = first..(last + 1).max(first);
+        options.intervals = options.intervals.clamp(1, kept.len().max(1));
+        let mut frame = StatsBuilder::new(options.precision);
+        frame.push(if options.log_scale { 0.0 } else { range.start });
+        frame.push(range.end);
+        let mut histogram = Self::new_with_stats(frame.finish(), &options);
+        histogram.load(vec);
+        let mut stats = Stats::new(&mut vec[kept], options.precision);
+        stats.min = histogram.stats.min;
+        stats.max = histogram.stats.max;
+        histogram.stats = Self::shaped(stats, &options);
+        Ok(histogram)
+    }
+
+    fn auto_intervals(sorted: &[f64], stats: &Stats) -> usize {
+        let n = sorted.len() as f64;
+        let iqr = quantile(sorted, 0.75) - quantile(sorted, 0.25);
+        let intervals = if iqr > 0.0 {
+            ((stats.max - stats.min) / (2.0 * iqr * n.powf(-1.0 / 3.0))).ceil()
+        } else {
+            n.log2().ceil() + 1.0
+        };
+        (intervals as usize).clamp(1, MAX_AUTO_INTERVALS)
+    }
+
+    fn new_with_data(vec: &[f64], mut stats: Stats, mut options: HistogramOptions) -> Self {
         if options.log_scale {
             stats.min = 0.0; // We will silently discard negative values
         }
-        options.intervals = options.intervals.clamp(1, vec.len());
+        options.intervals = options.intervals.clamp(1, vec.len().max(1));
         let mut histogram = Self::new_with_stats(stats, &options);
         histogram.load(vec);
         histogram
@@ -71,6 +392,17 @@ impl Histogram {
     /// named `stats` is needed to decide how future data (to be injected with
     /// the load method) will be accommodated.
     pub fn new_with_stats(stats: Stats, options: &HistogramOptions) -> Self {
+        let finite = stats.min.is_finite() && stats.max.is_finite();
+        if !options.log_scale && options.integer && finite {
+            return Self::new_integer(stats, options);
+        }
+        if !options.log_scale && stats.min == stats.max {
+            return Self::new_single_value(stats, options);
+        }
+        let spread = stats.max - stats.min;
+        if !options.log_scale && options.nice && spread.is_finite() && spread > 0.0 {
+            return Self::new_nice(stats, options);
+        }
         let step = if options.log_scale {
             f64::NAN
         } else {
@@ -80,13 +412,254 @@ impl Histogram {
             vec: Self::build_buckets(stats.min..stats.max, options),
             step,
             top: 0,
+            total: 0,
+            underflow: 0,
+            overflow: 0,
+            underflow_min: f64::INFINITY,
             last: options.intervals - 1,
-            stats,
-            log_scale: options.log_scale,
-            precision: options.precision,
+            stats: Self::shaped(stats, options),
+            geometric: false,
+            sampled_from: None,
+            options: options.clone(),
+        }
+    }
+
+    // All values are the same, so buckets would have no width: a single
+    // bucket is used instead, widened by 0.5 at each side of the value.
+    fn new_single_value(stats: Stats, options: &HistogramOptions) -> Self {
+        let options = HistogramOptions {
+            intervals: 1,
+            ..options.clone()
+        };
+        Self {
+            vec: vec![Bucket::new(stats.min - 0.5..stats.max + 0.5)],
+            step: 1.0,
+            top: 0,
+            total: 0,
+            underflow: 0,
+            overflow: 0,
+            underflow_min: f64::INFINITY,
+            last: 0,
+            stats: Self::shaped(stats, &options),
+            geometric: false,
+            sampled_from: None,
+            options,
+        }
+    }
+
+    // Buckets hold `step` integers each, from the one of the minimum to the
+    // one of the maximum.
+    fn new_integer(stats: Stats, options: &HistogramOptions) -> Self {
+        let first = stats.min.floor() as i64;
+        let span = stats.max.floor() as i64 - first + 1;
+        let step = (span + options.intervals as i64 - 1) / options.intervals as i64;
+        let intervals = ((span + step - 1) / step) as usize;
+        let vec = (0..intervals as i64)
+            .map(|i| Bucket::new((first + i * step) as f64..(first + (i + 1) * step) as f64))
+            .collect();
+        let options = HistogramOptions {
+            intervals,
+            ..options.clone()
+        };
+        Self {
+            vec,
+            step: step as f64,
+            top: 0,
+            total: 0,
+            underflow: 0,
+            overflow: 0,
+            underflow_min: f64::INFINITY,
+            last: intervals - 1,
+            stats: Self::shaped(stats, &options),
+            geometric: false,
+            sampled_from: None,
+            options,
+        }
+    }
+
+    // Buckets have edges on multiples of the smallest nice step that covers
+    // the range of data in no more than `options.intervals` buckets.
+    fn new_nice(stats: Stats, options: &HistogramOptions) -> Self {
+        let raw_step = (stats.max - stats.min) / options.intervals as f64;
+        let mut exponent = raw_step.log10().floor() as i32;
+        let mut mantissa = 0;
+        loop {
+            let multiple = |k: f64| nice_multiple(k, NICE_MANTISSAS[mantissa], exponent);
+            let step = multiple(1.0);
+            if step >= raw_step {
+                // Float rounding may leave the data out of the first or last
+                // bucket edges computed by dividing
+                let mut first = (stats.min / step).floor();
+                if multiple(first + 1.0) <= stats.min {
+                    first += 1.0;
+                }
+                let mut last = (stats.max / step).ceil();
+                if multiple(last - 1.0) >= stats.max {
+                    last -= 1.0;
+                }
+                let intervals = (last - first).max(1.0) as usize;
+                if intervals <= options.intervals {
+                    let vec = (0..intervals)
+                        .map(|i| {
+                            let k = first + i as f64;
+                            Bucket::new(multiple(k)..multiple(k + 1.0))
+                        })
+                        .collect();
+                    let options = HistogramOptions {
+                        intervals,
+                        ..options.clone()
+                    };
+                    return Self {
+                        vec,
+                        step,
+                        top: 0,
+                        total: 0,
+                        underflow: 0,
+                        overflow: 0,
+                        underflow_min: f64::INFINITY,
+                        last: intervals - 1,
+                        stats: Self::shaped(stats, &options),
+                        geometric: false,
+                        sampled_from: None,
+                        options,
+                    };
+                }
+            }
+            mantissa += 1;
+            if mantissa == NICE_MANTISSAS.len() {
+                mantissa = 0;
+                exponent += 1;
+            }
+        }
+    }
+
+    /// Creates a Histogram from the values read from `paths` by `reader`, in a
+    /// single pass that does not hold them in memory.
+    ///
+    /// Buckets split `range` (instead of the range of input data), so values
+    /// out of it are tallied as underflow or overflow.  The rest of them
+    /// account for the stats, whose minimum and maximum are the bounds of
+    /// `range`.
+    pub fn from_reader(
+        reader: &DataReader,
+        paths: &[&str],
+        range: Range<f64>,
+        options: &HistogramOptions,
+    ) -> Self {
+        let mut options = options.clone();
+        options.intervals = options.intervals.max(1);
+        let mut frame = StatsBuilder::new(options.precision);
+        frame.push(if options.log_scale { 0.0 } else { range.start });
+        frame.push(range.end);
+        let mut histogram = Self::new_with_stats(frame.finish(), &options);
+        let mut builder = StatsBuilder::new(options.precision);
+        reader.for_each_weighted_value(paths, |n, weight| {
+            if histogram.find_slot(n).is_some() {
+                builder.push_weighted(n, weight);
+            }
+            histogram.add_weighted(n, weight);
+        });
+        let mut stats = builder.finish();
+        stats.min = histogram.stats.min;
+        stats.max = histogram.stats.max;
+        histogram.stats = Self::shaped(stats, &options);
+        histogram
+    }
+
+    fn shaped(stats: Stats, options: &HistogramOptions) -> Stats {
+        let stats = if options.show_shape {
+            stats.with_shape()
+        } else {
+            stats
+        };
+        let stats = if options.show_mad {
+            stats.with_mad()
+        } else {
+            stats
+        };
+        match options.mean_ci {
+            Some(level) => stats.with_mean_ci(level),
+            None => stats,
         }
     }
 
+    /// Creates a Histogram from a vector of positive numerical data, using
+    /// buckets whose ranges grow geometrically from the minimum to the maximum
+    /// of the data (every bucket is a constant factor wider than the previous
+    /// one).  This suits heavily skewed data, like latencies or file sizes.
+    ///
+    /// `options` is a `HistogramOptions` struct with the preferences to create
+    /// histogram (its `log_scale` flag is ignored).  An error is returned if
+    /// the minimum of the data is not positive.
+    pub fn new_logarithmic(vec: &mut [f64], mut options: HistogramOptions) -> Result<Self, String> {
+        let stats = Stats::new(vec, options.precision);
+        options.intervals = options.intervals.clamp(1, vec.len().max(1));
+        let mut histogram = Self::new_logarithmic_with_stats(stats, &options)?;
+        histogram.load(vec);
+        Ok(histogram)
+    }
+
+    /// Creates a Histogram with geometrically growing buckets and no input
+    /// data.
+    ///
+    /// Parameters are similar to those on the `new_logarithmic` method, but a
+    /// parameter named `stats` is needed to decide how future data (to be
+    /// injected with the load method) will be accommodated.
+    pub fn new_logarithmic_with_stats(
+        stats: Stats,
+        options: &HistogramOptions,
+    ) -> Result<Self, String> {
+        if stats.min <= 0.0 {
+            return Err(format!(
+                "Logarithmic buckets need positive values, but minimum is {}",
+                stats.min
+            ));
+        }
+        // A single bucket is enough when all values are the same
+        let intervals = if stats.min == stats.max {
+            1
+        } else {
+            options.intervals
+        };
+        let options = &HistogramOptions {
+            intervals,
+            ..options.clone()
+        };
+        let step = (stats.max / stats.min).ln() / options.intervals as f64;
+        let mut vec = Vec::<Bucket>::with_capacity(options.intervals);
+        let mut lower = stats.min;
+        for i in 1..=options.intervals {
+            let upper = if i == options.intervals {
+                stats.max
+            } else {
+                stats.min * (step * i as f64).exp()
+            };
+            vec.push(Bucket::new(lower..upper));
+            lower = upper;
+        }
+        Ok(Self {
+            vec,
+            step,
+            top: 0,
+            total: 0,
+            underflow: 0,
+            overflow: 0,
+            underflow_min: f64::INFINITY,
+            last: options.intervals - 1,
+            stats: Self::shaped(stats, options),
+            geometric: true,
+            sampled_from: None,
+            options: options.clone(),
+        })
+    }
+
+    /// Notes that the data of the histogram is a sample of `total` input
+    /// values, so that its display reports it.
+    pub fn with_sampled_from(mut self, total: usize) -> Self {
+        self.sampled_from = Some(total);
+        self
+    }
+
     /// Add to the `Histogram` data the values of a slice of numerical data.
     pub fn load(&mut self, vec: &[f64]) {
         for x in vec {
@@ -94,20 +667,352 @@ impl Histogram {
         }
     }
 
-    /// Add to the `Histogram` a single piece of numerical data.
+    /// Add to the `Histogram` a single piece of numerical data.  Values out
+    /// of the range of the buckets are tallied as underflow or overflow.
     pub fn add(&mut self, n: f64) {
+        self.add_weighted(n, 1);
+    }
+
+    /// Add to the `Histogram` a piece of numerical data observed `weight`
+    /// times, as if `add` was called that many times.  NaN and infinite values
+    /// are ignored.
+    pub fn add_weighted(&mut self, n: f64, weight: usize) {
+        if !n.is_finite() {
+            return;
+        }
         if let Some(slot) = self.find_slot(n) {
-            self.vec[slot].inc();
+            self.vec[slot].inc(weight);
             self.top = self.top.max(self.vec[slot].count);
+            self.total += weight;
+        } else if n < self.stats.min {
+            self.underflow += weight;
+            self.underflow_min = self.underflow_min.min(n);
+        } else {
+            self.overflow += weight;
+        }
+    }
+
+    /// Adds the counts of the buckets of `other` to the ones of this
+    /// histogram, and merges their stats.  Both histograms need to have the
+    /// same buckets (same size, kind, step, minimum and maximum).
+    pub fn merge(&mut self, other: &Histogram) -> Result<(), MergeError> {
+        self.check_same_buckets(other)?;
+        self.sampled_from = match (self.sampled_from, other.sampled_from) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(self.stats.samples) + b.unwrap_or(other.stats.samples)),
+        };
+        for (bucket, other_bucket) in self.vec.iter_mut().zip(other.vec.iter()) {
+            bucket.count += other_bucket.count;
+        }
+        self.top = self.vec.iter().map(|b| b.count).max().unwrap_or(0);
+        self.total += other.total;
+        self.underflow += other.underflow;
+        self.overflow += other.overflow;
+        self.underflow_min = self.underflow_min.min(other.underflow_min);
+        self.stats.merge(&other.stats);
+        Ok(())
+    }
+
+    /// Returns a struct displaying this histogram and `other` side by side:
+    /// every bucket gets a row with the bar of each of them, in different
+    /// colors.  This suits comparing two inputs, like latencies before and
+    /// after a change.  Both histograms need to have the same buckets (see
+    /// `merge`).
+    pub fn compare<'a>(
+        &'a self,
+        other: &'a Histogram,
+    ) -> Result<HistogramComparison<'a>, MergeError> {
+        self.check_same_buckets(other)?;
+        Ok(HistogramComparison {
+            histograms: [self, other],
+        })
+    }
+
+    fn check_same_buckets(&self, other: &Histogram) -> Result<(), MergeError> {
+        if self.vec.len() != other.vec.len() {
+            return Err(MergeError(format!(
+                "{} buckets vs {} buckets",
+                self.vec.len(),
+                other.vec.len()
+            )));
+        }
+        if self.geometric != other.geometric || self.options.log_scale != other.options.log_scale {
+            return Err(MergeError(String::from("different kinds of buckets")));
+        }
+        let same_step = self.step == other.step || (self.step.is_nan() && other.step.is_nan());
+        if !same_step {
+            return Err(MergeError(format!(
+                "step {} vs step {}",
+                self.step, other.step
+            )));
+        }
+        if self.stats.min != other.stats.min || self.stats.max != other.stats.max {
+            return Err(MergeError(format!(
+                "range {}..{} vs range {}..{}",
+                self.stats.min, self.stats.max, other.stats.min, other.stats.max
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns a JSON representation of the histogram, holding its stats, its
+    /// buckets (with their ranges and counts), and the `top` (maximum count of
+    /// a bucket), `step` and `max` (upper edge of last bucket) values.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Returns a CSV representation of the buckets of the histogram: a header
+    /// line and a `range_start,range_end,count` row per bucket.  Numbers are
+    /// printed with full precision.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("range_start,range_end,count\n");
+        for bucket in &self.vec {
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                bucket.range.start, bucket.range.end, bucket.count
+            ));
+        }
+        csv
+    }
+
+    /// Returns a tab-separated representation of the buckets of the
+    /// histogram, meant for unix pipelines: a `range_start range_end count`
+    /// line per bucket, with no header and no colors.  Numbers are printed
+    /// with full precision.
+    pub fn to_tsv(&self) -> String {
+        let mut tsv = String::new();
+        for bucket in &self.vec {
+            tsv.push_str(&format!(
+                "{}\t{}\t{}\n",
+                bucket.range.start, bucket.range.end, bucket.count
+            ));
+        }
+        tsv
+    }
+
+    /// Returns a GitHub flavored markdown table with a `Range | Count | Bar`
+    /// row per bucket.  Ranges are printed with full precision, and bars are
+    /// drawn with the glyph of the histogram options, without any color.
+    pub fn to_markdown(&self) -> String {
+        let scale = self.top.div_ceil(MARKDOWN_BAR_LEN).max(1);
+        let mut markdown = String::from("| Range | Count | Bar |\n| --- | ---: | --- |\n");
+        for bucket in &self.vec {
+            markdown.push_str(&format!(
+                "| {} .. {} | {} | {} |\n",
+                bucket.range.start,
+                bucket.range.end,
+                bucket.count,
+                self.options.glyph.to_string().repeat(bucket.count / scale)
+            ));
         }
+        markdown
+    }
+
+    /// Returns an SVG document of `width` x `height` pixels with a bar per
+    /// bucket, and axis labels for bucket edges and counts.  Every bar has a
+    /// tooltip with the range and count of its bucket.
+    pub fn to_svg(&self, width: usize, height: usize) -> String {
+        self.to_svg_with_palette(width, height, DEFAULT_SVG_PALETTE)
+    }
+
+    /// Like `to_svg`, but bars are filled with the colors in `palette` (any
+    /// valid SVG color), used in turns.
+    pub fn to_svg_with_palette(&self, width: usize, height: usize, palette: &[&str]) -> String {
+        let palette = if palette.is_empty() {
+            DEFAULT_SVG_PALETTE
+        } else {
+            palette
+        };
+        let formatter = self.formatter();
+        let plot_width = width.saturating_sub(SVG_LEFT_MARGIN).max(1) as f64;
+        let plot_height = height
+            .saturating_sub(SVG_BOTTOM_MARGIN + SVG_TOP_MARGIN)
+            .max(1) as f64;
+        let bottom = (SVG_TOP_MARGIN as f64) + plot_height;
+        let bar_width = plot_width / self.vec.len() as f64;
+        let top = self.top.max(1) as f64;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\" font-family=\"sans-serif\" font-size=\"10\">\n"
+        );
+        svg.push_str(&format!(
+            "<line x1=\"{x}\" y1=\"{SVG_TOP_MARGIN}\" x2=\"{x}\" y2=\"{bottom}\" stroke=\"black\"/>\n\
+             <line x1=\"{x}\" y1=\"{bottom}\" x2=\"{width}\" y2=\"{bottom}\" stroke=\"black\"/>\n",
+            x = SVG_LEFT_MARGIN,
+        ));
+        for (label, y) in [(self.top, SVG_TOP_MARGIN as f64), (0, bottom)] {
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{y}\" text-anchor=\"end\" dominant-baseline=\"middle\">{label}</text>\n",
+                SVG_LEFT_MARGIN - 4,
+            ));
+        }
+        for (i, bucket) in self.vec.iter().enumerate() {
+            let x = SVG_LEFT_MARGIN as f64 + bar_width * i as f64;
+            let bar_height = plot_height * bucket.count as f64 / top;
+            svg.push_str(&format!(
+                "<rect x=\"{x:.2}\" y=\"{y:.2}\" width=\"{bar_width:.2}\" height=\"{bar_height:.2}\" \
+                 fill=\"{fill}\" stroke=\"white\"><title>{start} .. {end}: {count}</title></rect>\n",
+                y = bottom - bar_height,
+                fill = palette[i % palette.len()],
+                start = formatter.format(bucket.range.start),
+                end = formatter.format(bucket.range.end),
+                count = bucket.count,
+            ));
+        }
+        let edges = self
+            .vec
+            .iter()
+            .map(|bucket| bucket.range.start)
+            .chain(std::iter::once(self.vec[self.last].range.end));
+        for (i, edge) in edges.enumerate() {
+            svg.push_str(&format!(
+                "<text x=\"{x:.2}\" y=\"{y}\" text-anchor=\"middle\">{label}</text>\n",
+                x = SVG_LEFT_MARGIN as f64 + bar_width * i as f64,
+                y = bottom + 15.0,
+                label = formatter.format(edge),
+            ));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn formatter(&self) -> F64Formatter {
+        let formatter = match self.options.precision {
+            // Narrowest bucket decides the decimals, so that adjacent
+            // geometric edges can be told apart
+            None if self.geometric => F64Formatter::new_with_range(self.vec[0].range.clone()),
+            None if self.options.integer && !self.options.log_scale => F64Formatter::new(0),
+            None => F64Formatter::new_with_range(self.stats.min..self.stats.max),
+            Some(n) => F64Formatter::new(n),
+        };
+        match self.options.scientific {
+            Some(threshold) => {
+                formatter.with_scientific(threshold, self.options.precision.unwrap_or(3))
+            }
+            None => formatter,
+        }
+    }
+
+    /// Returns the width of buckets (or NaN if buckets have different widths,
+    /// as with logarithmic scale).
+    pub fn step(&self) -> f64 {
+        if self.geometric {
+            f64::NAN
+        } else {
+            self.step
+        }
+    }
+
+    /// Returns the number of values counted in the buckets (values out of
+    /// their range are not included).
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns the fraction of values (including the ones out of range) that
+    /// are less or equal than `threshold`.  Values are assumed to be spread
+    /// evenly in the bucket holding it, so the fraction is an estimate unless
+    /// `threshold` is on a bucket edge (or the histogram is an integer one).
+    /// For a `threshold` below the range of the buckets, values below it are
+    /// taken as at most `threshold` if the lowest of them is.  Returns NaN for
+    /// histograms with no values.
+    pub fn fraction_at_most(&self, threshold: f64) -> f64 {
+        let values = self.total + self.underflow + self.overflow;
+        if values == 0 {
+            return f64::NAN;
+        }
+        let below = match self.find_slot(threshold) {
+            Some(slot) => {
+                let range = &self.vec[slot].range;
+                let width = range.end - range.start;
+                let part = if self.options.integer && !self.options.log_scale {
+                    (threshold.floor() - range.start + 1.0) / width
+                } else if width > 0.0 {
+                    (threshold - range.start) / width
+                } else {
+                    1.0
+                };
+                let before: usize = self.vec[..slot].iter().map(|b| b.count).sum();
+                (self.underflow + before) as f64
+                    + part.clamp(0.0, 1.0) * self.vec[slot].count as f64
+            }
+            None if threshold < self.stats.min && threshold < self.underflow_min => 0.0,
+            None if threshold < self.stats.min => self.underflow as f64,
+            None => (self.underflow + self.total) as f64,
+        };
+        below / values as f64
+    }
+
+    /// Returns the number of buckets of the histogram.
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Returns true if no values were counted in the buckets.
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Returns an iterator over the buckets of the histogram, from the lowest
+    /// one, yielding their range and count.
+    pub fn buckets(&self) -> impl Iterator<Item = (&Range<f64>, usize)> {
+        self.vec.iter().map(|bucket| (&bucket.range, bucket.count))
+    }
+
+    /// Returns an iterator over the buckets of the histogram, from the lowest
+    /// one, yielding their range and density (count divided by the total
+    /// count of buckets and by the bucket width).
+    pub fn densities(&self) -> impl Iterator<Item = (&Range<f64>, f64)> {
+        let total = self.total;
+        self.vec
+            .iter()
+            .map(move |bucket| (&bucket.range, bucket.density(total)))
+    }
+
+    /// Returns the number of values added that were below the range of the
+    /// buckets.
+    pub fn underflow(&self) -> usize {
+        self.underflow
+    }
+
+    /// Returns the number of values added that were above the range of the
+    /// buckets.
+    pub fn overflow(&self) -> usize {
+        self.overflow
     }
 
     fn find_slot(&self, n: f64) -> Option<usize> {
-        if n < self.stats.min || n > self.stats.max {
+        if self.options.integer && !self.options.log_scale {
+            // Integer arithmetic avoids float error on bucket edges
+            let offset = n.floor() as i64 - self.vec[0].range.start as i64;
+            if offset < 0 {
+                return None;
+            }
+            let slot = (offset / self.step as i64) as usize;
+            return (slot <= self.last).then_some(slot);
+        }
+        // Bucket edges are computed with float arithmetic, so they may fall
+        // slightly short of the maximum: values up to it (plus some tolerance)
+        // have to be accommodated in last bucket anyway.
+        let tolerance = self.stats.max.abs().max(1.0) * f64::EPSILON;
+        let (min, max) = if self.stats.min == self.stats.max && !self.options.log_scale {
+            // The bucket of a single value histogram is wider than the value
+            (self.vec[0].range.start, self.vec[0].range.end)
+        } else {
+            (self.stats.min, self.stats.max)
+        };
+        if n < min || n > max + tolerance {
+            return None;
+        }
+        if !self.options.closed_last && n >= self.vec[self.last].range.end {
             return None;
         }
-        if self.log_scale {
-            let mut bucket = None;
+        if self.last == 0 {
+            // Avoid dividing by the zero step of a single value histogram
+            Some(0)
+        } else if self.options.log_scale {
+            let mut bucket = Some(self.last);
             for i in 0..self.vec.len() {
                 if self.vec[i].range.end >= n {
                     bucket = Some(i);
@@ -115,228 +1020,1972 @@ impl Histogram {
                 }
             }
             bucket
+        } else if self.geometric {
+            Some((((n / self.stats.min).ln() / self.step) as usize).min(self.last))
+        } else {
+            let start = self.vec[0].range.start;
+            Some((((n - start) / self.step) as usize).min(self.last))
+        }
+    }
+
+    fn build_buckets(range: Range<f64>, options: &HistogramOptions) -> Vec<Bucket> {
+        let mut vec = Vec::<Bucket>::with_capacity(options.intervals);
+        if options.log_scale {
+            let first_bucket_size = range.end / (2_f64.powi(options.intervals as i32) - 1.0);
+            let mut lower = 0.0;
+            for i in 0..options.intervals {
+                let upper = lower + 2_f64.powi(i as i32) * first_bucket_size;
+                vec.push(Bucket::new(lower..upper));
+                lower = upper;
+            }
+        } else {
+            let step = (range.end - range.start) / options.intervals as f64;
+            let mut lower = range.start;
+            for _ in 0..options.intervals {
+                vec.push(Bucket::new(lower..lower + step));
+                lower += step;
+            }
+        }
+        vec
+    }
+}
+
+impl Serialize for Histogram {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Histogram", 7)?;
+        state.serialize_field("stats", &self.stats)?;
+        state.serialize_field("buckets", &self.vec)?;
+        state.serialize_field("top", &self.top)?;
+        state.serialize_field("step", &self.step())?;
+        state.serialize_field("max", &self.vec[self.last].range.end)?;
+        state.serialize_field("underflow", &self.underflow)?;
+        state.serialize_field("overflow", &self.overflow)?;
+        state.end()
+    }
+}
+
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.options.width.filter(|width| *width > 0);
+        let width = width.unwrap_or_else(|| display_width(f, 110));
+        self.write_to(f, width)
+    }
+}
+
+impl Histogram {
+    /// Writes the histogram, as displayed by its `Display` implementation, to
+    /// any `io::Write` target, using `width` chars as terminal width.  The
+    /// output is streamed, so no intermediate string is built.
+    pub fn render<W: io::Write>(&self, w: &mut W, width: usize) -> io::Result<()> {
+        let mut writer = IoWriter::new(w);
+        self.write_to(&mut writer, width)
+            .map_err(|_| writer.into_error())
+    }
+
+    fn write_to<W: fmt::Write>(&self, f: &mut W, width: usize) -> fmt::Result {
+        if !self.options.hide_stats {
+            self.write_header(f)?;
+        }
+        if self.stats.samples == 0 {
+            return Ok(());
+        }
+        let factor = self.merge_factor();
+        let merged;
+        let hist = if factor > 1 {
+            merged = self.merged(factor);
+            &merged
         } else {
-            Some((((n - self.stats.min) / self.step) as usize).min(self.last))
+            self
+        };
+        let writer = self
+            .writer(width, factor)
+            .highlight(hist, &self.options.highlight);
+        match self.options.threshold {
+            Some(value) => writer.threshold(value).write(f, hist),
+            None => writer.write(f, hist),
+        }
+    }
+
+    // Returns a writer following the options of the histogram, for a display
+    // `width` chars wide where every row merges `merge_factor` buckets.
+    fn writer(&self, width: usize, merge_factor: usize) -> HistWriter<'_> {
+        HistWriter {
+            width,
+            formatter: self.formatter(),
+            bar_scale: self.options.bar_scale,
+            cumulative: self.options.cumulative,
+            hide_empty: self.options.hide_empty,
+            partial_blocks: self.options.partial_blocks,
+            glyph: self.options.glyph,
+            theme: &self.options.theme,
+            show_percent: self.options.show_percent,
+            reverse: self.options.reverse,
+            density: self.options.density,
+            quantile_markers: self.options.quantile_markers,
+            intensity: self.options.intensity && !Paint::is_enabled(),
+            zero_axis: self.options.zero_axis,
+            human_counts: self.options.human_counts,
+            interval_notation: self.options.interval_notation,
+            min_bar_len: self.options.min_bar_len,
+            height: match self.options.orientation {
+                Orientation::Horizontal => None,
+                Orientation::Vertical => Some(self.options.height.max(1)),
+            },
+            highlighted: Vec::new(),
+            merge_factor,
+            threshold: None,
+            hide_legend: self.options.hide_legend,
+        }
+    }
+
+    // Writes the stats of data, and how many values were left out of them
+    // or out of the range of buckets.
+    fn write_header<W: fmt::Write>(&self, f: &mut W) -> fmt::Result {
+        let legend = self.options.theme.legend;
+        if let Some(total) = self.sampled_from {
+            writeln!(
+                f,
+                "Sampled {} of {} values",
+                legend.paint(self.stats.samples.to_string()),
+                legend.paint(total.to_string())
+            )?;
+        }
+        write!(f, "{}", self.stats)?;
+        if self.stats.samples == 0 {
+            return Ok(());
         }
+        if self.underflow > 0 {
+            writeln!(
+                f,
+                "below range: {}",
+                legend.paint(self.underflow.to_string())
+            )?;
+        }
+        if self.overflow > 0 {
+            writeln!(
+                f,
+                "above range: {}",
+                legend.paint(self.overflow.to_string())
+            )?;
+        }
+        Ok(())
+    }
+
+    // Returns the number of adjacent buckets displayed in every row, the
+    // lowest power of two fitting them in `max_rows` rows.
+    fn merge_factor(&self) -> usize {
+        let max_rows = match (self.options.max_rows, self.options.orientation) {
+            (Some(rows), Orientation::Horizontal) => rows.max(1),
+            _ => return 1,
+        };
+        let mut factor = 1;
+        while self.vec.len().div_ceil(factor) > max_rows {
+            factor *= 2;
+        }
+        factor
+    }
+
+    // Returns a copy of the histogram whose buckets merge every `factor`
+    // adjacent buckets (the last of them may merge less).
+    fn merged(&self, factor: usize) -> Histogram {
+        let vec: Vec<Bucket> = self
+            .vec
+            .chunks(factor)
+            .map(|chunk| Bucket {
+                range: chunk[0].range.start..chunk[chunk.len() - 1].range.end,
+                count: chunk.iter().map(|bucket| bucket.count).sum(),
+            })
+            .collect();
+        Histogram {
+            top: vec.iter().map(|bucket| bucket.count).max().unwrap_or(0),
+            last: vec.len() - 1,
+            vec,
+            // Merged buckets of linear and integer histograms are `factor`
+            // times wider, and logarithms of ratios of geometric ones add up
+            step: self.step * factor as f64,
+            total: self.total,
+            underflow: self.underflow,
+            underflow_min: self.underflow_min,
+            overflow: self.overflow,
+            stats: self.stats.clone(),
+            geometric: self.geometric,
+            sampled_from: self.sampled_from,
+            options: self.options.clone(),
+        }
+    }
+}
+
+/// Two histograms with the same buckets, displayed side by side.  See
+/// `Histogram::compare`.
+pub struct HistogramComparison<'a> {
+    histograms: [&'a Histogram; 2],
+}
+
+impl fmt::Display for HistogramComparison<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b] = self.histograms;
+        let width = a.options.width.filter(|width| *width > 0);
+        let width = width.unwrap_or_else(|| display_width(f, 110));
+        let theme = &a.options.theme;
+        writeln!(
+            f,
+            "Counts: {} = {}; {} = {}",
+            COMPARISON_COLORS[0].paint("A"),
+            theme.legend.paint(a.total()),
+            COMPARISON_COLORS[1].paint("B"),
+            theme.legend.paint(b.total()),
+        )?;
+        // Bars are scaled like the ones of an histogram holding both inputs
+        let writer = a.writer(width, 1);
+        let range_width = writer.get_width(a).max(writer.get_width(b));
+        let top = a.top.max(b.top);
+        let count_width = top.to_string().len();
+        let max_bar_len = writer.get_max_bar_len(range_width + count_width);
+        let scales = COMPARISON_COLORS.map(|color| {
+            writer
+                .horizontal_scale(top, max_bar_len)
+                .with_glyph(a.options.glyph, color)
+        });
+        write!(f, "{}", scales[0])?;
+        for (bucket_a, bucket_b) in a.vec.iter().zip(b.vec.iter()) {
+            let range = format!(
+                "{:>width$} .. {:>width$}",
+                writer.formatter.format(bucket_a.range.start),
+                writer.formatter.format(bucket_a.range.end),
+                width = range_width
+            );
+            for (i, bucket) in [bucket_a, bucket_b].iter().enumerate() {
+                let prefix = if i == 0 {
+                    format!("[{}]", theme.range.paint(range.clone()))
+                } else {
+                    " ".repeat(range.len() + 2)
+                };
+                let color = COMPARISON_COLORS[i];
+                writeln!(
+                    f,
+                    "{prefix} {label} [{count}] {bar}",
+                    label = color.paint(if i == 0 { "A" } else { "B" }),
+                    count = theme.count.paint(format!("{:>count_width$}", bucket.count)),
+                    bar = scales[i].get_bar(bucket.count),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct HistWriter<'a> {
+    width: usize,
+    formatter: F64Formatter,
+    bar_scale: BarScale,
+    cumulative: bool,
+    hide_empty: bool,
+    partial_blocks: bool,
+    glyph: char,
+    theme: &'a Theme,
+    show_percent: bool,
+    reverse: bool,
+    density: bool,
+    quantile_markers: bool,
+    // Whether bars are drawn with glyphs conveying their intensity
+    intensity: bool,
+    zero_axis: bool,
+    human_counts: bool,
+    interval_notation: bool,
+    min_bar_len: usize,
+    // Rows of the highest column, if buckets are drawn as columns
+    height: Option<usize>,
+    // Indexes of the highlighted buckets
+    highlighted: Vec<usize>,
+    // Number of buckets of the histogram merged in every one displayed
+    merge_factor: usize,
+    // Target value, whose bucket is followed by a divider
+    threshold: Option<f64>,
+    hide_legend: bool,
+}
+
+/// A bucket of an histogram, along with what is displayed for it.
+struct Row<'a> {
+    bucket: &'a Bucket,
+    // Count displayed (it may be a cumulative count)
+    count: usize,
+    // Length of the bar, as understood by HorizontalScale
+    units: usize,
+    // Text of the count column
+    label: String,
+    // Fraction of the bucket range below quantiles in it, and their markers
+    markers: Vec<(f64, char)>,
+    // Glyph replacing the ones of the bar, if any
+    glyph: Option<char>,
+    highlighted: bool,
+    // Whether the bucket holds the threshold
+    divider: bool,
+    // Whether the range of the bucket includes its upper edge
+    closed: bool,
+}
+
+/// Data needed for laying out the columns of the rows of an histogram.
+struct RowLayout {
+    range_width: usize,
+    count_width: usize,
+    // Width of the percentage column (zero if it is not displayed)
+    percent_width: usize,
+    // Sum of the counts of all buckets
+    total: usize,
+    // Room for the bars at each side of the zero axis, if it is displayed
+    half_bar_len: Option<usize>,
+}
+
+impl HistWriter<'_> {
+    /// Highlights the buckets of `hist` holding any of `values`.
+    pub fn highlight(mut self, hist: &Histogram, values: &[f64]) -> Self {
+        self.highlighted = values.iter().filter_map(|v| hist.find_slot(*v)).collect();
+        self
+    }
+
+    /// Displays the share of values at most `value`, and draws a divider
+    /// between the buckets of values passing and failing that threshold.
+    pub fn threshold(mut self, value: f64) -> Self {
+        self.threshold = Some(value);
+        self
+    }
+
+    pub fn write<W: fmt::Write>(&self, f: &mut W, hist: &Histogram) -> fmt::Result {
+        if let Some(height) = self.height {
+            return self.write_columns(f, hist, height);
+        }
+        let counts = self.get_counts(hist);
+        let total = hist.total;
+        // Bars are drawn in terms of "units": counts, or counts normalized by
+        // the width of buckets when displaying densities.
+        let (units, labels) = if self.density {
+            Self::get_densities(hist, total)
+        } else {
+            let labels = counts.iter().map(|c| self.format_count(*c)).collect();
+            (counts.clone(), labels)
+        };
+        let top = units.iter().copied().max().unwrap_or(0);
+        let mut layout = RowLayout {
+            range_width: self.get_width(hist),
+            count_width: labels.iter().map(|l| l.len()).max().unwrap_or(1),
+            percent_width: 0,
+            total,
+            half_bar_len: None,
+        };
+        if self.show_percent {
+            layout.percent_width = counts
+                .iter()
+                .map(|c| Self::format_percent(*c, layout.total).len())
+                .max()
+                .unwrap_or(0);
+        }
+        // Room for the marks of highlighted rows
+        let highlight_width = if self.highlighted.is_empty() { 0 } else { 2 };
+        let mut max_bar_len = self.get_max_bar_len(
+            layout.range_width + layout.count_width + layout.percent_width + highlight_width,
+        );
+        if self.zero_axis {
+            max_bar_len = (max_bar_len / 2).max(1);
+            layout.half_bar_len = Some(max_bar_len);
+        }
+        let horizontal_scale = self.horizontal_scale(top, max_bar_len);
+        let markers = if self.quantile_markers {
+            self.write_marker_legend(f, hist)?
+        } else {
+            Vec::new()
+        };
+        if let Some(value) = self.threshold {
+            writeln!(
+                f,
+                "{}% ≤ {}",
+                self.theme
+                    .legend
+                    .paint(format!("{:.1}", hist.fraction_at_most(value) * 100.0)),
+                self.theme.legend.paint(self.formatter.format(value))
+            )?;
+        }
+        if !self.hide_legend {
+            self.write_legend(f, hist, &horizontal_scale)?;
+        }
+        let mut empty = 0;
+        let threshold_slot = self.threshold.and_then(|value| hist.find_slot(value));
+        let mut rows: Vec<Row> = hist
+            .vec
+            .iter()
+            .zip(counts)
+            .zip(units.into_iter().zip(labels))
+            .enumerate()
+            .map(|(i, ((bucket, count), (units, label)))| Row {
+                bucket,
+                count,
+                units,
+                label,
+                glyph: self
+                    .intensity
+                    .then(|| INTENSITY_CHARS[intensity_level(units, top)]),
+                markers: markers
+                    .iter()
+                    .filter(|(slot, _, _)| *slot == i)
+                    .map(|(_, fraction, marker)| (*fraction, *marker))
+                    .collect(),
+                highlighted: self.highlighted.contains(&i),
+                divider: threshold_slot == Some(i),
+                closed: i == hist.last && hist.options.closed_last,
+            })
+            .collect();
+        if self.reverse {
+            rows.reverse();
+        }
+        for row in rows {
+            // Passing buckets are the ones before the divider, or after it
+            // when reversed
+            if row.divider && self.reverse {
+                self.write_empty(f, empty)?;
+                empty = 0;
+                self.write_divider(f, &layout)?;
+            }
+            if self.hide_empty && row.bucket.count == 0 {
+                empty += 1;
+            } else {
+                self.write_empty(f, empty)?;
+                empty = 0;
+                self.write_bucket(f, &row, &horizontal_scale, &layout)?;
+            }
+            if row.divider && !self.reverse {
+                self.write_empty(f, empty)?;
+                empty = 0;
+                self.write_divider(f, &layout)?;
+            }
+        }
+        self.write_empty(f, empty)
+    }
+
+    /// Writes what bars represent, and how many buckets are merged in rows.
+    fn write_legend<W: fmt::Write>(
+        &self,
+        f: &mut W,
+        hist: &Histogram,
+        horizontal_scale: &HorizontalScale,
+    ) -> fmt::Result {
+        if self.merge_factor > 1 {
+            writeln!(
+                f,
+                "Every row merges {} buckets",
+                self.theme.legend.paint(self.merge_factor)
+            )?;
+        }
+        if self.density && self.bar_scale == BarScale::Linear {
+            writeln!(
+                f,
+                "Each {} represents a density of {}\n",
+                self.theme.bar.paint(if self.partial_blocks {
+                    crate::format::FULL_BLOCK
+                } else {
+                    self.glyph
+                }),
+                self.theme.legend.paint(format_density(
+                    horizontal_scale.get_scale() as f64
+                        / (hist.total as f64 * Self::min_width(hist))
+                )),
+            )
+        } else {
+            writeln!(f, "{horizontal_scale}")
+        }
+    }
+
+    /// Writes a line under the range column, followed by the threshold.
+    fn write_divider<W: fmt::Write>(&self, f: &mut W, layout: &RowLayout) -> fmt::Result {
+        let value = self.threshold.unwrap_or(f64::NAN);
+        // Brackets and the separator of edges are part of the column
+        let separator_len = if self.interval_notation { 2 } else { 4 };
+        writeln!(
+            f,
+            "{} {}",
+            "┈".repeat(layout.range_width * 2 + separator_len + 2),
+            self.theme.legend.paint(self.formatter.format(value))
+        )
+    }
+
+    /// Writes the buckets as columns of up to `height` rows, growing upward
+    /// from an axis below which the edges of the histogram are printed.
+    fn write_columns<W: fmt::Write>(
+        &self,
+        f: &mut W,
+        hist: &Histogram,
+        height: usize,
+    ) -> fmt::Result {
+        let counts = self.get_counts(hist);
+        let top = counts.iter().copied().max().unwrap_or(0);
+        let label_width = self.format_count(top).len();
+        // Columns are as wide as possible (up to 4 chars, with a space
+        // between them when there is room for it)
+        let room = self.width.saturating_sub(label_width + 2);
+        let column_width = (room / counts.len()).clamp(1, 4);
+        let block_width = column_width.saturating_sub(1).max(1);
+        let rows: Vec<usize> = counts
+            .iter()
+            .map(|count| match *count {
+                0 => 0,
+                count => ((count * height) as f64 / top as f64).round().max(1.0) as usize,
+            })
+            .collect();
+        for row in (1..=height).rev() {
+            let label = if row == height {
+                self.format_count(top)
+            } else {
+                String::new()
+            };
+            let cells: String = rows
+                .iter()
+                .map(|rows| {
+                    let cell = if *rows >= row {
+                        crate::format::FULL_BLOCK.to_string().repeat(block_width)
+                    } else {
+                        " ".repeat(block_width)
+                    };
+                    format!("{:column_width$}", cell)
+                })
+                .collect();
+            writeln!(
+                f,
+                "{:>label_width$} │{}",
+                self.theme.count.paint(label),
+                self.theme.bar.paint(cells.trim_end())
+            )?;
+        }
+        let columns_width = column_width * (counts.len() - 1) + block_width;
+        writeln!(
+            f,
+            "{:>label_width$} └{}",
+            self.theme.count.paint(0),
+            "─".repeat(columns_width)
+        )?;
+        let start = self.formatter.format(hist.vec[0].range.start);
+        let end = self.formatter.format(hist.vec[hist.last].range.end);
+        let gap = columns_width.saturating_sub(start.len() + end.len()).max(1);
+        writeln!(
+            f,
+            "{:label_width$}  {}{}{}",
+            "",
+            self.theme.range.paint(start),
+            " ".repeat(gap),
+            self.theme.range.paint(end)
+        )
+    }
+
+    /// Writes which char marks every quantile, returning the bucket holding
+    /// each of them, the fraction of the range of the bucket below it and
+    /// its marker.  Quantiles not available in the stats are left out.
+    fn write_marker_legend<W: fmt::Write>(
+        &self,
+        f: &mut W,
+        hist: &Histogram,
+    ) -> Result<Vec<(usize, f64, char)>, fmt::Error> {
+        let mut markers = Vec::new();
+        let mut legend = Vec::new();
+        let quantiles = [
+            (hist.stats.p50, "p50", '│'),
+            (hist.stats.p90, "p90", '┃'),
+            (hist.stats.p99, "p99", '║'),
+        ];
+        for (value, label, marker) in quantiles {
+            if let Some(slot) = hist.find_slot(value) {
+                let range = &hist.vec[slot].range;
+                let fraction = match range.end - range.start {
+                    width if width > 0.0 => ((value - range.start) / width).clamp(0.0, 1.0),
+                    _ => 0.0,
+                };
+                markers.push((slot, fraction, marker));
+                legend.push(format!(
+                    "{} {} = {}",
+                    MARKER_COLOR.paint(marker),
+                    label,
+                    self.theme.legend.paint(self.formatter.format(value))
+                ));
+            }
+        }
+        if !legend.is_empty() {
+            writeln!(f, "Markers: {}", legend.join("; "))?;
+        }
+        Ok(markers)
+    }
+
+    /// Returns the bar of a row with its quantile markers drawn over the
+    /// chars at their position, so that the bar keeps its length (markers of
+    /// empty bars are left out).
+    fn get_marked_bar(&self, row: &Row, horizontal_scale: &HorizontalScale) -> String {
+        let mut bar = horizontal_scale.get_bar(row.units);
+        if let Some(glyph) = row.glyph {
+            bar = self
+                .theme
+                .bar
+                .paint(bar.inner().chars().map(|_| glyph).collect());
+        }
+        if row.markers.is_empty() {
+            return bar.to_string();
+        }
+        // Chars of the bar, along with whether they are a marker
+        let mut cells: Vec<(char, bool)> = bar.inner().chars().map(|c| (c, false)).collect();
+        let len = cells.len();
+        if len == 0 {
+            return bar.to_string();
+        }
+        // Markers sharing a char leave the one of the highest quantile
+        for (fraction, marker) in row.markers.iter() {
+            let position = ((fraction * len as f64) as usize).min(len - 1);
+            cells[position] = (*marker, true);
+        }
+        let mut marked = String::new();
+        let mut run = String::new();
+        for (c, is_marker) in cells {
+            if is_marker {
+                marked.push_str(&self.theme.bar.paint(std::mem::take(&mut run)).to_string());
+                marked.push_str(&MARKER_COLOR.paint(c).to_string());
+            } else {
+                run.push(c);
+            }
+        }
+        marked.push_str(&self.theme.bar.paint(run).to_string());
+        marked
+    }
+
+    // Returns the width of the narrowest bucket
+    fn min_width(hist: &Histogram) -> f64 {
+        hist.vec
+            .iter()
+            .map(|b| b.range.end - b.range.start)
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Returns the units to be drawn for every bucket when displaying
+    /// densities (counts normalized to the width of the narrowest bucket),
+    /// along with the formatted densities.
+    fn get_densities(hist: &Histogram, total: usize) -> (Vec<usize>, Vec<String>) {
+        let min_width = Self::min_width(hist);
+        hist.vec
+            .iter()
+            .map(|bucket| {
+                let width = bucket.range.end - bucket.range.start;
+                let units = (bucket.count as f64 * min_width / width).round() as usize;
+                (units, format_density(bucket.density(total)))
+            })
+            .unzip()
+    }
+
+    fn write_empty<W: fmt::Write>(&self, f: &mut W, empty: usize) -> fmt::Result {
+        match empty {
+            0 => Ok(()),
+            1 => writeln!(f, "... (1 empty bucket) ..."),
+            _ => writeln!(f, "... ({empty} empty buckets) ..."),
+        }
+    }
+
+    fn write_bucket<W: fmt::Write>(
+        &self,
+        f: &mut W,
+        row: &Row,
+        horizontal_scale: &HorizontalScale,
+        layout: &RowLayout,
+    ) -> fmt::Result {
+        let bucket = row.bucket;
+        let percent = if self.show_percent {
+            format!(
+                " ({:>width$}%)",
+                Self::format_percent(row.count, layout.total),
+                width = layout.percent_width
+            )
+        } else {
+            String::new()
+        };
+        let highlight = if row.highlighted {
+            format!(" {}", HIGHLIGHT_COLOR.paint(HIGHLIGHT_CHAR))
+        } else {
+            String::new()
+        };
+        let (separator, close) = match (self.interval_notation, row.closed) {
+            (false, _) => (" .. ", ']'),
+            (true, true) => (", ", ']'),
+            (true, false) => (", ", ')'),
+        };
+        writeln!(
+            f,
+            "[{range}{close} [{count}]{percent} {bar}{highlight}",
+            range = self.theme.range.paint(format!(
+                "{:>width$}{separator}{:>width$}",
+                self.formatter.format(bucket.range.start),
+                self.formatter.format(bucket.range.end),
+                width = layout.range_width,
+            )),
+            count = self.theme.count.paint(format!(
+                "{:>width$}",
+                row.label,
+                width = layout.count_width
+            )),
+            bar = self.get_axed_bar(row, horizontal_scale, layout)
+        )
+    }
+
+    /// Returns the bar of a row, placed at the side of the zero axis that
+    /// corresponds to its bucket when the axis is displayed.
+    fn get_axed_bar(
+        &self,
+        row: &Row,
+        horizontal_scale: &HorizontalScale,
+        layout: &RowLayout,
+    ) -> String {
+        let bar = self.get_marked_bar(row, horizontal_scale);
+        let half_bar_len = match layout.half_bar_len {
+            Some(len) => len,
+            None => return bar,
+        };
+        if row.bucket.range.end <= 0.0 {
+            let len = horizontal_scale.get_bar(row.units).inner().chars().count();
+            format!(
+                "{}{}{}",
+                " ".repeat(half_bar_len.saturating_sub(len)),
+                bar,
+                ZERO_AXIS_CHAR
+            )
+        } else {
+            format!("{}{}{}", " ".repeat(half_bar_len), ZERO_AXIS_CHAR, bar)
+        }
+    }
+
+    fn format_count(&self, count: usize) -> String {
+        if self.human_counts {
+            format_si(count)
+        } else {
+            count.to_string()
+        }
+    }
+
+    fn format_percent(count: usize, total: usize) -> String {
+        if total == 0 {
+            return format!("{:.1}", 0.0);
+        }
+        format!("{:.1}", 100.0 * count as f64 / total as f64)
+    }
+
+    /// Returns the counts to be displayed for every bucket (the stored counts
+    /// are never modified).
+    fn get_counts(&self, hist: &Histogram) -> Vec<usize> {
+        let mut total = 0;
+        hist.vec
+            .iter()
+            .map(|bucket| {
+                if self.cumulative {
+                    total += bucket.count;
+                    total
+                } else {
+                    bucket.count
+                }
+            })
+            .collect()
+    }
+
+    fn get_width(&self, hist: &Histogram) -> usize {
+        // Any edge may be the widest one when some are printed in scientific
+        // notation
+        hist.vec
+            .iter()
+            .flat_map(|bucket| [bucket.range.start, bucket.range.end])
+            .chain([hist.stats.min, hist.stats.max])
+            .map(|edge| self.formatter.format(edge).len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Returns the scale for drawing bars of up to `top` units with
+    // `max_bar_len` chars.
+    fn horizontal_scale(&self, top: usize, max_bar_len: usize) -> HorizontalScale {
+        match self.bar_scale {
+            BarScale::Linear => HorizontalScale::new(top / max_bar_len),
+            BarScale::Log => HorizontalScale::new_logarithmic(top, max_bar_len),
+        }
+        .with_partial_blocks(self.partial_blocks)
+        .with_glyph(self.glyph, self.theme.bar)
+        .with_legend_color(self.theme.legend)
+        .with_min_bar_len(self.min_bar_len)
+    }
+
+    fn get_max_bar_len(&self, fixed_width: usize) -> usize {
+        const EXTRA_CHARS: usize = 10;
+        if self.width < fixed_width + EXTRA_CHARS {
+            75
+        } else {
+            self.width - fixed_width - EXTRA_CHARS
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+    use yansi::Paint;
+
+    #[test]
+    fn test_buckets() {
+        let stats = Stats::new(&mut [-2.0, 14.0], None);
+        let options = HistogramOptions {
+            intervals: 8,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[
+            -1.0, -1.1, 2.0, 2.0, 2.1, -0.9, 11.0, 11.2, 1.9, 1.99, 1.98, 1.97, 1.96,
+        ]);
+
+        assert_eq!(hist.top, 5);
+        let bucket = &hist.vec[0];
+        assert_eq!(bucket.range, -2.0..0.0);
+        assert_eq!(bucket.count, 3);
+        let bucket = &hist.vec[1];
+        assert_eq!(bucket.count, 5);
+        assert_eq!(bucket.range, 0.0..2.0);
+    }
+
+    #[test]
+    fn test_buckets_bad_stats() {
+        let options = HistogramOptions {
+            intervals: 6,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(Stats::new(&mut [-2.0, 4.0], None), &options);
+        hist.load(&[-1.0, 2.0, -1.0, 2.0, 10.0, 10.0, 10.0, -10.0]);
+        assert_eq!(hist.top, 2);
+        assert_eq!(hist.underflow(), 1);
+        assert_eq!(hist.overflow(), 3);
+        Paint::disable();
+        let display = format!("{hist}");
+        assert!(display.contains("\nbelow range: 1\nabove range: 3\n"));
+    }
+
+    #[test]
+    fn display_test() {
+        let stats = Stats::new(&mut [-2.0, 14.0], None);
+        let options = HistogramOptions {
+            intervals: 8,
+            precision: Some(3),
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[
+            -1.0, -1.1, 2.0, 2.0, 2.1, -0.9, 11.0, 11.2, 1.9, 1.99, 1.98, 1.97, 1.96,
+        ]);
+        Paint::disable();
+        let display = format!("{hist}");
+        assert!(display.contains("[-2.000 ..  0.000] [3] ∎∎∎\n"));
+        assert!(display.contains("[ 0.000 ..  2.000] [5] ∎∎∎∎∎\n"));
+        assert!(display.contains("[ 2.000 ..  4.000] [3] ∎∎∎\n"));
+        assert!(display.contains("[ 6.000 ..  8.000] [0] \n"));
+        assert!(display.contains("[10.000 .. 12.000] [2] ∎∎\n"));
+        assert!(!display.contains("range:"));
+    }
+
+    #[test]
+    fn display_test_bad_width() {
+        let options = HistogramOptions {
+            intervals: 8,
+            precision: Some(3),
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(Stats::new(&mut [-2.0, 14.0], None), &options);
+        hist.load(&[
+            -1.0, -1.1, 2.0, 2.0, 2.1, -0.9, 11.0, 11.2, 1.9, 1.99, 1.98, 1.97, 1.96,
+        ]);
+        Paint::disable();
+        let display = format!("{hist:2}");
+        assert!(display.contains("[-2.000 ..  0.000] [3] ∎∎∎\n"));
+    }
+
+    #[test]
+    fn display_test_human_units() {
+        let vector = &mut [
+            -1.0,
+            -12000000.0,
+            -12000001.0,
+            -12000002.0,
+            -12000003.0,
+            -2000000.0,
+            500000.0,
+            500000.0,
+        ];
+        let hist = Histogram::new(
+            vector,
+            HistogramOptions {
+                intervals: 10,
+                ..Default::default()
+            },
+        );
+        Paint::disable();
+        let display = format!("{hist}");
+        assert!(display.contains("[-12.0 M .. -10.4 M] [4] ∎∎∎∎\n"));
+        assert!(display.contains("[ -2.6 M ..  -1.1 M] [1] ∎\n"));
+        assert!(display.contains("[ -1.1 M ..   0.5 M] [3] ∎∎∎\n"));
+        assert!(display.contains("Samples = 8; Min = -12.0 M; Max = 0.5 M"));
+        assert!(display.contains("Average = -6.1 M;"));
+    }
+
+    #[test]
+    fn display_test_log_scale() {
+        let hist = Histogram::new(
+            &mut [0.4, 0.4, 0.4, 0.4, 255.0, 0.2, 1.2, 128.0, 126.0, -7.0],
+            HistogramOptions {
+                intervals: 8,
+                log_scale: true,
+                ..Default::default()
+            },
+        );
+        Paint::disable();
+        let display = format!("{hist}");
+        assert!(display.contains("[  0.00 ..   1.00] [5] ∎∎∎∎∎\n"));
+        assert!(display.contains("[  1.00 ..   3.00] [1] ∎\n"));
+        assert!(display.contains("[  3.00 ..   7.00] [0]"));
+        assert!(display.contains("[  7.00 ..  15.00] [0]"));
+        assert!(display.contains("[ 15.00 ..  31.00] [0]"));
+        assert!(display.contains("[ 31.00 ..  63.00] [0]"));
+        assert!(display.contains("[ 63.00 .. 127.00] [1] ∎\n"));
+        assert!(display.contains("[127.00 .. 255.00] [2] ∎∎\n"));
+    }
+
+    #[test]
+    fn auto_intervals() {
+        // Interquartile range is 50 (25 .. 75), so bucket width is
+        // 2 * 50 / 101^(1/3) = 21.52, and 100 / 21.52 rounds up to 5 buckets
+        let mut vec: Vec<f64> = (0..=100).map(|i| i as f64).collect();
+        let hist = Histogram::auto(&mut vec, HistogramOptions::default());
+        assert_eq!(hist.vec.len(), 5);
+        assert_float_eq!(hist.step(), 20.0, rmax <= f64::EPSILON);
+        assert_eq!(hist.vec.iter().map(|b| b.count).sum::<usize>(), 101);
+        // No interquartile range: Sturges' rule gives log2(10) + 1 = 5
+        let mut vec = [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 2.0];
+        let hist = Histogram::auto(&mut vec, HistogramOptions::default());
+        assert_eq!(hist.vec.len(), 5);
+        // Few values in the interquartile range, lots of outliers
+        let mut vec: Vec<f64> = (0..1000).map(|i| (i % 2) as f64).collect();
+        vec.push(1000000.0);
+        let hist = Histogram::auto(&mut vec, HistogramOptions::default());
+        assert_eq!(hist.vec.len(), MAX_AUTO_INTERVALS);
+    }
+
+    #[test]
+    fn auto_clipped_test() {
+        // Sorted values are -1000, 1 .. 98 and 1000, so p1 is -9.01 and p99 is
+        // 107.02: only the outliers are out of range
+        let mut vec: Vec<f64> = (1..=98).map(|i| i as f64).collect();
+        vec.extend([1000.0, -1000.0]);
+        let options = HistogramOptions {
+            intervals: 10,
+            ..Default::default()
+        };
+        let hist = Histogram::auto_clipped(&mut vec, 1.0, 99.0, options.clone()).unwrap();
+        assert_eq!((hist.underflow(), hist.overflow()), (1, 1));
+        assert_eq!(hist.total(), 98);
+        assert_float_eq!(hist.vec[0].range.start, -9.01, abs <= 1e-9);
+        assert_float_eq!(hist.vec[9].range.end, 107.02, abs <= 1e-9);
+        assert_eq!(hist.stats.samples, 98);
+        // Wider tails are clipped when the percentiles are closer
+        let hist = Histogram::auto_clipped(&mut vec, 10.0, 90.0, options.clone()).unwrap();
+        assert_eq!(hist.underflow() + hist.overflow() + hist.total(), 100);
+        assert_eq!((hist.underflow(), hist.overflow()), (10, 10));
+        assert!(Histogram::auto_clipped(&mut vec, 50.0, 50.0, options.clone()).is_err());
+        assert!(Histogram::auto_clipped(&mut vec, 1.0, 101.0, options).is_err());
+    }
+
+    #[test]
+    fn display_test_log_bars() {
+        let stats = Stats::new(&mut [0.0, 4.0], None);
+        let options = HistogramOptions {
+            intervals: 2,
+            precision: Some(1),
+            bar_scale: BarScale::Log,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[1.0, 3.0, 3.0]);
+        for _ in 0..100000 {
+            hist.add(1.0);
+        }
+        Paint::disable();
+        let display = format!("{hist:60}");
+        assert!(display.contains("logarithmic scale"));
+        assert!(!display.contains("represents a count of"));
+        // ln(3) / ln(100002) of the 41 chars available for bars
+        assert!(display.contains("[2.0 .. 4.0] [     2] ∎∎∎∎\n"));
+        assert!(display.contains(&format!("[0.0 .. 2.0] [100001] {}\n", "∎".repeat(41))));
+    }
+
+    #[test]
+    fn display_test_cumulative() {
+        let stats = Stats::new(&mut [-2.0, 14.0], None);
+        let options = HistogramOptions {
+            intervals: 8,
+            precision: Some(3),
+            cumulative: true,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[
+            -1.0, -1.1, 2.0, 2.0, 2.1, -0.9, 11.0, 11.2, 1.9, 1.99, 1.98, 1.97, 1.96,
+        ]);
+        Paint::disable();
+        let display = format!("{hist}");
+        assert!(display.contains("[-2.000 ..  0.000] [ 3] ∎∎∎\n"));
+        assert!(display.contains("[ 0.000 ..  2.000] [ 8] ∎∎∎∎∎∎∎∎\n"));
+        assert!(display.contains("[ 6.000 ..  8.000] [11] ∎∎∎∎∎∎∎∎∎∎∎\n"));
+        assert!(display.contains("[12.000 .. 14.000] [13] ∎∎∎∎∎∎∎∎∎∎∎∎∎\n"));
+        // Stored counts are untouched
+        assert_eq!(hist.top, 5);
+        assert_eq!(hist.vec[1].count, 5);
+    }
+
+    #[test]
+    fn display_test_hide_empty() {
+        let stats = Stats::new(&mut [-2.0, 14.0], None);
+        let options = HistogramOptions {
+            intervals: 8,
+            precision: Some(3),
+            hide_empty: true,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[-1.0, 2.0, 11.0]);
+        Paint::disable();
+        let display = format!("{hist}");
+        assert!(display.contains(
+            "[ 2.000 ..  4.000] [1] ∎\n... (3 empty buckets) ...\n[10.000 .. 12.000] [1] ∎\n"
+        ));
+        assert!(display.ends_with("[10.000 .. 12.000] [1] ∎\n... (1 empty bucket) ...\n"));
+        assert!(!display.contains("[0]"));
+    }
+
+    #[test]
+    fn display_test_partial_blocks_no_color() {
+        let stats = Stats::new(&mut [-2.0, 14.0], None);
+        let options = HistogramOptions {
+            intervals: 8,
+            precision: Some(3),
+            partial_blocks: true,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[-1.0, -1.1, 2.0, 2.0, 2.1]);
+        Paint::disable();
+        let display = format!("{hist}");
+        assert!(display.contains("Each █ represents a count of 1\n"));
+        assert!(display.contains("[-2.000 ..  0.000] [2] ██\n"));
+        assert!(display.contains("[ 2.000 ..  4.000] [3] ███\n"));
+    }
+
+    #[test]
+    fn json_test() {
+        let stats = Stats::new(&mut [-2.0, 14.0], None);
+        let options = HistogramOptions {
+            intervals: 2,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[-1.0, 1.0, 11.0, 20.0]);
+        let json: serde_json::Value = serde_json::from_str(&hist.to_json()).unwrap();
+        assert_eq!(json["stats"]["min"], -2.0);
+        assert_eq!(json["stats"]["max"], 14.0);
+        assert_eq!(json["stats"]["samples"], 2);
+        assert_eq!(json["buckets"][0]["range"]["start"], -2.0);
+        assert_eq!(json["buckets"][0]["range"]["end"], 6.0);
+        assert_eq!(json["buckets"][0]["count"], 2);
+        assert_eq!(json["buckets"][1]["range"]["start"], 6.0);
+        assert_eq!(json["buckets"][1]["range"]["end"], 14.0);
+        assert_eq!(json["buckets"][1]["count"], 1);
+        assert_eq!(json["top"], 2);
+        assert_eq!(json["step"], 8.0);
+        assert_eq!(json["max"], 14.0);
+        assert_eq!(json["overflow"], 1);
+        assert!(json["stats"].get("precision").is_none());
+    }
+
+    #[test]
+    fn csv_test() {
+        let stats = Stats::new(&mut [-2.0, 14.0], None);
+        let options = HistogramOptions {
+            intervals: 3,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[
+            -1.0, -1.1, 2.0, 2.0, 2.1, -0.9, 11.0, 11.2, 1.9, 1.99, 1.98, 1.97, 1.96, 15.0,
+        ]);
+        let csv = hist.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("range_start,range_end,count"));
+        assert_eq!(lines.next(), Some("-2,3.333333333333333,11"));
+        let mut total = 0;
+        for line in csv.lines().skip(1) {
+            let columns: Vec<&str> = line.split(',').collect();
+            assert_eq!(columns.len(), 3);
+            total += columns[2].parse::<usize>().unwrap();
+        }
+        assert_eq!(total, 13);
+    }
+
+    #[test]
+    fn tsv_test() {
+        let stats = Stats::new(&mut [-2.0, 14.0], None);
+        let options = HistogramOptions {
+            intervals: 3,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[-1.0, -1.1, 2.0, 11.0, 11.2, 15.0]);
+        let tsv = hist.to_tsv();
+        assert_eq!(tsv.lines().count(), 3);
+        assert_eq!(tsv.lines().next(), Some("-2\t3.333333333333333\t3"));
+        for line in tsv.lines() {
+            assert_eq!(line.split('\t').count(), 3);
+            assert!(!line.contains('\x1b'));
+        }
+    }
+
+    #[test]
+    fn markdown_test() {
+        let stats = Stats::new(&mut [-2.0, 14.0], None);
+        let options = HistogramOptions {
+            intervals: 3,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[-1.0, -1.1, 2.0, 2.0, 2.1, 11.0, 11.2]);
+        let markdown = hist.to_markdown();
+        let mut lines = markdown.lines();
+        assert_eq!(lines.next(), Some("| Range | Count | Bar |"));
+        assert_eq!(lines.next(), Some("| --- | ---: | --- |"));
+        assert_eq!(
+            lines.next(),
+            Some("| -2 .. 3.333333333333333 | 5 | ∎∎∎∎∎ |")
+        );
+        assert_eq!(lines.count(), 2);
+        assert!(!markdown.contains('\u{1b}'));
+    }
+
+    #[test]
+    fn markdown_test_long_bars() {
+        let stats = Stats::new(&mut [0.0, 10.0], None);
+        let options = HistogramOptions {
+            intervals: 2,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[1.0; 99]);
+        let markdown = hist.to_markdown();
+        let bar = format!("| 0 .. 5 | 99 | {} |", "∎".repeat(49));
+        assert!(markdown.lines().any(|line| line == bar));
+    }
+
+    #[test]
+    fn display_test_sampled() {
+        let mut vec = [1.0, 2.0, 3.0];
+        let hist = Histogram::new(&mut vec, HistogramOptions::default()).with_sampled_from(30);
+        Paint::disable();
+        let display = format!("{hist}");
+        assert!(display.starts_with("Sampled 3 of 30 values\nSamples = 3;"));
+    }
+
+    #[test]
+    fn add_weighted_test() {
+        let stats = Stats::new(&mut [0.0, 4.0], None);
+        let options = HistogramOptions {
+            intervals: 2,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.add_weighted(1.0, 1000);
+        hist.add_weighted(3.0, 10);
+        hist.add(3.5);
+        hist.add_weighted(9.0, 7);
+        assert_eq!(hist.vec[0].count, 1000);
+        assert_eq!(hist.vec[1].count, 11);
+        assert_eq!(hist.top, 1000);
+        assert_eq!(hist.overflow(), 7);
+    }
+
+    #[test]
+    fn total_test() {
+        let stats = Stats::new(&mut [0.0, 4.0], None);
+        let options = HistogramOptions {
+            intervals: 4,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        assert_eq!(hist.len(), 4);
+        assert!(hist.is_empty());
+        hist.load(&[1.0, 2.0, -1.0, 9.0]);
+        hist.add_weighted(3.0, 5);
+        assert_eq!(hist.total(), 7);
+        assert!(!hist.is_empty());
+        let mut other = Histogram::new_with_stats(Stats::new(&mut [0.0, 4.0], None), &options);
+        other.add(0.5);
+        hist.merge(&other).unwrap();
+        assert_eq!(hist.total(), 8);
+        assert_eq!(hist.len(), 4);
+    }
+
+    #[test]
+    fn display_test_no_decimals() {
+        let mut vec = [1.0, 2.0, 2.0, 10.0, 100.0, 150.0, 200.0];
+        let options = HistogramOptions {
+            intervals: 4,
+            precision: Some(0),
+            ..Default::default()
+        };
+        let hist = Histogram::new(&mut vec, options);
+        Paint::disable();
+        let display = format!("{hist}");
+        assert!(display.contains("\n[  1 ..  51] [4] ∎∎∎∎\n"));
+        assert!(display.contains("\n[ 51 .. 100] [1] ∎\n"));
+        assert!(display.contains("\n[150 .. 200] [1] ∎\n"));
+        assert!(display.contains("Samples = 7; Min = 1; Max = 200\n"));
+    }
+
+    #[test]
+    fn display_test_scientific() {
+        let mut vec = [0.0, 1.0, 2e9, 4e9];
+        let options = HistogramOptions {
+            intervals: 4,
+            precision: Some(1),
+            scientific: Some(1e6),
+            ..Default::default()
+        };
+        let hist = Histogram::new(&mut vec, options);
+        Paint::disable();
+        let display = format!("{hist}");
+        assert!(display.contains("\n[  0.0 .. 1.0e9] [2] ∎∎\n"));
+        assert!(display.contains("\n[1.0e9 .. 2.0e9] [0] \n"));
+        assert!(display.contains("\n[3.0e9 .. 4.0e9] [1] ∎\n"));
+    }
+
+    #[test]
+    fn empty_test() {
+        Paint::disable();
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let reader = crate::read::DataReader::default();
+        let mut vec = reader.read_files(&[file.path().to_str().unwrap()]);
+        assert!(vec.is_empty());
+        let hist = Histogram::new(&mut vec, HistogramOptions::default());
+        assert_eq!(hist.buckets().map(|(_, count)| count).sum::<usize>(), 0);
+        assert_eq!(format!("{hist}"), "Samples = 0\n");
+        let hist = Histogram::auto(&mut vec, HistogramOptions::default());
+        assert_eq!(format!("{hist}"), "Samples = 0\n");
+        let options = HistogramOptions {
+            nice: true,
+            ..Default::default()
+        };
+        let hist = Histogram::new(&mut vec, options);
+        assert_eq!(format!("{hist}"), "Samples = 0\n");
+    }
+
+    #[test]
+    fn display_test_count_width_powers_of_ten() {
+        Paint::disable();
+        for top in [100, 1000] {
+            let options = HistogramOptions {
+                intervals: 2,
+                precision: Some(0),
+                ..Default::default()
+            };
+            let mut hist = Histogram::new_with_stats(Stats::new(&mut [0.0, 10.0], None), &options);
+            hist.load(&vec![1.0; top]);
+            hist.load(&[9.0; 5]);
+            let display = format!("{hist:200}");
+            let rows: Vec<&str> = display.lines().filter(|l| l.starts_with('[')).collect();
+            let width = top.to_string().len();
+            assert!(rows[0].starts_with(&format!("[ 0 ..  5] [{top}] ")));
+            assert!(rows[1].starts_with(&format!("[ 5 .. 10] [{:>width$}] ", 5)));
+        }
+    }
+
+    #[test]
+    fn display_test_quantile_markers() {
+        let mut vec: Vec<f64> = (0..100).map(f64::from).collect();
+        vec.extend([45.0; 20]);
+        let options = HistogramOptions {
+            intervals: 4,
+            precision: Some(0),
+            quantile_markers: true,
+            ..Default::default()
+        };
+        let histogram = Histogram::new(&mut vec, options);
+        Paint::disable();
+        let display = format!("{histogram}");
+        assert!(display.contains("\nMarkers: │ p50 = 45; ┃ p90 = 87; ║ p99 = 98\n"));
+        // The median is in second bucket (24.75 .. 49.5), near its end
+        let rows: Vec<&str> = display.lines().filter(|l| l.starts_with('[')).collect();
+        assert_eq!(
+            rows[1],
+            "[25 .. 50] [45] ∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎∎│∎∎∎∎∎∎∎∎"
+        );
+        // Markers are drawn over the bars, that keep their length
+        for (row, count) in rows.iter().zip([25, 45, 25, 25]) {
+            assert_eq!(row.chars().skip(16).count(), count);
+        }
+        assert!(rows[3].contains('┃') && rows[3].contains('║'));
+        assert!(!rows[0].contains('│'));
+    }
+
+    #[test]
+    fn display_test_intensity() {
+        let stats = Stats::new(&mut [0.0, 4.0], None);
+        let options = HistogramOptions {
+            intervals: 4,
+            precision: Some(0),
+            intensity: true,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[0.5, 1.5, 1.5, 2.5, 2.5, 2.5, 3.5, 3.5, 3.5, 3.5]);
+        Paint::disable();
+        let display = format!("{hist}");
+        assert!(display.contains("[0 .. 1] [1] ░\n"));
+        assert!(display.contains("[1 .. 2] [2] ▒▒\n"));
+        assert!(display.contains("[2 .. 3] [3] ▓▓▓\n"));
+        assert!(display.contains("[3 .. 4] [4] ████\n"));
+    }
+
+    #[test]
+    fn display_test_zero_axis() {
+        let stats = Stats::new(&mut [-2.0, 2.0], None);
+        let options = HistogramOptions {
+            intervals: 4,
+            precision: Some(0),
+            zero_axis: true,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[-1.5, -1.5, -0.5, 0.5, 1.5, 1.5]);
+        Paint::disable();
+        let display = format!("{hist:32}");
+        // Bars get 9 chars at each side of the axis
+        assert!(display.contains("[-2 .. -1] [2]        ∎∎┆\n"));
+        assert!(display.contains("[-1 ..  0] [1]         ∎┆\n"));
+        assert!(display.contains("[ 0 ..  1] [1]          ┆∎\n"));
+        assert!(display.contains("[ 1 ..  2] [2]          ┆∎∎\n"));
+    }
+
+    #[test]
+    fn display_test_vertical() {
+        let mut vec = [1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 3.0, 3.0, 3.0];
+        let options = HistogramOptions {
+            intervals: 3,
+            precision: Some(0),
+            orientation: Orientation::Vertical,
+            height: 3,
+            ..Default::default()
+        };
+        let histogram = Histogram::new(&mut vec, options);
+        Paint::disable();
+        let display = format!("{histogram:20}");
+        let rows: Vec<&str> = display.lines().skip(4).collect();
+        // Only the column of the top count reaches the highest row
+        assert_eq!(
+            rows,
+            [
+                "6 │        ███",
+                "  │        ███",
+                "  │███ ███ ███",
+                "0 └───────────",
+                "   1         3",
+            ]
+        );
+    }
+
+    #[test]
+    fn nice_test() {
+        let mut vec = [0.13, 0.2, 0.35, 0.71, 1.58, 2.4718];
+        let options = HistogramOptions {
+            intervals: 5,
+            nice: true,
+            ..Default::default()
+        };
+        let hist = Histogram::new(&mut vec, options);
+        assert_float_eq!(hist.step, 0.5, rmax <= f64::EPSILON);
+        assert_eq!(hist.len(), 5);
+        for (range, _) in hist.buckets() {
+            for edge in [range.start, range.end] {
+                let multiple = edge / hist.step;
+                assert_float_eq!(multiple, multiple.round(), abs <= 1e-9);
+            }
+        }
+        assert_float_eq!(hist.vec[0].range.start, 0.0, abs <= f64::EPSILON);
+        assert_float_eq!(hist.vec[4].range.end, 2.5, rmax <= f64::EPSILON);
+        assert_eq!(hist.total(), 6);
+        assert_eq!(hist.find_slot(0.71), Some(1));
+        // Edges of small steps are exact
+        let mut vec = [0.31, 0.4, 0.58];
+        let options = HistogramOptions {
+            intervals: 3,
+            nice: true,
+            ..Default::default()
+        };
+        let hist = Histogram::new(&mut vec, options);
+        let edges: Vec<f64> = hist.buckets().map(|(range, _)| range.start).collect();
+        assert_eq!(edges, [0.3, 0.4, 0.5]);
+        assert_eq!(hist.vec[2].range.end, 0.6);
+        assert_eq!(hist.total(), 3);
+    }
+
+    #[test]
+    fn compare_test() {
+        let options = HistogramOptions {
+            intervals: 2,
+            precision: Some(1),
+            ..Default::default()
+        };
+        let stats = Stats::new(&mut [0.0, 10.0], Some(1));
+        let mut before = Histogram::new_with_stats(stats.clone(), &options);
+        before.load(&[1.0, 2.0, 3.0, 8.0]);
+        let mut after = Histogram::new_with_stats(stats, &options);
+        after.load(&[1.0, 6.0, 7.0, 8.0, 9.0]);
+        Paint::disable();
+        let display = format!("{}", before.compare(&after).unwrap());
+        assert_eq!(
+            display,
+            "Counts: A = 4; B = 5\n\
+             Each ∎ represents a count of 1\n\
+             [ 0.0 ..  5.0] A [3] ∎∎∎\n\
+             \x20              B [1] ∎\n\
+             [ 5.0 .. 10.0] A [1] ∎\n\
+             \x20              B [4] ∎∎∎∎\n"
+        );
+        // Bars are scaled like the ones of the histograms themselves
+        let mut vec = vec![1.0; 500];
+        vec.push(9.0);
+        let big = Histogram::new(&mut vec, options.clone());
+        let legend = |display: String| {
+            let mut lines = display.lines();
+            lines
+                .find(|line| line.starts_with("Each"))
+                .map(String::from)
+        };
+        let expected = Some(String::from("Each ∎ represents a count of 11"));
+        assert_eq!(legend(format!("{big:60}")), expected);
+        assert_eq!(
+            legend(format!("{:60}", big.compare(&big).unwrap())),
+            expected
+        );
+        let other = Histogram::new(&mut [1.0, 2.0, 3.0], options);
+        assert!(before.compare(&other).is_err());
+    }
+
+    #[test]
+    fn densities_test() {
+        let mut vec = [1.0, 2.0, 2.0, 3.0, 4.0, 4.0, 4.0, 5.0];
+        let options = HistogramOptions {
+            intervals: 4,
+            ..Default::default()
+        };
+        let histogram = Histogram::new(&mut vec, options);
+        let area: f64 = histogram
+            .densities()
+            .map(|(range, density)| density * (range.end - range.start))
+            .sum();
+        assert_float_eq!(area, 1.0, rmax <= 1e-12);
+        let mut vec = [1.0, 10.0, 20.0, 150.0, 999.0, 1000.0];
+        let options = HistogramOptions {
+            intervals: 3,
+            ..Default::default()
+        };
+        let histogram = Histogram::new_logarithmic(&mut vec, options).unwrap();
+        let area: f64 = histogram
+            .densities()
+            .map(|(range, density)| density * (range.end - range.start))
+            .sum();
+        assert_float_eq!(area, 1.0, rmax <= 1e-12);
+    }
+
+    #[test]
+    fn display_test_density() {
+        let mut vec = [1.0, 2.0, 2.0, 3.0, 4.0, 4.0, 4.0, 5.0];
+        let options = HistogramOptions {
+            intervals: 4,
+            precision: Some(1),
+            density: true,
+            ..Default::default()
+        };
+        let histogram = Histogram::new(&mut vec, options);
+        Paint::disable();
+        let display = format!("{histogram}");
+        assert!(display.contains("\nEach ∎ represents a density of 0.1250\n"));
+        assert!(display.contains("\n[1.0 .. 2.0] [0.1250] ∎\n"));
+        assert!(display.contains("\n[4.0 .. 5.0] [0.5000] ∎∎∎∎\n"));
+    }
+
+    #[test]
+    fn display_test_human_counts() {
+        let mut vec = [1.0, 2.0];
+        let options = HistogramOptions {
+            intervals: 2,
+            precision: Some(1),
+            human_counts: true,
+            ..Default::default()
+        };
+        let mut histogram = Histogram::new(&mut vec, options);
+        histogram.add_weighted(1.0, 1_234_566);
+        Paint::disable();
+        let display = format!("{histogram:50}");
+        assert!(display.contains("\n[1.0 .. 1.5] [1.2M] "));
+        assert!(display.contains("\n[1.5 .. 2.0] [   1] \n"));
+        assert!(histogram.to_tsv().contains("\t1234567\n"));
+    }
+
+    #[test]
+    fn display_test_min_bar_len() {
+        let mut vec = vec![1.0; 1000];
+        vec.push(2.0);
+        let options = HistogramOptions {
+            intervals: 2,
+            precision: Some(1),
+            min_bar_len: 1,
+            ..Default::default()
+        };
+        let histogram = Histogram::new(&mut vec, options.clone());
+        Paint::disable();
+        let display = format!("{histogram:50}");
+        assert!(display.contains("\n[1.5 .. 2.0] [   1] ∎\n"));
+        let options = HistogramOptions {
+            min_bar_len: 0,
+            ..options
+        };
+        let histogram = Histogram::new(&mut vec, options);
+        let display = format!("{histogram:50}");
+        assert!(display.contains("\n[1.5 .. 2.0] [   1] \n"));
+    }
+
+    #[test]
+    fn display_test_hide_legend_and_stats() {
+        let mut vec = vec![1.0, 2.0, 2.0, 3.0];
+        let options = HistogramOptions {
+            intervals: 2,
+            precision: Some(1),
+            ..Default::default()
+        };
+        let histogram = Histogram::new(&mut vec, options.clone());
+        Paint::disable();
+        let display = format!("{histogram:50}");
+        assert!(display.contains("Each ∎ represents a count of 1\n"));
+        let histogram = Histogram::new(
+            &mut vec,
+            HistogramOptions {
+                hide_legend: true,
+                ..options.clone()
+            },
+        );
+        let display = format!("{histogram:50}");
+        assert!(!display.contains("represents"));
+        assert!(display.starts_with("Samples = 4; "));
+        let histogram = Histogram::new(
+            &mut vec,
+            HistogramOptions {
+                hide_legend: true,
+                hide_stats: true,
+                ..options
+            },
+        );
+        let display = format!("{histogram:50}");
+        assert_eq!(display, "[1.0 .. 2.0] [1] ∎\n[2.0 .. 3.0] [3] ∎∎∎\n");
+    }
+
+    #[test]
+    fn threshold_test() {
+        let mut vec: Vec<f64> = (1..=100).map(|x| x as f64).collect();
+        let options = HistogramOptions {
+            intervals: 10,
+            integer: true,
+            threshold: Some(30.0),
+            ..Default::default()
+        };
+        let histogram = Histogram::new(&mut vec, options);
+        assert_float_eq!(histogram.fraction_at_most(30.0), 0.3, abs <= 1e-12);
+        assert_float_eq!(histogram.fraction_at_most(35.5), 0.35, abs <= 1e-12);
+        assert_float_eq!(histogram.fraction_at_most(0.0), 0.0, abs <= 1e-12);
+        assert_float_eq!(histogram.fraction_at_most(100.0), 1.0, abs <= 1e-12);
+        assert_float_eq!(histogram.fraction_at_most(500.0), 1.0, abs <= 1e-12);
+        Paint::disable();
+        let display = format!("{histogram:50}");
+        assert!(display.contains("\n30.0% ≤ 30\n"));
+        assert!(display.contains("\n[ 21 ..  31] [10] ∎∎∎∎∎∎∎∎∎∎\n┈┈┈┈┈┈┈┈┈┈┈┈ 30\n[ 31 .."));
+        // Values in the bucket of the threshold are taken as evenly spread
+        let mut vec = [1.0, 1.0, 2.0, 3.0];
+        let histogram = Histogram::new(
+            &mut vec,
+            HistogramOptions {
+                intervals: 2,
+                ..Default::default()
+            },
+        );
+        assert_float_eq!(histogram.fraction_at_most(1.5), 0.25, abs <= 1e-12);
+        assert_float_eq!(histogram.fraction_at_most(2.5), 0.75, abs <= 1e-12);
+        // Clipped values below the range count as below a threshold below it
+        let mut vec: Vec<f64> = (1..=98).map(|i| i as f64).collect();
+        vec.extend([1000.0, -1000.0]);
+        let options = HistogramOptions {
+            intervals: 10,
+            ..Default::default()
+        };
+        let histogram = Histogram::auto_clipped(&mut vec, 1.0, 99.0, options).unwrap();
+        assert_eq!(histogram.underflow(), 1);
+        assert_float_eq!(histogram.fraction_at_most(-20.0), 0.01, abs <= 1e-12);
+        assert_float_eq!(histogram.fraction_at_most(-1000.0), 0.01, abs <= 1e-12);
+        assert_float_eq!(histogram.fraction_at_most(-2000.0), 0.0, abs <= 1e-12);
+        assert_float_eq!(histogram.fraction_at_most(-9.01), 0.01, abs <= 1e-12);
+    }
+
+    #[test]
+    fn display_test_max_rows() {
+        let mut vec: Vec<f64> = (0..100).map(|x| x as f64).collect();
+        let options = HistogramOptions {
+            intervals: 10,
+            precision: Some(0),
+            max_rows: Some(4),
+            ..Default::default()
+        };
+        let histogram = Histogram::new(&mut vec, options);
+        assert_eq!(histogram.merge_factor(), 4);
+        let merged = histogram.merged(4);
+        let counts: Vec<usize> = merged.vec.iter().map(|bucket| bucket.count).collect();
+        let sums: Vec<usize> = histogram
+            .vec
+            .chunks(4)
+            .map(|chunk| chunk.iter().map(|bucket| bucket.count).sum())
+            .collect();
+        assert_eq!(counts, sums);
+        assert_eq!(counts, [40, 40, 20]);
+        assert_eq!(histogram.vec.len(), 10);
+        Paint::disable();
+        let display = format!("{histogram:50}");
+        assert!(display.contains("\nEvery row merges 4 buckets\n"));
+        assert!(display.contains("\n[ 0 .. 40] [40] "));
+        assert!(display.contains("\n[79 .. 99] [20] "));
+        assert_eq!(display.lines().filter(|l| l.starts_with('[')).count(), 3);
     }
 
-    fn build_buckets(range: Range<f64>, options: &HistogramOptions) -> Vec<Bucket> {
-        let mut vec = Vec::<Bucket>::with_capacity(options.intervals);
-        if options.log_scale {
-            let first_bucket_size = range.end / (2_f64.powi(options.intervals as i32) - 1.0);
-            let mut lower = 0.0;
-            for i in 0..options.intervals {
-                let upper = lower + 2_f64.powi(i as i32) * first_bucket_size;
-                vec.push(Bucket::new(lower..upper));
-                lower = upper;
-            }
-        } else {
-            let step = (range.end - range.start) / options.intervals as f64;
-            let mut lower = range.start;
-            for _ in 0..options.intervals {
-                vec.push(Bucket::new(lower..lower + step));
-                lower += step;
-            }
+    #[test]
+    fn display_test_width() {
+        let mut vec = vec![1.0; 100];
+        vec.push(2.0);
+        let options = HistogramOptions {
+            intervals: 1,
+            precision: Some(1),
+            width: Some(30),
+            ..Default::default()
+        };
+        let histogram = Histogram::new(&mut vec, options.clone());
+        Paint::disable();
+        let fixed = format!("{histogram}");
+        assert_eq!(fixed, format!("{histogram:80}"));
+        assert!(fixed.contains("\n[1.0 .. 2.0] [101] ∎∎∎∎∎∎∎∎∎∎∎∎∎∎\n"));
+        let options = HistogramOptions {
+            width: Some(0),
+            ..options
+        };
+        let histogram = Histogram::new(&mut vec, options);
+        assert_eq!(format!("{histogram:0}"), format!("{histogram:110}"));
+    }
+
+    #[test]
+    fn integer_test() {
+        // 11 integers from 0 to 10 in groups of 3
+        let mut vec: Vec<f64> = (0..=10).map(|i| i as f64).collect();
+        let options = HistogramOptions {
+            intervals: 4,
+            integer: true,
+            ..Default::default()
+        };
+        let hist = Histogram::new(&mut vec, options);
+        let ranges: Vec<Range<f64>> = hist.vec.iter().map(|b| b.range.clone()).collect();
+        assert_eq!(ranges, [0.0..3.0, 3.0..6.0, 6.0..9.0, 9.0..12.0]);
+        let counts: Vec<usize> = hist.vec.iter().map(|b| b.count).collect();
+        assert_eq!(counts, [3, 3, 3, 2]);
+        // Values on edges always go to the bucket they start
+        for (value, slot) in [(3.0, 1), (5.999, 1), (6.0, 2), (10.0, 3), (11.0, 3)] {
+            assert_eq!(hist.find_slot(value), Some(slot));
         }
-        vec
+        assert_eq!(hist.find_slot(-0.5), None);
+        assert_eq!(hist.find_slot(12.0), None);
+        Paint::disable();
+        let display = format!("{hist}");
+        assert!(display.contains("\n[ 0 ..  3] [3] ∎∎∎\n"));
+        assert!(display.contains("\n[ 9 .. 12] [2] ∎∎\n"));
+        // Fractional values are rounded down
+        let mut vec = [0.1 + 0.2, 0.7, 1.0, 2.0, 3.0];
+        let options = HistogramOptions {
+            intervals: 4,
+            integer: true,
+            ..Default::default()
+        };
+        let hist = Histogram::new(&mut vec, options);
+        let counts: Vec<usize> = hist.vec.iter().map(|b| b.count).collect();
+        assert_eq!(counts, [2, 1, 1, 1]);
     }
-}
 
-impl fmt::Display for Histogram {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.stats)?;
-        let formatter = match self.precision {
-            None => F64Formatter::new_with_range(self.stats.min..self.stats.max),
-            Some(n) => F64Formatter::new(n),
+    #[test]
+    fn display_test_interval_notation() {
+        let mut vec = [1.0, 2.0, 2.0, 3.0];
+        let options = HistogramOptions {
+            intervals: 2,
+            precision: Some(1),
+            interval_notation: true,
+            ..Default::default()
         };
-        let writer = HistWriter {
-            width: f.width().unwrap_or(110),
-            formatter,
+        let histogram = Histogram::new(&mut vec, options.clone());
+        Paint::disable();
+        let display = format!("{histogram}");
+        assert!(display.contains("\n[1.0, 2.0) [1] ∎\n"));
+        assert!(display.contains("\n[2.0, 3.0] [3] ∎∎∎\n"));
+        // The maximum is out of the last bucket if it is open
+        let options = HistogramOptions {
+            closed_last: false,
+            ..options
         };
-        writer.write(f, self)
+        let histogram = Histogram::new(&mut vec, options);
+        let display = format!("{histogram}");
+        assert!(display.contains("\n[2.0, 3.0) [2] ∎∎\n"));
+        assert!(display.contains("\nabove range: 1\n"));
+        assert_eq!(histogram.find_slot(2.999), Some(1));
+        assert_eq!(histogram.find_slot(3.0), None);
     }
-}
 
-struct HistWriter {
-    width: usize,
-    formatter: F64Formatter,
-}
+    #[test]
+    fn display_test_highlight() {
+        let mut vec = [1.0, 2.0, 2.0, 3.0, 4.0, 4.0, 4.0];
+        let options = HistogramOptions {
+            intervals: 3,
+            precision: Some(1),
+            highlight: vec![2.5, 10.0],
+            ..Default::default()
+        };
+        let histogram = Histogram::new(&mut vec, options);
+        Paint::disable();
+        let display = format!("{histogram}");
+        assert!(display.contains("\n[1.0 .. 2.0] [1] ∎\n"));
+        assert!(display.contains("\n[2.0 .. 3.0] [2] ∎∎ ◀\n"));
+        assert!(display.contains("\n[3.0 .. 4.0] [4] ∎∎∎∎\n"));
+    }
 
-impl HistWriter {
-    pub fn write(&self, f: &mut fmt::Formatter, hist: &Histogram) -> fmt::Result {
-        let width_range = self.get_width(hist);
-        let width_count = ((hist.top as f64).log10().ceil() as usize).max(1);
-        let horizontal_scale =
-            HorizontalScale::new(hist.top / self.get_max_bar_len(width_range + width_count));
-        writeln!(f, "{horizontal_scale}")?;
-        for x in &hist.vec {
-            self.write_bucket(f, x, &horizontal_scale, width_range, width_count)?;
-        }
-        Ok(())
+    #[test]
+    fn display_test_reverse() {
+        let mut vec = [1.0, 2.0, 2.0, 3.0, 4.0, 4.0, 4.0];
+        let options = HistogramOptions {
+            intervals: 3,
+            precision: Some(1),
+            cumulative: true,
+            reverse: true,
+            ..Default::default()
+        };
+        let histogram = Histogram::new(&mut vec, options);
+        Paint::disable();
+        let display = format!("{histogram}");
+        let rows: Vec<&str> = display.lines().filter(|l| l.starts_with('[')).collect();
+        assert_eq!(
+            rows,
+            [
+                "[3.0 .. 4.0] [7] ∎∎∎∎∎∎∎",
+                "[2.0 .. 3.0] [3] ∎∎∎",
+                "[1.0 .. 2.0] [1] ∎"
+            ]
+        );
     }
 
-    fn write_bucket(
-        &self,
-        f: &mut fmt::Formatter,
-        bucket: &Bucket,
-        horizontal_scale: &HorizontalScale,
-        width: usize,
-        width_count: usize,
-    ) -> fmt::Result {
-        writeln!(
-            f,
-            "[{range}] [{count}] {bar}",
-            range = Blue.paint(format!(
-                "{:>width$} .. {:>width$}",
-                self.formatter.format(bucket.range.start),
-                self.formatter.format(bucket.range.end),
-                width = width,
-            )),
-            count = horizontal_scale.get_count(bucket.count, width_count),
-            bar = horizontal_scale.get_bar(bucket.count)
-        )
+    #[test]
+    fn from_reader_test() {
+        use std::io::Write;
+        Paint::disable();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for n in [-1.0, 1.0, 2.0, 2.5, 7.0, 11.0] {
+            writeln!(file, "{}", n).unwrap();
+        }
+        let path = file.path().to_str().unwrap();
+        let options = HistogramOptions {
+            intervals: 2,
+            precision: Some(1),
+            ..Default::default()
+        };
+        let hist = Histogram::from_reader(&DataReader::default(), &[path], 0.0..10.0, &options);
+        let counts: Vec<usize> = hist.buckets().map(|(_, count)| count).collect();
+        assert_eq!(counts, [3, 1]);
+        assert_eq!((hist.underflow(), hist.overflow()), (1, 1));
+        assert_eq!(hist.stats.samples, 4);
+        assert_float_eq!(hist.stats.avg, 3.125, rmax <= f64::EPSILON);
+        let display = format!("{hist}");
+        assert!(display.starts_with("Samples = 4; Min = 0.0; Max = 10.0\n"));
+        assert!(display.contains("\n[ 0.0 ..  5.0] [3] ∎∎∎\n"));
     }
 
-    fn get_width(&self, hist: &Histogram) -> usize {
-        self.formatter
-            .format(hist.stats.min)
-            .len()
-            .max(self.formatter.format(hist.stats.max).len())
+    #[test]
+    fn non_finite_test() {
+        let mut vec = [1.0, f64::NAN, 2.0, f64::INFINITY, 3.0, 4.0];
+        let options = HistogramOptions {
+            intervals: 2,
+            ..Default::default()
+        };
+        let mut hist = Histogram::new(&mut vec, options);
+        hist.add(f64::NEG_INFINITY);
+        let counts: Vec<usize> = hist.buckets().map(|(_, count)| count).collect();
+        assert_eq!(counts, [2, 2]);
+        assert_eq!(hist.underflow() + hist.overflow(), 0);
+        assert_float_eq!(hist.stats.max, 4.0, rmax <= f64::EPSILON);
     }
 
-    fn get_max_bar_len(&self, fixed_width: usize) -> usize {
-        const EXTRA_CHARS: usize = 10;
-        if self.width < fixed_width + EXTRA_CHARS {
-            75
-        } else {
-            self.width - fixed_width - EXTRA_CHARS
-        }
+    #[test]
+    fn buckets_test() {
+        let options = HistogramOptions {
+            intervals: 2,
+            ..Default::default()
+        };
+        let hist = Histogram::new(&mut [1.0, 2.0, 3.0, 5.0], options);
+        let buckets: Vec<(&Range<f64>, usize)> = hist.buckets().collect();
+        assert_eq!(buckets, [(&(1.0..3.0), 2), (&(3.0..5.0), 2)]);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use float_eq::assert_float_eq;
-    use yansi::Paint;
+    #[test]
+    fn merge_test() {
+        let options = HistogramOptions {
+            intervals: 4,
+            ..Default::default()
+        };
+        let shard = |values: &[f64]| {
+            let mut hist = Histogram::new_with_stats(Stats::new(&mut [0.0, 8.0], None), &options);
+            hist.load(values);
+            hist
+        };
+        let mut hist = shard(&[0.5, 1.0, 2.5, 7.0, 9.0]);
+        let other = shard(&[1.5, 4.0, 4.5, 5.0, 6.0, -1.0]);
+        hist.merge(&other).unwrap();
+        let counts: Vec<usize> = hist.vec.iter().map(|b| b.count).collect();
+        assert_eq!(counts, [3, 1, 3, 2]);
+        assert_eq!(hist.top, 3);
+        assert_eq!(hist.underflow(), 1);
+        assert_eq!(hist.overflow(), 1);
+        assert_eq!(hist.stats.samples, 4);
+
+        let mut a = Histogram::new(&mut [1.0, 2.0, 3.0, 2.0], options.clone());
+        let b = Histogram::new(&mut [1.0, 2.0, 5.0, 5.0], options.clone());
+        assert!(a.merge(&b).is_err());
+        let c = Histogram::new(&mut [3.0, 1.0, 1.0, 1.0], options.clone());
+        a.merge(&c).unwrap();
+        assert_eq!(a.stats.samples, 8);
+        assert_float_eq!(a.stats.median(), 1.5, rmax <= f64::EPSILON);
+        assert_eq!(a.vec.iter().map(|b| b.count).sum::<usize>(), 8);
+        let d = Histogram::new(&mut [1.0, 2.0, 3.0], HistogramOptions::default());
+        let err = a.merge(&d).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot merge histograms: 4 buckets vs 1 buckets"
+        );
+    }
 
     #[test]
-    fn test_buckets() {
-        let stats = Stats::new(&mut [-2.0, 14.0], None);
+    fn render_test() {
+        let mut vec = [-1.0, -1.1, 2.0, 2.0, 2.1, -0.9, 11.0, 11.2, 1.9, 1.99];
         let options = HistogramOptions {
-            intervals: 8,
+            intervals: 4,
+            show_percent: true,
             ..Default::default()
         };
-        let mut hist = Histogram::new_with_stats(stats, &options);
-        hist.load(&[
-            -1.0, -1.1, 2.0, 2.0, 2.1, -0.9, 11.0, 11.2, 1.9, 1.99, 1.98, 1.97, 1.96,
-        ]);
+        let hist = Histogram::new(&mut vec, options);
+        let mut output = Vec::new();
+        hist.render(&mut output, 60).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), format!("{hist:60}"));
+    }
 
-        assert_eq!(hist.top, 5);
-        let bucket = &hist.vec[0];
-        assert_eq!(bucket.range, -2.0..0.0);
-        assert_eq!(bucket.count, 3);
-        let bucket = &hist.vec[1];
-        assert_eq!(bucket.count, 5);
-        assert_eq!(bucket.range, 0.0..2.0);
+    #[test]
+    fn render_error_test() {
+        struct Failing;
+        impl io::Write for Failing {
+            fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let hist = Histogram::new(&mut [1.0, 2.0], HistogramOptions::default());
+        let err = hist.render(&mut Failing, 80).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
     }
 
     #[test]
-    fn test_buckets_bad_stats() {
+    fn svg_test() {
+        let stats = Stats::new(&mut [0.0, 4.0], None);
         let options = HistogramOptions {
-            intervals: 6,
+            intervals: 2,
+            precision: Some(1),
             ..Default::default()
         };
-        let mut hist = Histogram::new_with_stats(Stats::new(&mut [-2.0, 4.0], None), &options);
-        hist.load(&[-1.0, 2.0, -1.0, 2.0, 10.0, 10.0, 10.0, -10.0]);
-        assert_eq!(hist.top, 2);
+        let mut hist = Histogram::new_with_stats(stats, &options);
+        hist.load(&[0.0, 1.0, 3.0, 3.5]);
+        hist.add(4.0);
+        let svg = hist.to_svg_with_palette(250, 140, &["red", "blue"]);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"250\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains(
+            "<rect x=\"50.00\" y=\"43.33\" width=\"100.00\" height=\"66.67\" fill=\"red\" \
+             stroke=\"white\"><title>0.0 .. 2.0: 2</title></rect>"
+        ));
+        assert!(svg.contains(
+            "<rect x=\"150.00\" y=\"10.00\" width=\"100.00\" height=\"100.00\" fill=\"blue\" \
+             stroke=\"white\"><title>2.0 .. 4.0: 3</title></rect>"
+        ));
+        assert!(svg.contains(">3</text>"));
+        assert!(svg.contains(">4.0</text>"));
+        assert_eq!(svg.matches("<text").count(), 5);
+        assert_eq!(hist.to_svg(250, 140).matches("#d62728").count(), 2);
     }
 
     #[test]
-    fn display_test() {
+    fn display_test_glyph() {
         let stats = Stats::new(&mut [-2.0, 14.0], None);
         let options = HistogramOptions {
             intervals: 8,
             precision: Some(3),
+            glyph: '#',
+            theme: Theme {
+                bar: Color::Cyan,
+                ..Default::default()
+            },
             ..Default::default()
         };
         let mut hist = Histogram::new_with_stats(stats, &options);
-        hist.load(&[
-            -1.0, -1.1, 2.0, 2.0, 2.1, -0.9, 11.0, 11.2, 1.9, 1.99, 1.98, 1.97, 1.96,
-        ]);
+        hist.load(&[-1.0, -1.1, 2.0, 2.0, 2.1]);
         Paint::disable();
         let display = format!("{hist}");
-        assert!(display.contains("[-2.000 ..  0.000] [3] ∎∎∎\n"));
-        assert!(display.contains("[ 0.000 ..  2.000] [5] ∎∎∎∎∎\n"));
-        assert!(display.contains("[ 2.000 ..  4.000] [3] ∎∎∎\n"));
-        assert!(display.contains("[ 6.000 ..  8.000] [0] \n"));
-        assert!(display.contains("[10.000 .. 12.000] [2] ∎∎\n"));
+        assert!(display.contains("Each # represents a count of 1\n"));
+        assert!(display.contains("[-2.000 ..  0.000] [2] ##\n"));
+        assert!(display.contains("[ 2.000 ..  4.000] [3] ###\n"));
     }
 
     #[test]
-    fn display_test_bad_width() {
+    fn display_test_percent() {
+        let stats = Stats::new(&mut [-2.0, 14.0], None);
         let options = HistogramOptions {
             intervals: 8,
             precision: Some(3),
+            show_percent: true,
             ..Default::default()
         };
-        let mut hist = Histogram::new_with_stats(Stats::new(&mut [-2.0, 14.0], None), &options);
+        let mut hist = Histogram::new_with_stats(stats, &options);
         hist.load(&[
-            -1.0, -1.1, 2.0, 2.0, 2.1, -0.9, 11.0, 11.2, 1.9, 1.99, 1.98, 1.97, 1.96,
+            -1.0, -1.1, 2.0, 2.0, 2.1, -0.9, 11.0, 11.2, 1.9, 1.99, 1.98, 1.97, 1.96, 20.0,
         ]);
         Paint::disable();
-        let display = format!("{hist:2}");
-        assert!(display.contains("[-2.000 ..  0.000] [3] ∎∎∎\n"));
-    }
-
-    #[test]
-    fn display_test_human_units() {
-        let vector = &mut [
-            -1.0,
-            -12000000.0,
-            -12000001.0,
-            -12000002.0,
-            -12000003.0,
-            -2000000.0,
-            500000.0,
-            500000.0,
-        ];
-        let hist = Histogram::new(
-            vector,
-            HistogramOptions {
-                intervals: 10,
-                ..Default::default()
-            },
-        );
-        Paint::disable();
         let display = format!("{hist}");
-        assert!(display.contains("[-12.0 M .. -10.4 M] [4] ∎∎∎∎\n"));
-        assert!(display.contains("[ -2.6 M ..  -1.1 M] [1] ∎\n"));
-        assert!(display.contains("[ -1.1 M ..   0.5 M] [3] ∎∎∎\n"));
-        assert!(display.contains("Samples = 8; Min = -12.0 M; Max = 0.5 M"));
-        assert!(display.contains("Average = -6.1 M;"));
-    }
-
-    #[test]
-    fn display_test_log_scale() {
-        let hist = Histogram::new(
-            &mut [0.4, 0.4, 0.4, 0.4, 255.0, 0.2, 1.2, 128.0, 126.0, -7.0],
-            HistogramOptions {
-                intervals: 8,
-                log_scale: true,
-                ..Default::default()
-            },
-        );
-        Paint::disable();
+        assert!(display.contains("[-2.000 ..  0.000] [3] (23.1%) ∎∎∎\n"));
+        assert!(display.contains("[ 0.000 ..  2.000] [5] (38.5%) ∎∎∎∎∎\n"));
+        assert!(display.contains("[ 6.000 ..  8.000] [0] ( 0.0%) \n"));
+        assert!(display.contains("[10.000 .. 12.000] [2] (15.4%) ∎∎\n"));
+        let mut hist = Histogram::new_with_stats(Stats::new(&mut [-2.0, 14.0], None), &options);
+        hist.load(&[-1.0]);
         let display = format!("{hist}");
-        assert!(display.contains("[  0.00 ..   1.00] [5] ∎∎∎∎∎\n"));
-        assert!(display.contains("[  1.00 ..   3.00] [1] ∎\n"));
-        assert!(display.contains("[  3.00 ..   7.00] [0]"));
-        assert!(display.contains("[  7.00 ..  15.00] [0]"));
-        assert!(display.contains("[ 15.00 ..  31.00] [0]"));
-        assert!(display.contains("[ 31.00 ..  63.00] [0]"));
-        assert!(display.contains("[ 63.00 .. 127.00] [1] ∎\n"));
-        assert!(display.contains("[127.00 .. 255.00] [2] ∎∎\n"));
+        assert!(display.contains("[-2.000 ..  0.000] [1] (100.0%) ∎\n"));
+        assert!(display.contains("[ 0.000 ..  2.000] [0] (  0.0%) \n"));
     }
 
     #[test]
@@ -389,10 +3038,10 @@ mod tests {
         };
         let buckets = Histogram::build_buckets(0.0..700.0, &options);
         assert!(buckets.len() == 7);
-        for i in 0..6 {
+        for (i, bucket) in buckets.iter().enumerate() {
             let min = (i * 100) as f64;
             let max = ((i + 1) * 100) as f64;
-            assert!(buckets[i].range == (min..max));
+            assert!(bucket.range == (min..max));
         }
     }
 
@@ -403,8 +3052,8 @@ mod tests {
             ..Default::default()
         };
         let hist = Histogram::new_with_stats(Stats::new(&mut [-12.0, 4.0], None), &options);
-        assert!(hist.find_slot(-13.0) == None);
-        assert!(hist.find_slot(13.0) == None);
+        assert!(hist.find_slot(-13.0).is_none());
+        assert!(hist.find_slot(13.0).is_none());
         assert!(hist.find_slot(-12.0) == Some(0));
         assert!(hist.find_slot(-11.0) == Some(0));
         assert!(hist.find_slot(-9.0) == Some(1));
@@ -412,6 +3061,85 @@ mod tests {
         assert!(hist.find_slot(1.1) == Some(6));
     }
 
+    #[test]
+    fn logarithmic_buckets() {
+        let hist = Histogram::new_logarithmic(
+            &mut [1.0, 10.0, 20.0, 150.0, 999.0, 1000.0],
+            HistogramOptions {
+                intervals: 3,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_float_eq!(hist.vec[0].range.start, 1.0, rmax <= f64::EPSILON);
+        assert_float_eq!(hist.vec[0].range.end, 10.0, rmax <= 4.0 * f64::EPSILON);
+        assert_float_eq!(hist.vec[1].range.end, 100.0, rmax <= 4.0 * f64::EPSILON);
+        assert_float_eq!(hist.vec[2].range.end, 1000.0, rmax <= f64::EPSILON);
+        assert_eq!(hist.find_slot(0.5), None);
+        assert_eq!(hist.find_slot(5.0), Some(0));
+        assert_eq!(hist.find_slot(50.0), Some(1));
+        assert_eq!(hist.find_slot(1000.0), Some(2));
+        assert_eq!(hist.vec[0].count, 1);
+        assert_eq!(hist.vec[1].count, 2);
+        assert_eq!(hist.vec[2].count, 3);
+    }
+
+    #[test]
+    fn logarithmic_buckets_not_positive() {
+        let options = HistogramOptions {
+            intervals: 3,
+            ..Default::default()
+        };
+        assert!(Histogram::new_logarithmic(&mut [0.0, 10.0], options.clone()).is_err());
+        assert!(Histogram::new_logarithmic(&mut [-1.0, 10.0], options).is_err());
+    }
+
+    #[test]
+    fn display_test_logarithmic_buckets() {
+        let hist = Histogram::new_logarithmic(
+            &mut [1.0, 1.5, 2.0, 5.0, 100.0, 1000000.0],
+            HistogramOptions {
+                intervals: 6,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        Paint::disable();
+        let display = format!("{hist}");
+        assert!(display.contains("[      1.000 ..      10.000] [4] ∎∎∎∎\n"));
+        assert!(display.contains("[ 100000.000 .. 1000000.000] [1] ∎\n"));
+    }
+
+    #[test]
+    fn find_slot_max_value() {
+        // With this maximum, the upper edge of last bucket is computed as
+        // 1000.0999999999999
+        let mut vec = [1000.1, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0];
+        let hist = Histogram::new(
+            &mut vec,
+            HistogramOptions {
+                intervals: 8,
+                log_scale: true,
+                ..Default::default()
+            },
+        );
+        assert!(hist.vec[7].range.end < 1000.1);
+        assert_eq!(hist.find_slot(1000.1), Some(7));
+        assert_eq!(hist.vec[7].count, 1);
+        assert_eq!(hist.overflow(), 0);
+        let hist = Histogram::new(
+            &mut vec,
+            HistogramOptions {
+                intervals: 8,
+                ..Default::default()
+            },
+        );
+        assert_eq!(hist.find_slot(1000.1), Some(7));
+        assert_eq!(hist.find_slot(1000.1 + 1e-13), Some(7));
+        assert_eq!(hist.find_slot(1000.2), None);
+        assert_eq!(hist.vec[7].count, 1);
+    }
+
     #[test]
     fn find_slot_logarithmic() {
         let hist = Histogram::new(
@@ -423,7 +3151,7 @@ mod tests {
                 ..Default::default()
             },
         );
-        assert!(hist.find_slot(-1.0) == None);
+        assert!(hist.find_slot(-1.0).is_none());
         assert!(hist.find_slot(0.0) == Some(0));
         assert!(hist.find_slot(0.5) == Some(0));
         assert!(hist.find_slot(1.5) == Some(1));
@@ -431,6 +3159,44 @@ mod tests {
         assert!(hist.find_slot(33.1) == Some(5));
         assert!(hist.find_slot(127.1) == Some(7));
         assert!(hist.find_slot(247.1) == Some(7));
-        assert!(hist.find_slot(1000.0) == None);
+        assert!(hist.find_slot(1000.0).is_none());
+    }
+
+    #[test]
+    fn single_value_test() {
+        let mut vec = [3.0, 3.0, 3.0];
+        let mut hist = Histogram::new(
+            &mut vec,
+            HistogramOptions {
+                intervals: 4,
+                ..Default::default()
+            },
+        );
+        assert_eq!(hist.vec.len(), 1);
+        assert!(hist.vec[0].range == (2.5..3.5));
+        assert_eq!(hist.vec[0].count, 3);
+        assert_eq!(hist.find_slot(3.0), Some(0));
+        assert_eq!(hist.find_slot(3.1), Some(0));
+        assert_eq!(hist.find_slot(2.5), Some(0));
+        assert!(hist.find_slot(2.4).is_none());
+        assert!(hist.find_slot(3.6).is_none());
+        hist.add(3.2);
+        hist.add(4.0);
+        assert_eq!(hist.vec[0].count, 4);
+        assert_eq!(hist.overflow(), 1);
+        Paint::disable();
+        let display = format!("{:2}", hist);
+        assert!(display.contains("Samples = 3; Min = 3.000; Max = 3.000\n"));
+        assert!(display.contains("[2.500 .. 3.500] [4] ∎∎∎∎\n"));
+        let hist = Histogram::new_logarithmic(
+            &mut vec,
+            HistogramOptions {
+                intervals: 4,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(hist.vec.len(), 1);
+        assert_eq!(hist.vec[0].count, 3);
     }
 }
@@ -3,6 +3,7 @@ use std::fmt;
 use yansi::Color::Blue;
 
 use crate::format::HorizontalScale;
+use crate::plot::display_width;
 
 #[derive(Debug)]
 /// A struct that represents a single match bar of a match bar histogram (a
@@ -55,7 +56,7 @@ impl MatchBar {
 
 impl fmt::Display for MatchBar {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let width = f.width().unwrap_or(100);
+        let width = display_width(f, 100);
         let horizontal_scale = HorizontalScale::new(self.top_values / width);
         let width_count = format!("{}", self.top_values).len();
         writeln!(
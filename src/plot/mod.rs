@@ -1,17 +1,110 @@
-pub use self::histogram::{Histogram, HistogramOptions};
+pub use self::boxplot::BoxPlot;
+pub use self::ecdf::Ecdf;
+pub use self::heatmap::Heatmap;
+pub use self::histogram::{
+    BarScale, Histogram, HistogramComparison, HistogramOptions, MergeError, Orientation,
+    DEFAULT_SVG_PALETTE,
+};
 pub use self::matchbar::{MatchBar, MatchBarRow};
+pub use self::qqplot::QQPlot;
 pub use self::splittimehist::SplitTimeHistogram;
 pub use self::terms::CommonTerms;
+pub use self::theme::Theme;
 pub use self::timehist::TimeHistogram;
 pub use self::xy::XyPlot;
 
+use std::fmt;
+use std::str::FromStr;
+
+mod boxplot;
+mod ecdf;
+mod heatmap;
 mod histogram;
 mod matchbar;
+mod qqplot;
 mod splittimehist;
 mod terms;
+mod theme;
 mod timehist;
 mod xy;
 
+/// The format charts are written in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Colored text for reading in a terminal.
+    #[default]
+    Human,
+    /// Tab-separated lines with no header nor colors, for unix pipelines.
+    Tsv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(OutputFormat::Human),
+            "tsv" => Ok(OutputFormat::Tsv),
+            _ => Err(format!("Unknown output format '{}'", s)),
+        }
+    }
+}
+
+/// Charts whose output can be split in lines, for placing them in a pane of a
+/// text UI where rows are laid out (or scrolled) one by one.
+pub trait RenderLines: fmt::Display {
+    /// Returns the lines of the chart as displayed with `width` chars, with
+    /// colors applied if painting is enabled, and with no line breaks.
+    fn render_lines(&self, width: usize) -> Vec<String> {
+        format!("{self:width$}").lines().map(String::from).collect()
+    }
+}
+
+impl RenderLines for BoxPlot {}
+impl RenderLines for CommonTerms {}
+impl RenderLines for Ecdf {}
+impl RenderLines for Heatmap {}
+impl RenderLines for Histogram {}
+impl RenderLines for HistogramComparison<'_> {}
+impl RenderLines for MatchBar {}
+impl RenderLines for QQPlot {}
+impl RenderLines for SplitTimeHistogram {}
+impl RenderLines for TimeHistogram {}
+impl RenderLines for XyPlot {}
+
+/// Returns the width of the terminal stdout is attached to, or None if stdout
+/// is not a terminal.  The COLUMNS environment variable, when set, takes
+/// precedence over the size reported by the terminal.
+pub fn detect_width() -> Option<usize> {
+    if atty::isnt(atty::Stream::Stdout) {
+        return None;
+    }
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|columns| *columns > 0)
+        .or_else(terminal_width)
+}
+
+#[cfg(unix)]
+fn terminal_width() -> Option<usize> {
+    // SAFETY: winsize is a plain C struct, and TIOCGWINSZ only writes into it
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (result == 0 && size.ws_col > 0).then_some(size.ws_col as usize)
+}
+
+#[cfg(not(unix))]
+fn terminal_width() -> Option<usize> {
+    None
+}
+
+/// Returns the width a chart is displayed with: the one of the formatter, or
+/// `fallback` when it is missing or zero.
+fn display_width(f: &fmt::Formatter, fallback: usize) -> usize {
+    f.width().filter(|width| *width > 0).unwrap_or(fallback)
+}
+
 /// Returns a datetime formatting string with a resolution that makes sense for a
 /// given number of seconds
 fn date_fmt_string(seconds: i64) -> &'static str {
@@ -35,4 +128,27 @@ mod tests {
         assert_eq!(date_fmt_string(10), "%H:%M:%S%.3f");
         assert_eq!(date_fmt_string(0), "%H:%M:%S%.6f");
     }
+
+    #[test]
+    fn test_render_lines() {
+        let mut vec = [1.0, 2.0, 2.0];
+        let options = HistogramOptions {
+            intervals: 2,
+            precision: Some(1),
+            ..Default::default()
+        };
+        let histogram = Histogram::new(&mut vec, options);
+        yansi::Paint::disable();
+        let lines = histogram.render_lines(40);
+        assert_eq!(lines.join("\n") + "\n", format!("{histogram:40}"));
+        assert_eq!(lines.last().unwrap(), "[1.5 .. 2.0] [2] ∎∎");
+        assert!(lines.iter().all(|line| !line.contains('\n')));
+    }
+
+    #[test]
+    fn test_output_format() {
+        assert_eq!("human".parse(), Ok(OutputFormat::Human));
+        assert_eq!("tsv".parse(), Ok(OutputFormat::Tsv));
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
 }
@@ -0,0 +1,203 @@
+use std::fmt;
+
+use yansi::Color::{Blue, Red};
+
+use crate::format::F64Formatter;
+use crate::plot::display_width;
+use crate::stats::{normal, Stats};
+
+#[derive(Debug)]
+/// A struct holding data to plot the quantiles of a set of numerical values
+/// against the ones of a normal distribution (a Q-Q plot).  Values are
+/// standardized with their mean and standard deviation, so that values
+/// following a normal distribution fall close to the diagonal, while skewed
+/// or heavy tailed ones bend away from it.
+pub struct QQPlot {
+    sorted: Vec<f64>,
+    stats: Stats,
+    height: usize,
+    precision: Option<usize>,
+}
+
+impl QQPlot {
+    /// Creates a QQPlot from a vector of numerical data.
+    ///
+    /// `height` is the number of "rows" to display.  The number of "columns"
+    /// depends on the width used for the display.
+    ///
+    /// `precision` is an Option with the number of decimals to display.  If
+    /// "None" is used, human units will be used, with an heuristic based on the
+    /// input data for deciding the units and the decimal places.
+    pub fn new(vec: &mut [f64], height: usize, precision: Option<usize>) -> Self {
+        let stats = Stats::new(vec, precision);
+        Self {
+            sorted: vec.iter().copied().filter(|x| x.is_finite()).collect(),
+            stats,
+            height: height.max(1),
+            precision,
+        }
+    }
+
+    /// Returns a point for every input value, in increasing order: the
+    /// quantile of the standard normal distribution for its rank (with
+    /// plotting positions `(i - 0.5) / n`), and the value standardized with
+    /// the mean and the sample standard deviation of data.
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        let len = self.sorted.len() as f64;
+        let std = self.stats.std_dev(true);
+        self.sorted
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let theoretical = normal::quantile((i as f64 + 0.5) / len);
+                let sample = if std > 0.0 {
+                    (x - self.stats.avg) / std
+                } else {
+                    0.0
+                };
+                (theoretical, sample)
+            })
+            .collect()
+    }
+}
+
+// Returns the cell where a value falls when splitting `-limit..limit` in
+// `cells` cells of the same size.
+fn find_cell(value: f64, limit: f64, cells: usize) -> usize {
+    let cell = ((value + limit) / (2.0 * limit) * cells as f64).max(0.0) as usize;
+    cell.min(cells - 1)
+}
+
+// Returns the value at the center of a cell when splitting `-limit..limit`
+// in `cells` cells of the same size.
+fn cell_center(cell: usize, limit: f64, cells: usize) -> f64 {
+    limit * ((2 * cell + 1) as f64 / cells as f64 - 1.0)
+}
+
+impl fmt::Display for QQPlot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let formatter = match self.precision {
+            None => F64Formatter::new_with_range(self.stats.min..self.stats.max),
+            Some(n) => F64Formatter::new(n),
+        };
+        writeln!(
+            f,
+            "Samples = {len}; Average = {avg}; Sample STD = {std}",
+            len = Blue.paint(self.sorted.len()),
+            avg = Blue.paint(formatter.format(self.stats.avg)),
+            std = Blue.paint(formatter.format(self.stats.std_dev(true))),
+        )?;
+        let points = self.points();
+        if points.is_empty() {
+            return Ok(());
+        }
+        // Both axes share the same range, so that the diagonal is y = x
+        let limit = points
+            .iter()
+            .map(|(x, y)| x.abs().max(y.abs()))
+            .fold(0.0, f64::max)
+            .max(1.0);
+        let y_formatter = F64Formatter::new(2);
+        let labels: Vec<String> = (0..self.height)
+            .map(|row| y_formatter.format(cell_center(row, limit, self.height)))
+            .collect();
+        let label_width = labels.iter().map(|l| l.len()).max().unwrap_or(1);
+        // 3 chars are used for the brackets and space around labels
+        let columns = display_width(f, 80).saturating_sub(label_width + 3).max(1);
+        let mut grid = vec![vec![' '; columns]; self.height];
+        // The diagonal is drawn first, so that points are drawn over it
+        let diagonal = (0..columns).map(|column| cell_center(column, limit, columns));
+        for (column, x) in diagonal.enumerate() {
+            grid[find_cell(x, limit, self.height)][column] = '.';
+        }
+        for (x, y) in points.iter() {
+            grid[find_cell(*y, limit, self.height)][find_cell(*x, limit, columns)] = '*';
+        }
+        for (row, label) in grid.iter().zip(labels.iter()).rev() {
+            let cells: String = row
+                .iter()
+                .map(|cell| match cell {
+                    '*' => Red.paint(cell).to_string(),
+                    _ => cell.to_string(),
+                })
+                .collect();
+            writeln!(
+                f,
+                "[{}] {}",
+                Blue.paint(format!("{:>width$}", label, width = label_width)),
+                cells
+            )?;
+        }
+        writeln!(
+            f,
+            "Normal quantiles = {low} .. {high}; Standardized values on the left",
+            low = Blue.paint(y_formatter.format(-limit)),
+            high = Blue.paint(y_formatter.format(limit)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use yansi::Paint;
+
+    // Returns `len` values of a normal distribution, with the Box-Muller
+    // transform.
+    fn normal_sample(len: usize, mean: f64, std: f64) -> Vec<f64> {
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..len)
+            .map(|_| {
+                let u: f64 = 1.0 - rng.gen::<f64>();
+                let v: f64 = rng.gen();
+                let z = (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos();
+                z.mul_add(std, mean)
+            })
+            .collect()
+    }
+
+    // Returns the largest distance to the diagonal of the points of the
+    // central 90% of values.
+    fn central_deviation(qqplot: &QQPlot) -> f64 {
+        let points = qqplot.points();
+        let tail = points.len() / 20;
+        points[tail..points.len() - tail]
+            .iter()
+            .map(|(x, y)| (x - y).abs())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn normal_data_test() {
+        let mut vec = normal_sample(2000, 50.0, 5.0);
+        let qqplot = QQPlot::new(&mut vec, 10, None);
+        assert_eq!(qqplot.points().len(), 2000);
+        assert!(central_deviation(&qqplot) < 0.1);
+        // Exponentially distributed values are skewed
+        let mut skewed: Vec<f64> = (1..2000).map(|i| -(i as f64 / 2000.0).ln()).collect();
+        assert!(central_deviation(&QQPlot::new(&mut skewed, 10, None)) > 0.3);
+    }
+
+    #[test]
+    fn display_test() {
+        let mut vec = [-1.0, 0.0, 0.0, 1.0];
+        let qqplot = QQPlot::new(&mut vec, 3, Some(1));
+        Paint::disable();
+        let display = format!("{qqplot:15}");
+        assert!(display.starts_with("Samples = 4; Average = 0.0; Sample STD = 0.8\n"));
+        assert!(display.contains("\n[ 0.82]      .*\n"));
+        assert!(display.contains("\n[ 0.00]   *.*  \n"));
+        assert!(display.contains("\n[-0.82] *.     \n"));
+        assert!(display
+            .ends_with("Normal quantiles = -1.22 .. 1.22; Standardized values on the left\n"));
+    }
+
+    #[test]
+    fn constant_data_test() {
+        let qqplot = QQPlot::new(&mut [2.0, 2.0, 2.0], 3, None);
+        assert!(qqplot.points().iter().all(|(_, y)| *y == 0.0));
+        assert_eq!(format!("{qqplot}").lines().count(), 5);
+    }
+}
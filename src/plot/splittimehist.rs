@@ -4,7 +4,7 @@ use chrono::{DateTime, Duration, FixedOffset};
 use yansi::Color::{Blue, Cyan, Green, Magenta, Red};
 
 use crate::format::{HorizontalScale, BAR_CHAR};
-use crate::plot::date_fmt_string;
+use crate::plot::{date_fmt_string, display_width};
 
 const COLORS: &[yansi::Color] = &[Red, Blue, Magenta, Green, Cyan];
 
@@ -131,7 +131,7 @@ impl SplitTimeHistogram {
             write!(
                 f,
                 "{}",
-                COLORS[i].paint(BAR_CHAR.repeat(row.count[i] / divisor).to_string())
+                COLORS[i].paint(BAR_CHAR.to_string().repeat(row.count[i] / divisor))
             )?;
         }
         writeln!(f)
@@ -140,7 +140,7 @@ impl SplitTimeHistogram {
 
 impl fmt::Display for SplitTimeHistogram {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let width = f.width().unwrap_or(100);
+        let width = display_width(f, 100);
         let total = self.vec.iter().map(|r| r.total()).sum::<usize>();
         let top = self.vec.iter().map(|r| r.total()).max().unwrap_or(1);
         let horizontal_scale = HorizontalScale::new(top / width);
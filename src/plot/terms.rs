@@ -4,6 +4,7 @@ use std::fmt;
 use yansi::Color::Blue;
 
 use crate::format::HorizontalScale;
+use crate::plot::display_width;
 
 #[derive(Debug)]
 /// A struct holding data to plot a Histogram of the most frequent terms in an
@@ -35,13 +36,15 @@ impl CommonTerms {
 
 impl fmt::Display for CommonTerms {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let width = f.width().unwrap_or(100);
+        let width = display_width(f, 100);
         let mut counts: Vec<(&String, &usize)> = self.terms.iter().collect();
         if counts.is_empty() {
             writeln!(f, "No data")?;
             return Ok(());
         }
-        counts.sort_by(|a, b| b.1.cmp(a.1));
+        // Ties are broken by term, so that output does not depend on the
+        // order of the hash map
+        counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
         let values = &counts[..self.lines.min(counts.len())];
         let label_width = values.iter().fold(1, |acc, x| acc.max(x.0.len()));
         let horizontal_scale = HorizontalScale::new(counts[0].1 / width);
@@ -56,6 +59,10 @@ impl fmt::Display for CommonTerms {
                 bar = horizontal_scale.get_bar(**count)
             )?;
         }
+        let others: usize = counts[values.len()..].iter().map(|(_, c)| **c).sum();
+        if others > 0 {
+            writeln!(f, "(others: {})", Blue.paint(others))?;
+        }
         Ok(())
     }
 }
@@ -92,5 +99,36 @@ mod tests {
         assert!(display.contains("[   foo] [100] ∎∎∎∎∎∎∎∎∎∎\n"));
         assert!(display.contains("[barbar] [ 20] ∎∎\n"));
         assert!(!display.contains("arr"));
+        assert!(display.ends_with("(others: 10)\n"));
+    }
+
+    #[test]
+    fn test_common_terms_ties() {
+        let mut terms = CommonTerms::new(3);
+        for term in ["zeta", "alpha", "mu", "zeta", "alpha", "beta"] {
+            terms.observe(String::from(term));
+        }
+        Paint::disable();
+        let display = format!("{terms:10}");
+        let lines: Vec<&str> = display.lines().skip(2).collect();
+        assert_eq!(
+            lines,
+            [
+                "[alpha] [2] ∎∎",
+                "[ zeta] [2] ∎∎",
+                "[ beta] [1] ∎",
+                "(others: 1)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_common_terms_no_others() {
+        let mut terms = CommonTerms::new(2);
+        terms.observe(String::from("foo"));
+        terms.observe(String::from("bar"));
+        Paint::disable();
+        let display = format!("{terms}");
+        assert!(!display.contains("others"));
     }
 }
@@ -0,0 +1,57 @@
+use yansi::Color::{self, Blue, Cyan, Green, Red, Unset, White, Yellow};
+
+/// The colors used for drawing an histogram.  Colors are only used when
+/// painting is enabled (see `yansi::Paint`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Color of the bars.
+    pub bar: Color,
+    /// Color of the ranges of the buckets.
+    pub range: Color,
+    /// Color of the counts (or densities) of the buckets.
+    pub count: Color,
+    /// Color of the numbers in the legends above the buckets.
+    pub legend: Color,
+}
+
+impl Theme {
+    /// Creates a custom theme.
+    pub const fn new(bar: Color, range: Color, count: Color, legend: Color) -> Self {
+        Self {
+            bar,
+            range,
+            count,
+            legend,
+        }
+    }
+
+    /// A theme with no colors, where only the text attributes of the
+    /// terminal are used.
+    pub const fn monochrome() -> Self {
+        Self::new(Unset, Unset, Unset, Unset)
+    }
+
+    /// A theme with bright colors, readable on dark backgrounds.
+    pub const fn high_contrast() -> Self {
+        Self::new(Yellow, White, Cyan, White)
+    }
+}
+
+impl Default for Theme {
+    /// Red bars, blue ranges and legends and green counts.
+    fn default() -> Self {
+        Self::new(Red, Blue, Green, Blue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_test() {
+        assert_eq!(Theme::default().bar, Red);
+        assert_eq!(Theme::monochrome(), Theme::new(Unset, Unset, Unset, Unset));
+        assert_ne!(Theme::high_contrast(), Theme::default());
+    }
+}
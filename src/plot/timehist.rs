@@ -4,7 +4,7 @@ use chrono::{DateTime, Duration, FixedOffset};
 use yansi::Color::Blue;
 
 use crate::format::HorizontalScale;
-use crate::plot::date_fmt_string;
+use crate::plot::{date_fmt_string, display_width};
 
 #[derive(Debug)]
 struct TimeBucket {
@@ -22,6 +22,9 @@ impl TimeBucket {
     }
 }
 
+/// Max number of buckets for a `TimeHistogram` with fixed intervals.
+pub const MAX_TIME_BUCKETS: i64 = 10_000;
+
 #[derive(Debug)]
 /// A struct holding data to plot a `TimeHistogram` of timestamp data.
 pub struct TimeHistogram {
@@ -32,6 +35,8 @@ pub struct TimeHistogram {
     top: usize,
     last: usize,
     nanos: u64,
+    // Window of the moving average to display along with counts, if any
+    moving_average: Option<usize>,
 }
 
 impl TimeHistogram {
@@ -55,11 +60,98 @@ impl TimeHistogram {
             top: 0,
             last: size - 1,
             nanos: (max - min).num_microseconds().unwrap() as u64,
+            moving_average: None,
         };
         timehist.load(ts);
         timehist
     }
 
+    /// Creates a Histogram from a vector of `DateTime` elements, with buckets
+    /// of a fixed `interval` (like a minute or an hour).
+    ///
+    /// Buckets are aligned to multiples of the interval in the wall clock of
+    /// the earliest timestamp, so that buckets are comparable across inputs.
+    /// Fails for non-positive intervals, or when more than `MAX_TIME_BUCKETS`
+    /// buckets would be needed.
+    pub fn new_with_interval(
+        interval: Duration,
+        ts: &[DateTime<FixedOffset>],
+    ) -> Result<Self, String> {
+        let interval_us = match interval.num_microseconds() {
+            Some(us) if us > 0 => us,
+            _ => return Err(String::from("Interval should be positive")),
+        };
+        let first = *ts.iter().min().unwrap();
+        let last = *ts.iter().max().unwrap();
+        let local_us = first.naive_local().timestamp_micros();
+        let min = first - Duration::microseconds(local_us.rem_euclid(interval_us));
+        let size = (last - min).num_microseconds().unwrap() / interval_us + 1;
+        if size > MAX_TIME_BUCKETS {
+            return Err(format!(
+                "Interval is too small, {} buckets would be needed (max is {})",
+                size, MAX_TIME_BUCKETS
+            ));
+        }
+        let vec = (0..size)
+            .map(|i| TimeBucket::new(min + interval * i as i32))
+            .collect();
+        let max = min + interval * size as i32;
+        let mut timehist = Self {
+            vec,
+            min,
+            max,
+            step: max - min,
+            top: 0,
+            last: size as usize - 1,
+            nanos: (max - min).num_microseconds().unwrap() as u64,
+            moving_average: None,
+        };
+        timehist.load(ts);
+        Ok(timehist)
+    }
+
+    /// Displays the centered moving average of counts (see `moving_average`)
+    /// with this window along with the counts of every bucket.
+    pub fn with_moving_average(mut self, window: usize) -> Self {
+        self.moving_average = Some(window);
+        self
+    }
+
+    /// Returns the centered moving average of the counts of buckets, averaging
+    /// `window` buckets around every bucket.  Windows shrink at the edges (so
+    /// that averages there are not biased toward zero), and even windows take
+    /// one more bucket from the left side.
+    pub fn moving_average(&self, window: usize) -> Vec<f64> {
+        let window = window.max(1);
+        let len = self.vec.len();
+        (0..len)
+            .map(|i| {
+                let start = i.saturating_sub(window / 2);
+                let end = (i + (window - 1) / 2 + 1).min(len);
+                let sum: usize = self.vec[start..end].iter().map(|b| b.count).sum();
+                sum as f64 / (end - start) as f64
+            })
+            .collect()
+    }
+
+    /// Returns a tab-separated representation of the buckets of the
+    /// histogram, meant for unix pipelines: a `range_start range_end count`
+    /// line per bucket, with no header and no colors.  Timestamps are printed
+    /// in RFC 3339 format.
+    pub fn to_tsv(&self) -> String {
+        let mut tsv = String::new();
+        for (i, bucket) in self.vec.iter().enumerate() {
+            let end = self.vec.get(i + 1).map_or(self.max, |next| next.start);
+            tsv.push_str(&format!(
+                "{}\t{}\t{}\n",
+                bucket.start.to_rfc3339(),
+                end.to_rfc3339(),
+                bucket.count
+            ));
+        }
+        tsv
+    }
+
     /// Add to the `TimeHistogram` data the values of a slice of `DateTime`
     /// elements.  Elements not in the initial range (the one passed to `new`)
     /// will be silently discarded.
@@ -84,12 +176,11 @@ impl TimeHistogram {
             None
         } else {
             let x = (ts - self.min).num_microseconds().unwrap() as u64;
-            if self.nanos == 0 {
+            match (x * self.vec.len() as u64).checked_div(self.nanos) {
+                Some(slot) => Some((slot as usize).min(self.last)),
                 // All timestamps are the same.  We will have a degenerate plot
                 // (as opposed to failing hard).
-                Some(0)
-            } else {
-                Some(((x * self.vec.len() as u64 / self.nanos) as usize).min(self.last))
+                None => Some(0),
             }
         }
     }
@@ -97,7 +188,7 @@ impl TimeHistogram {
 
 impl fmt::Display for TimeHistogram {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let width = f.width().unwrap_or(100);
+        let width = display_width(f, 100);
         let horizontal_scale = HorizontalScale::new(self.top / width);
         let width_count = format!("{}", self.top).len();
         writeln!(
@@ -110,10 +201,23 @@ impl fmt::Display for TimeHistogram {
         )?;
         writeln!(f, "{horizontal_scale}")?;
         let ts_fmt = date_fmt_string(self.step.num_seconds());
-        for row in &self.vec {
+        let averages: Vec<String> = match self.moving_average {
+            Some(window) => self
+                .moving_average(window)
+                .iter()
+                .map(|avg| format!("{:.1}", avg))
+                .collect(),
+            None => Vec::new(),
+        };
+        let width_average = averages.iter().map(|s| s.len()).max().unwrap_or(0);
+        for (i, row) in self.vec.iter().enumerate() {
+            let average = match averages.get(i) {
+                Some(avg) => format!(" (~{:>width$})", avg, width = width_average),
+                None => String::new(),
+            };
             writeln!(
                 f,
-                "[{label}] [{count}] {bar}",
+                "[{label}] [{count}]{average} {bar}",
                 label = Blue.paint(format!("{}", row.start.format(ts_fmt))),
                 count = horizontal_scale.get_count(row.count, width_count),
                 bar = horizontal_scale.get_bar(row.count)
@@ -165,6 +269,66 @@ mod tests {
         assert!(display.contains("[04:25:00.004750] [1] ∎\n"));
     }
 
+    #[test]
+    fn test_fixed_interval() {
+        Paint::disable();
+        let vec = vec![
+            DateTime::parse_from_rfc3339("2022-04-15T04:25:10+02:00").unwrap(),
+            DateTime::parse_from_rfc3339("2022-04-15T04:25:59+02:00").unwrap(),
+            DateTime::parse_from_rfc3339("2022-04-15T04:26:00+02:00").unwrap(),
+            DateTime::parse_from_rfc3339("2022-04-15T04:28:30+02:00").unwrap(),
+        ];
+        let th = TimeHistogram::new_with_interval(Duration::minutes(1), &vec).unwrap();
+        let display = format!("{th}");
+        assert!(display.contains("Matches: 4"));
+        assert!(display.contains("[04:25:00.000] [2] ∎∎\n"));
+        assert!(display.contains("[04:26:00.000] [1] ∎\n"));
+        assert!(display.contains("[04:27:00.000] [0] \n"));
+        assert!(display.contains("[04:28:00.000] [1] ∎\n"));
+        assert!(!display.contains("04:29"));
+        let th = TimeHistogram::new_with_interval(Duration::hours(1), &vec).unwrap();
+        let display = format!("{th}");
+        assert!(display.contains("[04:00:00] [4] ∎∎∎∎\n"));
+        assert!(TimeHistogram::new_with_interval(Duration::zero(), &vec).is_err());
+        assert!(TimeHistogram::new_with_interval(Duration::milliseconds(1), &vec).is_err());
+    }
+
+    #[test]
+    fn test_tsv() {
+        let vec = vec![
+            DateTime::parse_from_rfc3339("2022-04-15T04:25:10+02:00").unwrap(),
+            DateTime::parse_from_rfc3339("2022-04-15T04:25:59+02:00").unwrap(),
+            DateTime::parse_from_rfc3339("2022-04-15T04:26:30+02:00").unwrap(),
+        ];
+        let th = TimeHistogram::new_with_interval(Duration::minutes(1), &vec).unwrap();
+        assert_eq!(
+            th.to_tsv(),
+            "2022-04-15T04:25:00+02:00\t2022-04-15T04:26:00+02:00\t2\n\
+             2022-04-15T04:26:00+02:00\t2022-04-15T04:27:00+02:00\t1\n"
+        );
+    }
+
+    #[test]
+    fn test_moving_average() {
+        Paint::disable();
+        let vec = vec![
+            DateTime::parse_from_rfc3339("2022-04-15T04:25:00+00:00").unwrap(),
+            DateTime::parse_from_rfc3339("2022-04-15T04:25:00+00:00").unwrap(),
+            DateTime::parse_from_rfc3339("2022-04-15T04:25:00+00:00").unwrap(),
+            DateTime::parse_from_rfc3339("2022-04-15T04:26:00+00:00").unwrap(),
+            DateTime::parse_from_rfc3339("2022-04-15T04:28:00+00:00").unwrap(),
+            DateTime::parse_from_rfc3339("2022-04-15T04:28:00+00:00").unwrap(),
+        ];
+        // Counts are 3, 1, 0, 2
+        let th = TimeHistogram::new_with_interval(Duration::minutes(1), &vec).unwrap();
+        assert_eq!(th.moving_average(1), [3.0, 1.0, 0.0, 2.0]);
+        assert_eq!(th.moving_average(3), [2.0, 4.0 / 3.0, 1.0, 1.0]);
+        assert_eq!(th.moving_average(2), [3.0, 2.0, 0.5, 1.0]);
+        let display = format!("{}", th.with_moving_average(3));
+        assert!(display.contains("[04:25:00.000] [3] (~2.0) ∎∎∎\n"));
+        assert!(display.contains("[04:26:00.000] [1] (~1.3) ∎\n"));
+    }
+
     #[test]
     fn test_single_timestamp() {
         Paint::disable();
@@ -7,6 +7,9 @@ use yansi::Color::{Blue, Red};
 use crate::format::F64Formatter;
 use crate::stats::Stats;
 
+// Glyphs for the levels of a sparkline, from lowest to highest
+const SPARK_CHARS: &[char] = &['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
 #[derive(Debug)]
 /// A struct holding data to plot a XY graph.
 pub struct XyPlot {
@@ -66,8 +69,12 @@ impl XyPlot {
 
     /// Add to the `XyPlot` data the values of a slice of numerical data.
     pub fn load(&mut self, vec: &[f64]) {
-        self.width = self.width.min(vec.len());
-        let num_chunks = vec.len() / self.width;
+        // A width of zero stands for a column per value
+        if self.width == 0 {
+            self.width = vec.len();
+        }
+        self.width = self.width.min(vec.len()).max(1);
+        let num_chunks = (vec.len() / self.width).max(1);
         let iter = vec.chunks(num_chunks);
         for x in iter {
             let sum: f64 = x.iter().sum();
@@ -80,6 +87,28 @@ impl XyPlot {
     }
 }
 
+impl XyPlot {
+    /// Returns the plot as a single line of text, with a glyph per column
+    /// whose height is proportional to the value of the column.
+    pub fn sparkline(&self) -> String {
+        let range = self.stats.max - self.stats.min;
+        let top = (SPARK_CHARS.len() - 1) as f64;
+        let line: String = self
+            .x_axis
+            .iter()
+            .map(|x| {
+                let level = if range > 0.0 {
+                    ((x - self.stats.min) / range * top).round() as usize
+                } else {
+                    0
+                };
+                SPARK_CHARS[level.min(SPARK_CHARS.len() - 1)]
+            })
+            .collect();
+        Red.paint(line).to_string()
+    }
+}
+
 impl fmt::Display for XyPlot {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.stats)?;
@@ -103,7 +132,7 @@ impl fmt::Display for XyPlot {
     }
 }
 
-fn print_line(
+pub(crate) fn print_line(
     f: &mut fmt::Formatter,
     x_axis: &[f64],
     range: Range<f64>,
@@ -163,6 +192,17 @@ mod tests {
         assert!(display.contains("[-1.000] ●  ●"));
     }
 
+    #[test]
+    fn sparkline_test() {
+        let plot = XyPlot::new(&[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 3.5], 10, 5, None);
+        Paint::disable();
+        assert_eq!(plot.sparkline(), "▁▂▃▄▅▆▇█▅");
+        let plot = XyPlot::new(&[-1.0, 0.0, 1.0, 2.0, 3.0, 4.0, -1.0, 4.0], 4, 5, None);
+        assert_eq!(plot.sparkline(), "▂▅▇▅");
+        let plot = XyPlot::new(&[2.0, 2.0], 2, 5, None);
+        assert_eq!(plot.sparkline(), "▁▁");
+    }
+
     #[test]
     fn display_test_human_units() {
         let vector = &mut [1000000.0, -1000000.0, -2000000.0, -4000000.0];
@@ -1,51 +1,597 @@
-use std::io::BufRead;
-use std::ops::Range;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+use std::sync::{Arc, Mutex};
 
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use regex::Regex;
 
 use crate::plot::{CommonTerms, MatchBar, MatchBarRow};
-use crate::read::open_file;
+use crate::read::csv::{self, CsvColumn};
+use crate::read::{open_file, open_files, read_lines, Progress, SkipLast};
+use crate::stats::StatsBuilder;
 
-#[derive(Debug, Default, Builder)]
+// Function called with a skipped line and its line number
+type ErrorFn = dyn FnMut(&str, usize) + Send;
+
+/// A callback for lines with no value to parse (see
+/// `DataReaderBuilder::on_error`).  It is shared by the copies of a reader
+/// used for reading files concurrently.
+#[derive(Clone)]
+struct ErrorCallback(Arc<Mutex<ErrorFn>>);
+
+impl fmt::Debug for ErrorCallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ErrorCallback")
+    }
+}
+
+#[derive(Debug, Default, Clone, Builder)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct DataReader {
     #[builder(setter(strip_option), default)]
     range: Option<Range<f64>>,
+    // Drop values out of these (inclusive) bounds.  Set via `clamp_range`.
+    #[builder(setter(custom), default)]
+    clamp: Option<RangeInclusive<f64>>,
     #[builder(setter(strip_option), default)]
     regex: Option<Regex>,
+    // Parse only this whitespace-separated field (0-based) of every line.
+    // Ignored if a regex is set.
+    #[builder(setter(strip_option), default)]
+    field: Option<usize>,
+    // Parse input as CSV with a header row, reading values from this column.
+    // Ignored if a regex or a field is set.
+    #[builder(setter(into, strip_option), default)]
+    csv_column: Option<CsvColumn>,
+    // Parse lines as JSON, reading values from this dotted path (like
+    // `response.duration_ms`).  Ignored if a regex, a field or a CSV column is
+    // set.
+    #[builder(setter(into, strip_option), default)]
+    json_path: Option<String>,
+    // Parse only the chars at these (0-based) offsets of every line, for
+    // fixed-width columns with no delimiter.  Set via `fixed_field`.  Ignored
+    // if any of the options above is set.
+    #[builder(setter(custom), default)]
+    fixed_field: Option<Range<usize>>,
+    // Parse every whitespace-separated token of lines, instead of the whole
+    // line.  Ignored if any of the options above is set.
+    #[builder(default)]
+    split_all: bool,
+    // Remove this grouping char (like the comma in `1,234.5`) from numbers
+    // before parsing them.
+    #[builder(setter(strip_option), default)]
+    strip_separators: Option<char>,
+    // Use this char as decimal point instead of the dot.  Grouping chars are
+    // removed before the decimal point is converted.
+    #[builder(setter(strip_option), default)]
+    decimal: Option<char>,
+    // Use the comma as decimal point (like in `3,14`).  Same as setting
+    // `decimal` to a comma.
+    #[builder(default)]
+    decimal_comma: bool,
+    // Parse values as human durations (like `1m30s` or `250ms`), converted
+    // to seconds.  Supported units are ns, us, ms, s, m, h (and a few more).
+    #[builder(default)]
+    parse_duration: bool,
+    // Parse values as integers in this radix (2 to 36), like 16 for `1f` or
+    // `0x1f`.
+    #[builder(setter(strip_option), default)]
+    radix: Option<u32>,
+    // Remove trailing chars that are not digits (like units in `123ms`)
+    // before parsing values.
+    #[builder(default)]
+    strip_units: bool,
+    // Keep only a random sample of this many values (reservoir sampling), so
+    // that huge inputs do not need to fit in memory.
+    #[builder(setter(strip_option), default)]
+    sample: Option<usize>,
+    // Seed for the random numbers used for sampling, for reproducible runs.
+    #[builder(setter(strip_option), default)]
+    seed: Option<u64>,
+    // Ignore lines starting with this char (after whitespace), like `#`.
+    // Blank lines are always ignored.
+    #[builder(setter(strip_option), default)]
+    comment_char: Option<char>,
+    // Drop this many lines from the start of input (before looking for the
+    // header of CSV input).
+    #[builder(default)]
+    skip_header: usize,
+    // Drop this many lines from the end of input.  They are held in memory
+    // until the input is exhausted.
+    #[builder(default)]
+    skip_footer: usize,
+    // Parse lines as pre-aggregated `value weight` pairs (whitespace
+    // separated), where weight is the number of times value was observed.
+    // Regex, field, CSV, JSON and fixed field selectors are ignored in this
+    // mode.
+    #[builder(default)]
+    weighted: bool,
+    // Report the number of lines read so far into stderr (if it is a
+    // terminal) while reading.
+    #[builder(default)]
+    progress: bool,
+    // Called for every skipped line (see `on_error`).
+    #[builder(setter(custom), default)]
+    on_error: Option<ErrorCallback>,
+    #[builder(setter(skip))]
+    csv_index: Cell<usize>,
+    #[builder(setter(skip))]
+    misses: Cell<usize>,
+    #[builder(setter(skip))]
+    errors: Cell<usize>,
+    #[builder(setter(skip))]
+    lines: Cell<usize>,
+    #[builder(setter(skip))]
+    total: Cell<usize>,
+    #[builder(setter(skip))]
+    excluded: Cell<usize>,
+    #[builder(setter(skip))]
+    non_finite: Cell<usize>,
+}
+
+impl DataReaderBuilder {
+    /// Drops values below `low` or above `high` (when set) while reading, so
+    /// that they do not account for the stats of data.
+    pub fn clamp_range(&mut self, low: Option<f64>, high: Option<f64>) -> &mut Self {
+        self.clamp = Some(Some(
+            low.unwrap_or(f64::NEG_INFINITY)..=high.unwrap_or(f64::INFINITY),
+        ));
+        self
+    }
+
+    /// Parses only the chars from offset `start` (0-based, included) to
+    /// offset `end` (excluded) of every line, ignoring the spaces around the
+    /// number.  Lines shorter than `end` chars are skipped, and counted as
+    /// errors.
+    pub fn fixed_field(&mut self, start: usize, end: usize) -> &mut Self {
+        self.fixed_field = Some(Some(start..end));
+        self
+    }
+
+    /// Calls `callback` with every line skipped while reading because it did
+    /// not match the regex or its value could not be parsed, along with its
+    /// line number (starting at 1, and going on across inputs unless they are
+    /// read concurrently).  For instance, the first few of them can be logged
+    /// for debugging the format of an input.
+    pub fn on_error<F: FnMut(&str, usize) + Send + 'static>(&mut self, callback: F) -> &mut Self {
+        self.on_error = Some(Some(ErrorCallback(Arc::new(Mutex::new(callback)))));
+        self
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(Some(range)) = &self.fixed_field {
+            if range.is_empty() {
+                return Err(format!(
+                    "Fixed field should end after its start, not at {}..{}",
+                    range.start, range.end
+                ));
+            }
+        }
+        if let Some(Some(radix)) = self.radix {
+            if !(2..=36).contains(&radix) {
+                return Err(format!("Radix should be between 2 and 36, not {}", radix));
+            }
+        }
+        let decimal = match self.decimal_comma {
+            Some(true) => Some(','),
+            _ => self.decimal.flatten(),
+        };
+        match (self.strip_separators.flatten(), decimal) {
+            (Some(separator), Some(point)) if separator == point => Err(format!(
+                "'{}' cannot be both a grouping char and the decimal point",
+                separator
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl DataReader {
+    /// Returns the number of lines skipped by the last read because they did
+    /// not match the regex.
+    pub fn misses(&self) -> usize {
+        self.misses.get()
+    }
+
+    /// Returns the number of lines skipped by the last read because they
+    /// could not be read or their value (or the selected field) could not be
+    /// parsed as a float.
+    pub fn errors(&self) -> usize {
+        self.errors.get()
+    }
+
+    /// Returns the number of lines looked for values by the last read, not
+    /// counting skipped headers and footers, blank lines and comments.  For
+    /// `read_matches` and `read_terms`, every line read is counted.
+    pub fn lines(&self) -> usize {
+        self.lines.get()
+    }
+
+    /// Returns true if the last read looked at some lines, but none of them
+    /// held a value: likely, the input is not in the expected format.
+    pub fn nothing_parsed(&self) -> bool {
+        self.lines() > 0 && self.misses() + self.errors() == self.lines()
+    }
+
+    /// Returns the number of values dropped by the last read because they
+    /// were out of the range or the clamp range.
+    pub fn excluded(&self) -> usize {
+        self.excluded.get()
+    }
+
+    /// Returns the number of NaN or infinite values dropped by the last read.
+    pub fn non_finite(&self) -> usize {
+        self.non_finite.get()
+    }
+
+    /// Returns the number of values found by the last read, including the
+    /// ones left out of the sample (if sampling).
+    pub fn total(&self) -> usize {
+        self.total.get()
+    }
+
     pub fn read(&self, path: &str) -> Vec<f64> {
-        let mut vec: Vec<f64> = Vec::new();
-        let line_parser = match self.regex {
-            Some(_) => Self::parse_regex,
-            None => Self::parse_float,
+        self.read_files(&[path])
+    }
+
+    /// Reads values from several paths, as if they were a single input.
+    ///
+    /// If a sample size is set, only that many values, picked at random, are
+    /// returned (in the order they were found).
+    pub fn read_files(&self, paths: &[&str]) -> Vec<f64> {
+        let mut total = 0;
+        let vec = match self.sample {
+            None => {
+                let mut vec: Vec<f64> = Vec::new();
+                self.for_each_value(paths, |n| vec.push(n));
+                total = vec.len();
+                vec
+            }
+            Some(size) => {
+                let mut rng = match self.seed {
+                    Some(seed) => StdRng::seed_from_u64(seed),
+                    None => StdRng::from_entropy(),
+                };
+                // Values are kept along with their position in input
+                let mut reservoir: Vec<(usize, f64)> = Vec::with_capacity(size);
+                self.for_each_value(paths, |n| {
+                    if reservoir.len() < size {
+                        reservoir.push((total, n));
+                    } else {
+                        let slot = rng.gen_range(0..=total);
+                        if slot < size {
+                            reservoir[slot] = (total, n);
+                        }
+                    }
+                    total += 1;
+                });
+                reservoir.sort_unstable_by_key(|(position, _)| *position);
+                reservoir.into_iter().map(|(_, n)| n).collect()
+            }
+        };
+        self.total.set(total);
+        vec
+    }
+
+    /// Reads values from several paths concurrently (one thread per file, up
+    /// to the number of cpus), adding up the skip counters of every file.
+    ///
+    /// Values are not in input order.  Unlike with `read_files`, files are not
+    /// read as a single input: CSV headers, skipped header and footer lines
+    /// are looked for in each of them.  Falls back to `read_files` when
+    /// sampling.
+    #[cfg(feature = "parallel")]
+    pub fn read_files_parallel(&self, paths: &[&str]) -> Vec<f64> {
+        if self.sample.is_some() || paths.len() < 2 {
+            return self.read_files(paths);
+        }
+        // Counters are not thread safe, so every file is read with a copy
+        let readers: Vec<DataReader> = paths.iter().map(|_| self.clone()).collect();
+        let results: Vec<(DataReader, Vec<f64>)> = readers
+            .into_par_iter()
+            .zip(paths.par_iter())
+            .map(|(reader, path)| {
+                let vec = reader.read_files(&[path]);
+                (reader, vec)
+            })
+            .collect();
+        for counter in [
+            &self.misses,
+            &self.errors,
+            &self.lines,
+            &self.excluded,
+            &self.non_finite,
+        ] {
+            counter.set(0);
+        }
+        let mut vec = Vec::new();
+        for (reader, values) in results {
+            self.misses.set(self.misses.get() + reader.misses());
+            self.errors.set(self.errors.get() + reader.errors());
+            self.lines.set(self.lines.get() + reader.lines());
+            self.excluded.set(self.excluded.get() + reader.excluded());
+            self.non_finite
+                .set(self.non_finite.get() + reader.non_finite());
+            vec.extend(values);
+        }
+        self.total.set(vec.len());
+        vec
+    }
+
+    /// Feeds the values read from `path` into a StatsBuilder, without
+    /// keeping them in memory.
+    pub fn read_into(&self, path: &str, stats: &mut StatsBuilder) {
+        self.for_each_weighted_value(&[path], |n, weight| stats.push_weighted(n, weight));
+    }
+
+    /// Reads `(value, weight)` pairs from several paths, as if they were a
+    /// single input.  Unless in weighted mode, every weight is 1.
+    pub fn read_weighted(&self, paths: &[&str]) -> Vec<(f64, usize)> {
+        let mut vec: Vec<(f64, usize)> = Vec::new();
+        self.for_each_weighted_value(paths, |n, weight| vec.push((n, weight)));
+        vec
+    }
+
+    /// Calls `f` for every value read, once per unit of weight of the value
+    /// (in weighted mode).
+    fn for_each_value<F: FnMut(f64)>(&self, paths: &[&str], mut f: F) {
+        self.for_each_weighted_value(paths, |n, weight| {
+            for _ in 0..weight {
+                f(n);
+            }
+        });
+    }
+
+    pub(crate) fn for_each_weighted_value<F: FnMut(f64, usize)>(&self, paths: &[&str], mut f: F) {
+        self.misses.set(0);
+        self.errors.set(0);
+        self.lines.set(0);
+        self.excluded.set(0);
+        self.non_finite.set(0);
+        let lines = Progress::to_stderr(read_lines(open_files(paths)).enumerate(), self.progress)
+            .skip(self.skip_header);
+        let mut lines = SkipLast::new(lines, self.skip_footer)
+            .filter(|(_, line)| !matches!(line, Ok(as_string) if self.is_ignored(as_string)));
+        let line_parser = match (&self.regex, self.field, &self.csv_column) {
+            (Some(_), _, _) => Self::parse_regex,
+            (None, Some(_), _) => Self::parse_field,
+            (None, None, Some(column)) => {
+                match lines.next() {
+                    Some((_, Ok(header))) => match column.resolve(&header) {
+                        Some(index) => self.csv_index.set(index),
+                        None => {
+                            error!("Column {} not found in CSV header", column);
+                            return;
+                        }
+                    },
+                    Some((_, Err(error))) => {
+                        error!("{}", error);
+                        return;
+                    }
+                    None => return,
+                }
+                Self::parse_csv
+            }
+            (None, None, None) if self.json_path.is_some() => Self::parse_json,
+            (None, None, None) if self.fixed_field.is_some() => Self::parse_fixed,
+            (None, None, None) => Self::parse_float,
         };
-        for line in open_file(path).lines() {
+        let split_all = self.split_all
+            && self.regex.is_none()
+            && self.field.is_none()
+            && self.csv_column.is_none()
+            && self.json_path.is_none()
+            && self.fixed_field.is_none();
+        let mut emit = |n: f64, weight: usize| {
+            if !n.is_finite() {
+                self.non_finite.set(self.non_finite.get() + weight);
+                return;
+            }
+            let in_range = !matches!(&self.range, Some(r) if !r.contains(&n))
+                && !matches!(&self.clamp, Some(r) if !r.contains(&n));
+            if in_range {
+                f(n, weight);
+            } else {
+                self.excluded.set(self.excluded.get() + weight);
+            }
+        };
+        for (index, line) in lines {
+            self.lines.set(self.lines.get() + 1);
+            let skipped = self.misses() + self.errors();
             match line {
                 Ok(as_string) => {
-                    if let Some(n) = line_parser(self, &as_string) {
-                        match &self.range {
-                            Some(range) => {
-                                if range.contains(&n) {
-                                    vec.push(n);
-                                }
-                            }
-                            _ => vec.push(n),
+                    if self.weighted {
+                        if let Some((n, weight)) = self.parse_weighted(&as_string) {
+                            emit(n, weight);
+                        }
+                    } else if split_all {
+                        for n in self.parse_tokens(&as_string) {
+                            emit(n, 1);
                         }
+                    } else if let Some(n) = line_parser(self, &as_string) {
+                        emit(n, 1);
+                    }
+                    if self.misses() + self.errors() > skipped {
+                        self.report_error(&as_string, index + 1);
                     }
                 }
-                Err(error) => error!("{}", error),
+                Err(error) => {
+                    error!("{}", error);
+                    self.errors.set(self.errors.get() + 1);
+                }
             }
         }
-        vec
+    }
+
+    fn report_error(&self, line: &str, number: usize) {
+        if let Some(ErrorCallback(callback)) = &self.on_error {
+            let mut callback = callback.lock().unwrap();
+            (*callback)(line, number);
+        }
+    }
+
+    /// True for lines with no data to be parsed (blank lines and comments).
+    fn is_ignored(&self, line: &str) -> bool {
+        let line = line.trim_start();
+        match self.comment_char {
+            Some(c) => line.is_empty() || line.starts_with(c),
+            None => line.is_empty(),
+        }
+    }
+
+    /// Returns the token with grouping chars removed and the decimal point
+    /// converted to a dot, as expected by `f64::from_str`.
+    fn normalize<'a>(&self, token: &'a str) -> Cow<'a, str> {
+        let decimal = if self.decimal_comma {
+            Some(',')
+        } else {
+            self.decimal
+        };
+        if self.strip_separators.is_none() && decimal.is_none() {
+            return Cow::Borrowed(token);
+        }
+        token
+            .chars()
+            .filter(|c| Some(*c) != self.strip_separators)
+            .map(|c| if Some(c) == decimal { '.' } else { c })
+            .collect()
+    }
+
+    /// Returns the value of a token, either a number or (when parsing
+    /// durations) a duration in seconds.
+    fn to_number(&self, token: &str) -> Result<f64, String> {
+        if self.parse_duration {
+            return humantime::parse_duration(token.trim())
+                .map(|duration| duration.as_secs_f64())
+                .map_err(|err| err.to_string());
+        }
+        let token = self.normalize(token);
+        let radix = self.radix.unwrap_or(10);
+        let token = if self.strip_units {
+            token.trim_end_matches(|c: char| !c.is_digit(radix))
+        } else {
+            &token
+        };
+        match self.radix {
+            Some(radix) => parse_radix(token, radix),
+            None => token.parse::<f64>().map_err(|err| err.to_string()),
+        }
     }
 
     fn parse_float(&self, line: &str) -> Option<f64> {
-        match line.parse::<f64>() {
+        match self.to_number(line) {
             Ok(n) => Some(n),
             Err(parse_error) => {
                 debug!("Cannot parse float ({}) at '{}'", parse_error, line);
+                self.errors.set(self.errors.get() + 1);
+                None
+            }
+        }
+    }
+
+    fn parse_tokens(&self, line: &str) -> Vec<f64> {
+        let vec: Vec<f64> = line
+            .split_whitespace()
+            .filter_map(|token| self.to_number(token).ok())
+            .collect();
+        if vec.is_empty() {
+            debug!("Cannot parse any float at '{}'", line);
+            self.errors.set(self.errors.get() + 1);
+        }
+        vec
+    }
+
+    fn parse_weighted(&self, line: &str) -> Option<(f64, usize)> {
+        let mut tokens = line.split_whitespace();
+        let (value, weight) = match (tokens.next(), tokens.next()) {
+            (Some(value), Some(weight)) => (value, weight),
+            _ => {
+                debug!("There is no value and weight at '{}'", line);
+                self.errors.set(self.errors.get() + 1);
+                return None;
+            }
+        };
+        let n = self.parse_float(value)?;
+        match weight.parse::<usize>() {
+            Ok(weight) => Some((n, weight)),
+            Err(parse_error) => {
+                debug!("Cannot parse weight ({}) at '{}'", parse_error, line);
+                self.errors.set(self.errors.get() + 1);
+                None
+            }
+        }
+    }
+
+    fn parse_field(&self, line: &str) -> Option<f64> {
+        let field = self.field.unwrap();
+        match line.split_whitespace().nth(field) {
+            Some(token) => self.parse_float(token),
+            None => {
+                debug!("There is no field {} at '{}'", field, line);
+                self.errors.set(self.errors.get() + 1);
+                None
+            }
+        }
+    }
+
+    fn parse_csv(&self, line: &str) -> Option<f64> {
+        let index = self.csv_index.get();
+        match csv::split_line(line).get(index) {
+            Some(cell) if cell.trim().is_empty() => {
+                debug!("Empty cell at '{}'", line);
+                None
+            }
+            Some(cell) => self.parse_float(cell.trim()),
+            None => {
+                debug!("There is no column {} at '{}'", index, line);
+                self.errors.set(self.errors.get() + 1);
+                None
+            }
+        }
+    }
+
+    fn parse_fixed(&self, line: &str) -> Option<f64> {
+        let range = self.fixed_field.as_ref().unwrap();
+        let mut offsets = line
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain(std::iter::once(line.len()));
+        let start = offsets.nth(range.start);
+        let end = start.and_then(|_| offsets.nth(range.end - range.start - 1));
+        match (start, end) {
+            (Some(start), Some(end)) => self.parse_float(line[start..end].trim()),
+            _ => {
+                debug!("Line is shorter than {} chars at '{}'", range.end, line);
+                self.errors.set(self.errors.get() + 1);
+                None
+            }
+        }
+    }
+
+    fn parse_json(&self, line: &str) -> Option<f64> {
+        let path = self.json_path.as_ref().unwrap();
+        let value = match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(value) => value,
+            Err(error) => {
+                debug!("Cannot parse JSON ({}) at '{}'", error, line);
+                self.errors.set(self.errors.get() + 1);
+                return None;
+            }
+        };
+        let leaf = path.split('.').try_fold(&value, |node, key| match node {
+            serde_json::Value::Array(vec) => key.parse::<usize>().ok().and_then(|i| vec.get(i)),
+            _ => node.get(key),
+        });
+        match leaf.and_then(|leaf| leaf.as_f64()) {
+            Some(n) => Some(n),
+            None => {
+                debug!("There is no number at {} in '{}'", path, line);
+                self.errors.set(self.errors.get() + 1);
                 None
             }
         }
@@ -64,6 +610,7 @@ impl DataReader {
             }
             None => {
                 debug!("Regex does not match '{}'", line);
+                self.misses.set(self.misses.get() + 1);
                 None
             }
         }
@@ -74,9 +621,11 @@ impl DataReader {
         for s in strings {
             rows.push(MatchBarRow::new(s));
         }
-        for line in open_file(path).lines() {
+        self.lines.set(0);
+        for line in read_lines(open_file(path)) {
             match line {
                 Ok(as_string) => {
+                    self.lines.set(self.lines.get() + 1);
                     for row in rows.iter_mut() {
                         row.inc_if_matches(&as_string);
                     }
@@ -90,9 +639,11 @@ impl DataReader {
     pub fn read_terms(&self, path: &str, lines: usize) -> CommonTerms {
         let mut terms = CommonTerms::new(lines);
         let regex = self.regex.as_ref().unwrap();
-        for line in open_file(path).lines() {
+        self.lines.set(0);
+        for line in read_lines(open_file(path)) {
             match line {
                 Ok(as_string) => {
+                    self.lines.set(self.lines.get() + 1);
                     if let Some(cap) = regex.captures(&as_string) {
                         if let Some(name) = cap.name("value") {
                             terms.observe(String::from(name.as_str()));
@@ -108,10 +659,36 @@ impl DataReader {
     }
 }
 
+/// Parses an integer in the given radix, with an optional sign and an optional
+/// prefix for usual radixes (`0x`, `0o` or `0b`).
+fn parse_radix(token: &str, radix: u32) -> Result<f64, String> {
+    let (negative, digits) = match token.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, token.strip_prefix('+').unwrap_or(token)),
+    };
+    let prefix = match radix {
+        16 => Some(['x', 'X']),
+        8 => Some(['o', 'O']),
+        2 => Some(['b', 'B']),
+        _ => None,
+    };
+    let digits = match prefix {
+        Some(prefix) => digits
+            .strip_prefix('0')
+            .and_then(|rest| rest.strip_prefix(prefix))
+            .unwrap_or(digits),
+        None => digits,
+    };
+    let n = i64::from_str_radix(digits, radix).map_err(|err| err.to_string())? as f64;
+    Ok(if negative { -n } else { n })
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::stats::Stats;
+    use float_eq::assert_float_eq;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -125,6 +702,194 @@ mod tests {
         writeln!(file, "-2.7").unwrap();
         let vec = reader.read(file.path().to_str().unwrap());
         assert_eq!(vec, [1.3, 2.0, -2.7]);
+        assert_eq!((reader.lines(), reader.errors()), (4, 1));
+        assert!(!reader.nothing_parsed());
+    }
+
+    #[test]
+    fn nothing_parsed() {
+        let reader = DataReader::default();
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"foo\n\n\xff\xfe\n").unwrap();
+        assert!(reader.read(file.path().to_str().unwrap()).is_empty());
+        assert_eq!(reader.lines(), 2);
+        assert!(reader.nothing_parsed());
+        let empty = NamedTempFile::new().unwrap();
+        reader.read(empty.path().to_str().unwrap());
+        assert!(!reader.nothing_parsed());
+    }
+
+    #[test]
+    fn windows_line_endings_and_bom() {
+        let reader = DataReader::default();
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"\xef\xbb\xbf1.5\r\n123\r\n-2\r").unwrap();
+        let vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, [1.5, 123.0, -2.0]);
+        assert_eq!(reader.errors(), 0);
+    }
+
+    #[test]
+    fn strip_separators() {
+        let reader = DataReaderBuilder::default()
+            .strip_separators(',')
+            .build()
+            .unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "1,234.5").unwrap();
+        writeln!(file, "1,234,567").unwrap();
+        writeln!(file, "12").unwrap();
+        writeln!(file, "1.2.3").unwrap();
+        let vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, [1234.5, 1234567.0, 12.0]);
+        assert_eq!(reader.errors(), 1);
+    }
+
+    #[test]
+    fn strip_separators_with_decimal() {
+        let reader = DataReaderBuilder::default()
+            .strip_separators('.')
+            .decimal(',')
+            .split_all(true)
+            .build()
+            .unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "1.234,5 7,25").unwrap();
+        let vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, [1234.5, 7.25]);
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn decimal_comma() {
+        let reader = DataReaderBuilder::default()
+            .decimal_comma(true)
+            .build()
+            .unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "3,14").unwrap();
+        writeln!(file, "-2").unwrap();
+        writeln!(file, "1.5").unwrap();
+        let vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, [3.14, -2.0, 1.5]);
+    }
+
+    #[test]
+    fn decimal_comma_conflicts_with_comma_separator() {
+        let result = DataReaderBuilder::default()
+            .decimal_comma(true)
+            .strip_separators(',')
+            .build();
+        assert!(result.is_err());
+        let result = DataReaderBuilder::default()
+            .decimal('.')
+            .strip_separators('.')
+            .build();
+        assert!(result.is_err());
+        let result = DataReaderBuilder::default()
+            .decimal_comma(true)
+            .strip_separators('.')
+            .build();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn sample() {
+        let mut file = NamedTempFile::new().unwrap();
+        for i in 0..1000 {
+            writeln!(file, "{}", i).unwrap();
+        }
+        let path = file.path().to_str().unwrap();
+        let reader = DataReaderBuilder::default()
+            .sample(10)
+            .seed(42)
+            .build()
+            .unwrap();
+        let vec = reader.read(path);
+        assert_eq!(vec.len(), 10);
+        assert_eq!(reader.total(), 1000);
+        assert!(vec.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(vec, reader.read(path));
+        let reader = DataReaderBuilder::default().sample(2000).build().unwrap();
+        assert_eq!(reader.read(path).len(), 1000);
+        assert_eq!(reader.total(), 1000);
+    }
+
+    #[test]
+    fn comments_and_blank_lines() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "# latencies").unwrap();
+        writeln!(file, "1.5").unwrap();
+        writeln!(file).unwrap();
+        writeln!(file, "  # indented comment").unwrap();
+        writeln!(file, "  ").unwrap();
+        writeln!(file, "2").unwrap();
+        writeln!(file, "bad").unwrap();
+        let path = file.path().to_str().unwrap();
+        let reader = DataReaderBuilder::default()
+            .comment_char('#')
+            .build()
+            .unwrap();
+        assert_eq!(reader.read(path), [1.5, 2.0]);
+        assert_eq!(reader.errors(), 1);
+        let reader = DataReader::default();
+        assert_eq!(reader.read(path), [1.5, 2.0]);
+        assert_eq!(reader.errors(), 3);
+    }
+
+    #[test]
+    fn skip_header_and_footer() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "Latency report").unwrap();
+        writeln!(file, "name,value").unwrap();
+        writeln!(file, "a,1").unwrap();
+        writeln!(file, "b,2").unwrap();
+        writeln!(file, "total,3").unwrap();
+        let reader = DataReaderBuilder::default()
+            .skip_header(1)
+            .skip_footer(1)
+            .csv_column("value")
+            .build()
+            .unwrap();
+        assert_eq!(reader.read(file.path().to_str().unwrap()), [1.0, 2.0]);
+        assert_eq!(reader.errors(), 0);
+    }
+
+    #[test]
+    fn on_error() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "header").unwrap();
+        writeln!(file, "1").unwrap();
+        writeln!(file).unwrap();
+        writeln!(file, "foo").unwrap();
+        writeln!(file, "2").unwrap();
+        writeln!(file, "3 ms").unwrap();
+        let skipped = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&skipped);
+        let reader = DataReaderBuilder::default()
+            .skip_header(1)
+            .on_error(move |line, number| sink.lock().unwrap().push((line.to_string(), number)))
+            .build()
+            .unwrap();
+        assert_eq!(reader.read(file.path().to_str().unwrap()), [1.0, 2.0]);
+        assert_eq!(
+            *skipped.lock().unwrap(),
+            [("foo".to_string(), 4), ("3 ms".to_string(), 6)]
+        );
+    }
+
+    #[test]
+    fn csv_header_after_comment() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "# exported data").unwrap();
+        writeln!(file, "name,value").unwrap();
+        writeln!(file, "a,1").unwrap();
+        let reader = DataReaderBuilder::default()
+            .comment_char('#')
+            .csv_column("value")
+            .build()
+            .unwrap();
+        assert_eq!(reader.read(file.path().to_str().unwrap()), [1.0]);
     }
 
     #[test]
@@ -155,6 +920,7 @@ mod tests {
         writeln!(file, "foo 5").unwrap();
         let vec = reader.read(file.path().to_str().unwrap());
         assert_eq!(vec, [1.6, 3.0]);
+        assert_eq!(reader.misses(), 4);
     }
 
     #[test]
@@ -162,7 +928,122 @@ mod tests {
         let reader = DataReader::default();
         let file = NamedTempFile::new().unwrap();
         let vec = reader.read(file.path().to_str().unwrap());
-        assert_eq!(vec, []);
+        assert_eq!(vec, Vec::<f64>::new());
+    }
+
+    #[test]
+    fn field() {
+        let reader = DataReaderBuilder::default().field(1).build().unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "foo 1.3 1.6").unwrap();
+        writeln!(file, "  bar\t-2  ").unwrap();
+        writeln!(file, "baz").unwrap();
+        writeln!(file, "foo bar 2").unwrap();
+        writeln!(file, "0 7").unwrap();
+        let vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, [1.3, -2.0, 7.0]);
+        assert_eq!(reader.errors(), 2);
+        assert_eq!(reader.misses(), 0);
+    }
+
+    #[test]
+    fn fixed_field() {
+        let reader = DataReaderBuilder::default()
+            .fixed_field(6, 12)
+            .build()
+            .unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "000001  12.5OK").unwrap();
+        writeln!(file, "000002-100.0KO").unwrap();
+        writeln!(file, "ñ0003   3.25").unwrap();
+        writeln!(file, "000004  12").unwrap();
+        writeln!(file, "000005 abcdeOK").unwrap();
+        let vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, [12.5, -100.0, 3.25]);
+        assert_eq!(reader.errors(), 2);
+        assert!(DataReaderBuilder::default()
+            .fixed_field(3, 3)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn csv_column() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "name,\"latency, ms\",status").unwrap();
+        writeln!(file, "\"a, b\",1.5,200").unwrap();
+        writeln!(file, "c,,500").unwrap();
+        writeln!(file, "d,n/a,500").unwrap();
+        writeln!(file, "e").unwrap();
+        writeln!(file, "f, 3 ,200").unwrap();
+        let reader = DataReaderBuilder::default()
+            .csv_column("latency, ms")
+            .build()
+            .unwrap();
+        let vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, [1.5, 3.0]);
+        assert_eq!(reader.errors(), 2);
+        let reader = DataReaderBuilder::default().csv_column(2).build().unwrap();
+        let vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, [200.0, 500.0, 500.0, 200.0]);
+        let reader = DataReaderBuilder::default()
+            .csv_column("missing")
+            .build()
+            .unwrap();
+        let vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, Vec::<f64>::new());
+    }
+
+    #[test]
+    fn json_path() {
+        let reader = DataReaderBuilder::default()
+            .json_path("response.duration_ms")
+            .build()
+            .unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"{{"response": {{"duration_ms": 12.5, "status": 200}}}}"#
+        )
+        .unwrap();
+        writeln!(file, "not json").unwrap();
+        writeln!(file, "[1, 2]").unwrap();
+        writeln!(file, r#"{{"response": {{"status": 500}}}}"#).unwrap();
+        writeln!(file, r#"{{"response": {{"duration_ms": "slow"}}}}"#).unwrap();
+        writeln!(
+            file,
+            r#"{{"request": 1, "response": {{"duration_ms": 3}}}}"#
+        )
+        .unwrap();
+        let vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, [12.5, 3.0]);
+        assert_eq!(reader.errors(), 4);
+        let reader = DataReaderBuilder::default()
+            .json_path("values.1")
+            .build()
+            .unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, r#"{{"values": [1, 2, 3]}}"#).unwrap();
+        let vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, [2.0]);
+    }
+
+    #[test]
+    fn split_all() {
+        let reader = DataReaderBuilder::default()
+            .split_all(true)
+            .range(0.0..40.0)
+            .build()
+            .unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "12 45 7").unwrap();
+        writeln!(file, "foo").unwrap();
+        writeln!(file).unwrap();
+        writeln!(file, "x=1 2.5 bar\t3").unwrap();
+        let vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, [12.0, 7.0, 2.5, 3.0]);
+        // Blank lines are not reported as errors
+        assert_eq!(reader.errors(), 1);
     }
 
     #[test]
@@ -180,6 +1061,182 @@ mod tests {
         assert_eq!(vec, [-0.5, 0.5]);
     }
 
+    #[test]
+    fn clamp_range() {
+        let mut file = NamedTempFile::new().unwrap();
+        for n in [1.0, 2.0, 3.0, 4.0, 100.0] {
+            writeln!(file, "{}", n).unwrap();
+        }
+        let path = file.path().to_str().unwrap();
+        let mut vec = DataReader::default().read(path);
+        assert_float_eq!(Stats::new(&mut vec, None).avg, 22.0, rmax <= f64::EPSILON);
+        let reader = DataReaderBuilder::default()
+            .clamp_range(Some(2.0), Some(4.0))
+            .build()
+            .unwrap();
+        let mut vec = reader.read(path);
+        assert_eq!(vec, [2.0, 3.0, 4.0]);
+        assert_eq!(reader.excluded(), 2);
+        let stats = Stats::new(&mut vec, None);
+        assert_float_eq!(stats.avg, 3.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.max, 4.0, rmax <= f64::EPSILON);
+        let reader = DataReaderBuilder::default()
+            .clamp_range(None, Some(3.0))
+            .build()
+            .unwrap();
+        assert_eq!(reader.read(path), [1.0, 2.0, 3.0]);
+        assert_eq!(reader.excluded(), 2);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn read_files_parallel() {
+        let mut files: Vec<NamedTempFile> = Vec::new();
+        for content in ["1\nfoo\n2\n", "3\n-1\n", "4\nbar\n"] {
+            let mut file = NamedTempFile::new().unwrap();
+            write!(file, "{}", content).unwrap();
+            files.push(file);
+        }
+        let paths: Vec<&str> = files.iter().map(|f| f.path().to_str().unwrap()).collect();
+        let reader = DataReaderBuilder::default()
+            .range(0.0..10.0)
+            .build()
+            .unwrap();
+        let mut vec = reader.read_files_parallel(&paths);
+        vec.sort_by(|a, b| a.total_cmp(b));
+        assert_eq!(vec, [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(reader.errors(), 2);
+        assert_eq!(reader.excluded(), 1);
+        assert_eq!(reader.total(), 4);
+        let mut sequential = reader.read_files(&paths);
+        sequential.sort_by(|a, b| a.total_cmp(b));
+        assert_eq!(vec, sequential);
+        assert_eq!(reader.errors(), 2);
+    }
+
+    #[test]
+    fn durations() {
+        let mut file = NamedTempFile::new().unwrap();
+        for line in ["1m30s", "250ms", "2h", "30", "1h 2m 3s", "foo"] {
+            writeln!(file, "{}", line).unwrap();
+        }
+        let reader = DataReaderBuilder::default()
+            .parse_duration(true)
+            .build()
+            .unwrap();
+        let vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, [90.0, 0.25, 7200.0, 3723.0]);
+        assert_eq!(reader.errors(), 2);
+        let reader = DataReaderBuilder::default()
+            .parse_duration(true)
+            .field(1)
+            .build()
+            .unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "took 10us").unwrap();
+        writeln!(file, "took 1s500ms").unwrap();
+        assert_eq!(reader.read(file.path().to_str().unwrap()), [1e-5, 1.5]);
+    }
+
+    #[test]
+    fn radix() {
+        let mut file = NamedTempFile::new().unwrap();
+        for line in ["0x1f", "ff", "-0X10", "zz", "1.5"] {
+            writeln!(file, "{}", line).unwrap();
+        }
+        let path = file.path().to_str().unwrap();
+        let reader = DataReaderBuilder::default().radix(16).build().unwrap();
+        assert_eq!(reader.read(path), [31.0, 255.0, -16.0]);
+        assert_eq!(reader.errors(), 2);
+        let reader = DataReaderBuilder::default().radix(2).build().unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "0b101").unwrap();
+        writeln!(file, "11").unwrap();
+        assert_eq!(reader.read(file.path().to_str().unwrap()), [5.0, 3.0]);
+        assert!(DataReaderBuilder::default().radix(37).build().is_err());
+    }
+
+    #[test]
+    fn strip_units() {
+        let mut file = NamedTempFile::new().unwrap();
+        for line in ["123ms", "1.5s", "7", "ms", "2e3 bytes"] {
+            writeln!(file, "{}", line).unwrap();
+        }
+        let path = file.path().to_str().unwrap();
+        assert_eq!(DataReader::default().read(path), [7.0]);
+        let reader = DataReaderBuilder::default()
+            .strip_units(true)
+            .build()
+            .unwrap();
+        assert_eq!(reader.read(path), [123.0, 1.5, 7.0, 2000.0]);
+        assert_eq!(reader.errors(), 1);
+        let reader = DataReaderBuilder::default()
+            .strip_units(true)
+            .radix(16)
+            .build()
+            .unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "0x1fh").unwrap();
+        assert_eq!(reader.read(file.path().to_str().unwrap()), [31.0]);
+    }
+
+    #[test]
+    fn non_finite() {
+        let mut file = NamedTempFile::new().unwrap();
+        for line in ["1", "NaN", "inf", "2", "-infinity", "3"] {
+            writeln!(file, "{}", line).unwrap();
+        }
+        let reader = DataReader::default();
+        let mut vec = reader.read(file.path().to_str().unwrap());
+        assert_eq!(vec, [1.0, 2.0, 3.0]);
+        assert_eq!(reader.non_finite(), 3);
+        assert_eq!(reader.errors(), 0);
+        let stats = Stats::new(&mut vec, None);
+        assert_float_eq!(stats.min, 1.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.max, 3.0, rmax <= f64::EPSILON);
+    }
+
+    #[test]
+    fn weighted() {
+        let reader = DataReaderBuilder::default().weighted(true).build().unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "1.5 3").unwrap();
+        writeln!(file, "2 1").unwrap();
+        writeln!(file, "3").unwrap();
+        writeln!(file, "4 -1").unwrap();
+        writeln!(file, "5 0").unwrap();
+        let path = file.path().to_str().unwrap();
+        assert_eq!(
+            reader.read_weighted(&[path]),
+            [(1.5, 3), (2.0, 1), (5.0, 0)]
+        );
+        assert_eq!(reader.errors(), 2);
+        assert_eq!(reader.read(path), [1.5, 1.5, 1.5, 2.0]);
+        let mut stats = StatsBuilder::new(None);
+        reader.read_into(path, &mut stats);
+        assert_eq!(stats.samples(), 4);
+    }
+
+    #[test]
+    fn read_into_stats() {
+        let reader = DataReaderBuilder::default()
+            .range(-1.0..1.0)
+            .build()
+            .unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "1.3").unwrap();
+        writeln!(file, "-0.5").unwrap();
+        writeln!(file, "foobar").unwrap();
+        writeln!(file, "0.5").unwrap();
+        let mut builder = StatsBuilder::new(None);
+        reader.read_into(file.path().to_str().unwrap(), &mut builder);
+        let stats = builder.finish();
+        assert_eq!(stats.samples, 2);
+        assert_eq!(stats.min, -0.5);
+        assert_eq!(stats.max, 0.5);
+        assert_eq!(stats.avg, 0.0);
+    }
+
     #[test]
     fn basic_match_reader() {
         let reader = DataReader::default();
@@ -0,0 +1,75 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, FixedOffset, Timelike};
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// A recurring unit of calendar time, for counting timestamps per unit
+/// regardless of their date (see `TimeReader::read_grouped`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarUnit {
+    /// Hours of the day, from 00 to 23.
+    #[default]
+    HourOfDay,
+    /// Days of the week, from Monday to Sunday.
+    DayOfWeek,
+}
+
+impl CalendarUnit {
+    /// Returns the labels of the buckets of the unit, in order.
+    pub fn labels(&self) -> Vec<String> {
+        match self {
+            CalendarUnit::HourOfDay => (0..24).map(|hour| format!("{:02}", hour)).collect(),
+            CalendarUnit::DayOfWeek => WEEKDAYS.iter().map(|day| day.to_string()).collect(),
+        }
+    }
+
+    /// Returns the index of the bucket of a timestamp, as seen in its own
+    /// offset.
+    pub fn slot(&self, dt: &DateTime<FixedOffset>) -> usize {
+        match self {
+            CalendarUnit::HourOfDay => dt.hour() as usize,
+            CalendarUnit::DayOfWeek => dt.weekday().num_days_from_monday() as usize,
+        }
+    }
+}
+
+impl fmt::Display for CalendarUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CalendarUnit::HourOfDay => write!(f, "hour"),
+            CalendarUnit::DayOfWeek => write!(f, "weekday"),
+        }
+    }
+}
+
+impl FromStr for CalendarUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hour" => Ok(CalendarUnit::HourOfDay),
+            "weekday" => Ok(CalendarUnit::DayOfWeek),
+            _ => Err(format!("Unknown calendar unit '{}'", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_slot() {
+        // A Thursday
+        let dt = DateTime::parse_from_rfc3339("2021-04-15T23:25:31+00:00").unwrap();
+        assert_eq!(CalendarUnit::HourOfDay.slot(&dt), 23);
+        assert_eq!(CalendarUnit::DayOfWeek.slot(&dt), 3);
+        assert_eq!(CalendarUnit::DayOfWeek.labels()[3], "Thu");
+        assert_eq!(CalendarUnit::HourOfDay.labels().len(), 24);
+        assert_eq!("weekday".parse(), Ok(CalendarUnit::DayOfWeek));
+        assert!("month".parse::<CalendarUnit>().is_err());
+    }
+}
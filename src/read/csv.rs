@@ -0,0 +1,100 @@
+use std::fmt;
+
+/// A column of a CSV input, either by its (0-based) index or by its name in
+/// the header row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvColumn {
+    Index(usize),
+    Name(String),
+}
+
+impl From<usize> for CsvColumn {
+    fn from(index: usize) -> Self {
+        CsvColumn::Index(index)
+    }
+}
+
+impl From<&str> for CsvColumn {
+    /// Strings holding a non-negative integer are taken as an index, anything
+    /// else as a column name.
+    fn from(name_or_index: &str) -> Self {
+        match name_or_index.parse::<usize>() {
+            Ok(index) => CsvColumn::Index(index),
+            Err(_) => CsvColumn::Name(name_or_index.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for CsvColumn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CsvColumn::Index(index) => write!(f, "#{}", index),
+            CsvColumn::Name(name) => write!(f, "'{}'", name),
+        }
+    }
+}
+
+impl CsvColumn {
+    /// Returns the index of the column given the header row of the input, or
+    /// None if there such column does not exist.
+    pub(crate) fn resolve(&self, header: &str) -> Option<usize> {
+        match self {
+            CsvColumn::Index(index) => Some(*index),
+            CsvColumn::Name(name) => split_line(header)
+                .iter()
+                .position(|cell| cell.trim() == name),
+        }
+    }
+}
+
+/// Splits a CSV line in its cells.  Cells can be quoted with double quotes, so
+/// that they contain commas, and quotes inside quoted cells are escaped by
+/// doubling them.  Cells spanning several lines are not supported.
+pub(crate) fn split_line(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) => {
+                if chars.peek() == Some(&'"') {
+                    cell.push('"');
+                    chars.next();
+                } else {
+                    quoted = false;
+                }
+            }
+            ('"', false) => quoted = true,
+            (',', false) => cells.push(std::mem::take(&mut cell)),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell);
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_split_line() {
+        assert_eq!(split_line("a,b,,c"), ["a", "b", "", "c"]);
+        assert_eq!(split_line(""), [""]);
+        assert_eq!(
+            split_line("\"x, y\",1.5,\"say \"\"hi\"\"\""),
+            ["x, y", "1.5", "say \"hi\""]
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let header = "name,\"latency, ms\",status";
+        assert_eq!(CsvColumn::from("latency, ms").resolve(header), Some(1));
+        assert_eq!(CsvColumn::from("status").resolve(header), Some(2));
+        assert_eq!(CsvColumn::from("2").resolve(header), Some(2));
+        assert_eq!(CsvColumn::from("missing").resolve(header), None);
+    }
+}
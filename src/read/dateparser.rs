@@ -22,6 +22,23 @@ const TIME_FORMATS: &[&str] = &[
 // Max length that a timestamp can have
 const MAX_LEN: usize = 28;
 
+/// Unit of Unix epoch timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpochUnit {
+    Seconds,
+    Millis,
+}
+
+impl EpochUnit {
+    // Max number of decimals for sub-unit precision (up to nanoseconds)
+    fn max_decimals(self) -> usize {
+        match self {
+            EpochUnit::Seconds => 9,
+            EpochUnit::Millis => 6,
+        }
+    }
+}
+
 pub struct LogDateParser {
     range: Range<usize>,
     parser: Box<DateParsingFun>,
@@ -41,9 +58,12 @@ impl LogDateParser {
         // the 1st digit and then try to parse as much text as possible with any
         // of the "supported" formats (so that we do not lose precision digits
         // or TZ info).
-        for (i, c) in log_line.chars().enumerate() {
+        for (i, c) in log_line.char_indices() {
             if c.is_ascii_digit() {
                 for j in (i..(i + MAX_LEN).min(log_line.len() + 1)).rev() {
+                    if !log_line.is_char_boundary(j) {
+                        continue;
+                    }
                     if let Some(parser) = Self::guess_parser(&log_line[i..j]) {
                         return Ok(Self {
                             range: i..j,
@@ -57,6 +77,33 @@ impl LogDateParser {
         Err(format!("Could not parse a timestamp in {log_line}"))
     }
 
+    /// Creates a parser for epoch timestamps of the given unit, located at the
+    /// first digit of `log_line`.
+    pub fn new_with_epoch(log_line: &str, unit: EpochUnit) -> Result<Self, String> {
+        let start = match log_line.find(|c: char| c.is_ascii_digit()) {
+            Some(i) => i,
+            None => return Err(format!("Could not locate an epoch timestamp in {log_line}")),
+        };
+        let rest = &log_line[start..];
+        let mut end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if rest[end..].starts_with('.') {
+            let decimals = rest[end + 1..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len() - end - 1);
+            end += 1 + decimals.min(unit.max_decimals());
+        }
+        let range = start..start + end;
+        if parse_epoch(&log_line[range.clone()], unit).is_err() {
+            return Err(format!("Could not parse an epoch timestamp in {log_line}"));
+        }
+        Ok(Self {
+            range,
+            parser: Box::new(move |string: &str| parse_epoch(string, unit)),
+        })
+    }
+
     fn new_with_format(log_line: &str, format_string: &str) -> Result<Self, String> {
         // We look for where the timestamp is in logs using a brute force
         // approach with 1st log line, but capping the max length we scan for
@@ -101,32 +148,8 @@ impl LogDateParser {
             return Some(Box::new(DateTime::parse_from_rfc3339));
         } else if DateTime::parse_from_rfc2822(s).is_ok() {
             return Some(Box::new(DateTime::parse_from_rfc2822));
-        } else if Self::looks_like_timestamp(s) {
-            return Some(Box::new(|string: &str| {
-                let dot = match string.find('.') {
-                    Some(x) => x,
-                    None => string.len(),
-                };
-                let nanosecs = if dot < string.len() {
-                    let missing_zeros = (10 + dot - string.len()) as u32;
-                    match string[dot + 1..].parse::<u32>() {
-                        Ok(x) => x * 10_u32.pow(missing_zeros),
-                        _ => 0,
-                    }
-                } else {
-                    0
-                };
-                match string[..dot].parse::<i64>() {
-                    Ok(secs) => {
-                        let naive = NaiveDateTime::from_timestamp_opt(secs, nanosecs).unwrap();
-                        let date_time: DateTime<Utc> = Utc.from_local_datetime(&naive).unwrap();
-                        Ok(date_time.with_timezone(&TimeZone::from_offset(
-                            &FixedOffset::west_opt(0).unwrap(),
-                        )))
-                    }
-                    Err(_) => DateTime::parse_from_rfc3339(""),
-                }
-            }));
+        } else if let Some(unit) = Self::looks_like_timestamp(s) {
+            return Some(Box::new(move |string: &str| parse_epoch(string, unit)));
         }
         for format in DATE_FORMATS.iter() {
             if NaiveDateTime::parse_from_str(s, format).is_ok() {
@@ -160,12 +183,57 @@ impl LogDateParser {
         None
     }
 
-    // Returns true if string looks like a unix-like timestamp of arbitrary
-    // precision
-    fn looks_like_timestamp(s: &str) -> bool {
-        Regex::new(r"^[0-9]{10}(\.[0-9]{1,9})?$")
+    // Returns the unit of the string if it looks like a unix-like timestamp of
+    // arbitrary precision: 10 digits for seconds, 13 digits for milliseconds
+    fn looks_like_timestamp(s: &str) -> Option<EpochUnit> {
+        if Regex::new(r"^[0-9]{10}(\.[0-9]{1,9})?$")
+            .unwrap()
+            .is_match(s)
+        {
+            Some(EpochUnit::Seconds)
+        } else if Regex::new(r"^[0-9]{13}(\.[0-9]{1,6})?$")
             .unwrap()
             .is_match(s)
+        {
+            Some(EpochUnit::Millis)
+        } else {
+            None
+        }
+    }
+}
+
+/// Parses an epoch timestamp (with optional decimals) of the given unit.
+fn parse_epoch(string: &str, unit: EpochUnit) -> Result<DateTime<FixedOffset>, ParseError> {
+    let (integer, decimals) = match string.find('.') {
+        Some(dot) => (&string[..dot], &string[dot + 1..]),
+        None => (string, ""),
+    };
+    let max_decimals = unit.max_decimals();
+    let fraction = match decimals {
+        "" => Some(0),
+        _ if decimals.len() > max_decimals => None,
+        _ => decimals
+            .parse::<u32>()
+            .ok()
+            .map(|x| x * 10_u32.pow((max_decimals - decimals.len()) as u32)),
+    };
+    let timestamp = match (integer.parse::<i64>(), fraction) {
+        (Ok(n), Some(fraction)) => match unit {
+            EpochUnit::Seconds => NaiveDateTime::from_timestamp_opt(n, fraction),
+            EpochUnit::Millis => NaiveDateTime::from_timestamp_opt(
+                n.div_euclid(1000),
+                n.rem_euclid(1000) as u32 * 1_000_000 + fraction,
+            ),
+        },
+        _ => None,
+    };
+    match timestamp {
+        Some(naive) => {
+            let date_time: DateTime<Utc> = Utc.from_local_datetime(&naive).unwrap();
+            Ok(date_time.with_timezone(&TimeZone::from_offset(&FixedOffset::west_opt(0).unwrap())))
+        }
+        // There is no way to build a ParseError, so we get one from chrono
+        None => DateTime::parse_from_rfc3339(""),
     }
 }
 
@@ -253,6 +321,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_epoch_millis() {
+        let r = LogDateParser::new_with_guess("1609459200000 foo").unwrap();
+        assert_eq!(
+            r.parse("1609459200000 foo"),
+            DateTime::parse_from_rfc3339("2021-01-01T00:00:00+00:00")
+        );
+        let r = LogDateParser::new_with_guess("ts 1609459200123.5").unwrap();
+        assert_eq!(
+            r.parse("ts 1609459200123.5"),
+            DateTime::parse_from_rfc3339("2021-01-01T00:00:00.1235+00:00")
+        );
+        let r = LogDateParser::new_with_guess("1609459200").unwrap();
+        assert_eq!(
+            r.parse("1609459200"),
+            DateTime::parse_from_rfc3339("2021-01-01T00:00:00+00:00")
+        );
+    }
+
+    #[test]
+    fn test_forced_epoch_unit() {
+        let r = LogDateParser::new_with_epoch("[1609459200000] foo", EpochUnit::Millis).unwrap();
+        assert_eq!(
+            r.parse("[1609459200000] foo"),
+            DateTime::parse_from_rfc3339("2021-01-01T00:00:00+00:00")
+        );
+        // Would be guessed as seconds
+        let r = LogDateParser::new_with_epoch("1609459200", EpochUnit::Millis).unwrap();
+        assert_eq!(
+            r.parse("1609459200"),
+            DateTime::parse_from_rfc3339("1970-01-19T15:04:19.200+00:00")
+        );
+        let r = LogDateParser::new_with_epoch("t=1609459200.25 x", EpochUnit::Seconds).unwrap();
+        assert_eq!(
+            r.parse("t=1609459200.25 x"),
+            DateTime::parse_from_rfc3339("2021-01-01T00:00:00.25+00:00")
+        );
+        assert!(LogDateParser::new_with_epoch("no digits", EpochUnit::Seconds).is_err());
+    }
+
     #[test]
     fn test_known_formats() {
         let r = LogDateParser::new_with_guess("2021-04-28 06:25:24,321").unwrap();
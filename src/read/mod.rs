@@ -1,20 +1,50 @@
 pub use self::buckets::{DataReader, DataReaderBuilder};
+pub use self::calendar::CalendarUnit;
+pub use self::csv::CsvColumn;
+pub use self::dateparser::EpochUnit;
+pub use self::sniff::{sniff, InputKind, Sniff};
 pub use self::splittimes::{SplitTimeReader, SplitTimeReaderBuilder};
-pub use self::times::TimeReaderBuilder;
+pub use self::times::{TimeReader, TimeReaderBuilder};
+pub use self::timezone::Timezone;
 
 mod buckets;
+mod calendar;
+mod csv;
 mod dateparser;
+mod sniff;
 mod splittimes;
 mod times;
+mod timezone;
 
+use std::collections::VecDeque;
 use std::fs::File;
-use std::io::{self, BufReader};
+use std::io::{self, BufRead, BufReader};
+use std::time::{Duration, Instant};
+
+use flate2::bufread::MultiGzDecoder;
+
+// First bytes of any stream (and usual extension) of compression formats
+const CODECS: &[(&[u8], &str, Codec)] = &[
+    (&[0x1f, 0x8b], ".gz", Codec::Gzip),
+    #[cfg(feature = "zstd")]
+    (&[0x28, 0xb5, 0x2f, 0xfd], ".zst", Codec::Zstd),
+    #[cfg(feature = "bzip2")]
+    (b"BZh", ".bz2", Codec::Bzip2),
+];
+
+// Byte order mark some editors write at the start of UTF-8 files.
+const UTF8_BOM: &[u8] = &[0xef, 0xbb, 0xbf];
 
 /// Return `io::BufRead` from a path, falling back to using stdin if path is "-".
+/// With the `url` feature, paths starting with `http://` or `https://` are
+/// fetched.  Compressed input is decompressed transparently (see `Codec`), and
+/// a leading UTF-8 BOM is left out.
 /// Exits the program with exit code 1 if path does not exist.
 fn open_file(path: &str) -> Box<dyn io::BufRead> {
-    match path {
+    let reader: Box<dyn io::BufRead> = match path {
         "-" => Box::new(BufReader::new(io::stdin())),
+        #[cfg(feature = "url")]
+        _ if path.starts_with("http://") || path.starts_with("https://") => open_url(path),
         _ => match File::open(path) {
             Ok(fd) => Box::new(io::BufReader::new(fd)),
             Err(error) => {
@@ -22,6 +52,269 @@ fn open_file(path: &str) -> Box<dyn io::BufRead> {
                 panic!("{}", error);
             }
         },
+    };
+    strip_bom(decompress(reader, path))
+}
+
+/// Returns the lines of a reader, without their line endings: unlike
+/// `BufRead::lines`, a carriage return ending an unterminated last line is
+/// also left out, so that Windows files always parse cleanly.
+fn read_lines(reader: Box<dyn io::BufRead>) -> impl Iterator<Item = io::Result<String>> {
+    reader.lines().map(|line| {
+        line.map(|mut line| {
+            if line.ends_with('\r') {
+                line.pop();
+            }
+            line
+        })
+    })
+}
+
+/// Consumes the UTF-8 byte order mark at the start of a reader, if any.
+fn strip_bom(mut reader: Box<dyn io::BufRead>) -> Box<dyn io::BufRead> {
+    if matches!(reader.fill_buf(), Ok(head) if head.starts_with(UTF8_BOM)) {
+        reader.consume(UTF8_BOM.len());
+    }
+    reader
+}
+
+/// Return `io::BufRead` streaming the body of the response to a GET request
+/// to `url`.  Exits the program if the request fails, or if the response
+/// status is not a successful one.
+#[cfg(feature = "url")]
+fn open_url(url: &str) -> Box<dyn io::BufRead> {
+    match ureq::get(url).call() {
+        Ok(response) => Box::new(BufReader::new(response.into_reader())),
+        Err(error) => {
+            error!("Could not open {}: {}", url, error);
+            panic!("{}", error);
+        }
+    }
+}
+
+/// Return `io::BufRead` reading from several paths in sequence (see
+/// `open_file`).  A line break is inserted between inputs not ending in one.
+fn open_files(paths: &[&str]) -> Box<dyn io::BufRead> {
+    match paths {
+        [path] => open_file(path),
+        _ => Box::new(ConcatReader {
+            readers: paths.iter().map(|path| open_file(path)).collect(),
+            unterminated: false,
+            newline: false,
+        }),
+    }
+}
+
+/// A reader concatenating the contents of several readers.
+struct ConcatReader {
+    readers: VecDeque<Box<dyn io::BufRead>>,
+    // If the last consumed byte was not a line break
+    unterminated: bool,
+    // If a line break has to be emitted before moving to next reader
+    newline: bool,
+}
+
+impl io::Read for ConcatReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = {
+            let data = io::BufRead::fill_buf(self)?;
+            let len = data.len().min(buf.len());
+            buf[..len].copy_from_slice(&data[..len]);
+            len
+        };
+        io::BufRead::consume(self, len);
+        Ok(len)
+    }
+}
+
+impl io::BufRead for ConcatReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        loop {
+            if self.newline {
+                return Ok(b"\n");
+            }
+            match self.readers.front_mut() {
+                None => return Ok(&[]),
+                Some(reader) => {
+                    if !reader.fill_buf()?.is_empty() {
+                        break;
+                    }
+                    self.readers.pop_front();
+                    self.newline = self.unterminated && !self.readers.is_empty();
+                }
+            }
+        }
+        self.readers.front_mut().unwrap().fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if amt == 0 {
+            return;
+        }
+        if self.newline {
+            self.newline = false;
+            self.unterminated = false;
+            return;
+        }
+        if let Some(reader) = self.readers.front_mut() {
+            if let Ok(buf) = reader.fill_buf() {
+                self.unterminated = buf[amt - 1] != b'\n';
+            }
+            reader.consume(amt);
+        }
+    }
+}
+
+/// Iterator adapter leaving out the last `n` items of another iterator.  Those
+/// items are held in memory until the inner iterator is exhausted.
+struct SkipLast<I: Iterator> {
+    inner: I,
+    buffer: VecDeque<I::Item>,
+    n: usize,
+}
+
+impl<I: Iterator> SkipLast<I> {
+    fn new(inner: I, n: usize) -> Self {
+        Self {
+            inner,
+            buffer: VecDeque::with_capacity(n + 1),
+            n,
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for SkipLast<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.push_back(self.inner.next()?);
+            if self.buffer.len() > self.n {
+                return self.buffer.pop_front();
+            }
+        }
+    }
+}
+
+// Progress is reported every this many lines, or sooner if it was reported
+// longer than PROGRESS_INTERVAL ago.
+const PROGRESS_LINES: usize = 100_000;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Iterator adapter reporting into a writer the number of items (lines) that
+/// went through it, throttled to avoid spamming.  The report is erased once
+/// the inner iterator is exhausted.
+struct Progress<I, W: io::Write> {
+    inner: I,
+    out: Option<W>,
+    count: usize,
+    last_report: Instant,
+}
+
+impl<I: Iterator> Progress<I, io::Stderr> {
+    /// Reports into stderr if `enabled` is true and stderr is a terminal.
+    fn to_stderr(inner: I, enabled: bool) -> Self {
+        let out = if enabled && atty::is(atty::Stream::Stderr) {
+            Some(io::stderr())
+        } else {
+            None
+        };
+        Self::new(inner, out)
+    }
+}
+
+impl<I: Iterator, W: io::Write> Progress<I, W> {
+    fn new(inner: I, out: Option<W>) -> Self {
+        Self {
+            inner,
+            out,
+            count: 0,
+            last_report: Instant::now(),
+        }
+    }
+
+    fn report(&mut self) {
+        if let Some(out) = self.out.as_mut() {
+            // Errors writing progress are not worth stopping the read
+            let _ = write!(out, "\r{} lines read", self.count);
+            let _ = out.flush();
+        }
+        self.last_report = Instant::now();
+    }
+}
+
+impl<I: Iterator, W: io::Write> Iterator for Progress<I, W> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next();
+        if self.out.is_none() {
+            return item;
+        }
+        match item {
+            Some(_) => {
+                self.count += 1;
+                // Checking the clock for every line would be too expensive
+                if self.count % PROGRESS_LINES == 0
+                    || (self.count % 1024 == 0 && self.last_report.elapsed() >= PROGRESS_INTERVAL)
+                {
+                    self.report();
+                }
+            }
+            None => {
+                if let Some(mut out) = self.out.take() {
+                    let _ = write!(out, "\r\x1b[2K");
+                    let _ = out.flush();
+                }
+            }
+        }
+        item
+    }
+}
+
+/// Compression formats decompressed transparently.  Gzip is always supported,
+/// while zstd and bzip2 need the `zstd` and `bzip2` features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Codec {
+    Gzip,
+    #[cfg(feature = "zstd")]
+    Zstd,
+    #[cfg(feature = "bzip2")]
+    Bzip2,
+}
+
+impl Codec {
+    /// Returns the compression format of an input, detected from its first
+    /// bytes or, failing that, from the extension of its path.
+    fn detect(head: &[u8], path: &str) -> Option<Self> {
+        CODECS
+            .iter()
+            .find(|(magic, _, _)| head.starts_with(magic))
+            .or_else(|| CODECS.iter().find(|(_, ext, _)| path.ends_with(ext)))
+            .map(|(_, _, codec)| *codec)
+    }
+}
+
+/// Wraps a reader in a decompressor if its content is compressed (see
+/// `Codec::detect`).
+fn decompress(mut reader: Box<dyn io::BufRead>, path: &str) -> Box<dyn io::BufRead> {
+    let codec = match reader.fill_buf() {
+        Ok(head) => Codec::detect(head, path),
+        Err(_) => Codec::detect(&[], path),
+    };
+    match codec {
+        Some(Codec::Gzip) => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+        #[cfg(feature = "zstd")]
+        Some(Codec::Zstd) => match zstd::stream::read::Decoder::with_buffer(reader) {
+            Ok(decoder) => Box::new(BufReader::new(decoder)),
+            Err(error) => {
+                error!("Could not decompress {}: {}", path, error);
+                panic!("{}", error);
+            }
+        },
+        #[cfg(feature = "bzip2")]
+        Some(Codec::Bzip2) => Box::new(BufReader::new(bzip2::bufread::MultiBzDecoder::new(reader))),
+        None => reader,
     }
 }
 
@@ -29,10 +322,177 @@ fn open_file(path: &str) -> Box<dyn io::BufRead> {
 mod tests {
 
     use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::{BufRead, Write};
+    use tempfile::NamedTempFile;
 
     #[test]
     #[should_panic]
     fn test_bad_file() {
         open_file("/no/good");
     }
+
+    #[test]
+    fn test_bom_and_crlf() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"\xef\xbb\xbf123\r\n4.5\r\n").unwrap();
+        let lines: Vec<String> = open_file(file.path().to_str().unwrap())
+            .lines()
+            .map(|l| l.unwrap())
+            .collect();
+        assert_eq!(lines, ["123", "4.5"]);
+    }
+
+    #[test]
+    fn test_gzip_file() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"1.5\nfoo\n").unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&encoder.finish().unwrap()).unwrap();
+        let lines: Vec<String> = open_file(file.path().to_str().unwrap())
+            .lines()
+            .map(|l| l.unwrap())
+            .collect();
+        assert_eq!(lines, ["1.5", "foo"]);
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn test_zstd_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&zstd::encode_all(&b"1.5\nfoo\n"[..], 0).unwrap())
+            .unwrap();
+        let lines: Vec<String> = open_file(file.path().to_str().unwrap())
+            .lines()
+            .map(|l| l.unwrap())
+            .collect();
+        assert_eq!(lines, ["1.5", "foo"]);
+    }
+
+    #[test]
+    #[cfg(feature = "bzip2")]
+    fn test_bzip2_file() {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder.write_all(b"1.5\nfoo\n").unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&encoder.finish().unwrap()).unwrap();
+        let lines: Vec<String> = open_file(file.path().to_str().unwrap())
+            .lines()
+            .map(|l| l.unwrap())
+            .collect();
+        assert_eq!(lines, ["1.5", "foo"]);
+    }
+
+    #[test]
+    fn test_codec_detection() {
+        assert_eq!(Codec::detect(&[0x1f, 0x8b, 0x08], "-"), Some(Codec::Gzip));
+        assert_eq!(Codec::detect(b"", "input.gz"), Some(Codec::Gzip));
+        assert_eq!(Codec::detect(b"1.5\n", "input.txt"), None);
+        #[cfg(feature = "zstd")]
+        assert_eq!(Codec::detect(b"", "input.zst"), Some(Codec::Zstd));
+        #[cfg(feature = "bzip2")]
+        assert_eq!(Codec::detect(b"BZh91AY", "-"), Some(Codec::Bzip2));
+    }
+
+    #[test]
+    fn test_several_files() {
+        let mut file_a = NamedTempFile::new().unwrap();
+        write!(file_a, "1\n2").unwrap();
+        let file_b = NamedTempFile::new().unwrap();
+        let mut file_c = NamedTempFile::new().unwrap();
+        write!(file_c, "3\n4\n").unwrap();
+        let mut file_d = NamedTempFile::new().unwrap();
+        write!(file_d, "5").unwrap();
+        let paths: Vec<&str> = [&file_a, &file_b, &file_c, &file_d]
+            .iter()
+            .map(|f| f.path().to_str().unwrap())
+            .collect();
+        let lines: Vec<String> = open_files(&paths).lines().map(|l| l.unwrap()).collect();
+        assert_eq!(lines, ["1", "2", "3", "4", "5"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_several_files_bad_one() {
+        let file = NamedTempFile::new().unwrap();
+        open_files(&[file.path().to_str().unwrap(), "/no/good"]);
+    }
+
+    // Serves a single HTTP request with the given status line and body,
+    // returning the URL to request
+    #[cfg(feature = "url")]
+    fn serve_once(status: &'static str, body: &'static [u8]) -> String {
+        use std::io::Read;
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/data", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\n\r\n",
+                status,
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(body).unwrap();
+        });
+        url
+    }
+
+    #[test]
+    #[cfg(feature = "url")]
+    fn test_url() {
+        let url = serve_once("200 OK", b"1.5\nfoo\n");
+        let lines: Vec<String> = open_file(&url).lines().map(|l| l.unwrap()).collect();
+        assert_eq!(lines, ["1.5", "foo"]);
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"2\n3\n").unwrap();
+        let gzipped: &'static [u8] = Box::leak(encoder.finish().unwrap().into_boxed_slice());
+        let url = serve_once("200 OK", gzipped);
+        let lines: Vec<String> = open_file(&url).lines().map(|l| l.unwrap()).collect();
+        assert_eq!(lines, ["2", "3"]);
+    }
+
+    #[test]
+    #[cfg(feature = "url")]
+    #[should_panic]
+    fn test_url_not_found() {
+        open_file(&serve_once("404 Not Found", b""));
+    }
+
+    #[test]
+    fn test_skip_last() {
+        let vec: Vec<i32> = SkipLast::new(1..=5, 2).collect();
+        assert_eq!(vec, [1, 2, 3]);
+        let vec: Vec<i32> = SkipLast::new(1..=5, 0).collect();
+        assert_eq!(vec, [1, 2, 3, 4, 5]);
+        assert_eq!(SkipLast::new(1..=2, 3).count(), 0);
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut out: Vec<u8> = Vec::new();
+        let progress = Progress::new(0..250_000, Some(&mut out));
+        assert_eq!(progress.count(), 250_000);
+        let report = String::from_utf8(out).unwrap();
+        assert!(report.contains("\r100000 lines read"));
+        assert!(report.contains("\r200000 lines read"));
+        assert!(report.ends_with("\r\x1b[2K"));
+        let progress = Progress::new(0..10, None::<&mut Vec<u8>>);
+        assert_eq!(progress.sum::<i32>(), 45);
+    }
+
+    #[test]
+    fn test_plain_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "1.5").unwrap();
+        let lines: Vec<String> = open_file(file.path().to_str().unwrap())
+            .lines()
+            .map(|l| l.unwrap())
+            .collect();
+        assert_eq!(lines, ["1.5"]);
+    }
 }
@@ -0,0 +1,91 @@
+use std::fmt;
+
+use crate::read::dateparser::LogDateParser;
+use crate::read::{open_file, read_lines};
+
+/// The kind of values held by an input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    Numbers,
+    Timestamps,
+}
+
+impl fmt::Display for InputKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputKind::Numbers => write!(f, "numbers"),
+            InputKind::Timestamps => write!(f, "timestamps"),
+        }
+    }
+}
+
+/// What was learned about an input from its first lines (see `sniff`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sniff {
+    /// The kind of values of the input.
+    pub kind: InputKind,
+    /// Number of non-empty lines looked at.
+    pub lines: usize,
+    /// Number of them where a timestamp was found.
+    pub timestamps: usize,
+}
+
+impl Sniff {
+    /// Returns true if only some of the lines looked at hold timestamps.
+    pub fn is_mixed(&self) -> bool {
+        self.timestamps > 0 && self.timestamps < self.lines
+    }
+}
+
+/// Looks at the first `lines` non-empty lines of `path` to find out which
+/// kind of values it holds: timestamps if a timestamp (in any of the formats
+/// guessed by `timehist`) is found in all of them, numbers otherwise.
+pub fn sniff(path: &str, lines: usize) -> Sniff {
+    let mut sniff = Sniff {
+        kind: InputKind::Numbers,
+        lines: 0,
+        timestamps: 0,
+    };
+    let candidates = read_lines(open_file(path))
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .take(lines);
+    for line in candidates {
+        sniff.lines += 1;
+        if LogDateParser::new(&line, &None).is_ok() {
+            sniff.timestamps += 1;
+        }
+    }
+    if sniff.lines > 0 && sniff.timestamps == sniff.lines {
+        sniff.kind = InputKind::Timestamps;
+    }
+    sniff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn sniff_text(text: &str) -> Sniff {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+        sniff(file.path().to_str().unwrap(), 3)
+    }
+
+    #[test]
+    fn test_sniff() {
+        let timestamps = sniff_text("[2021-04-15 06:25:31] a\n\n[2021-04-15 06:25:32] b\n");
+        assert_eq!(timestamps.kind, InputKind::Timestamps);
+        assert_eq!(timestamps.lines, 2);
+        assert!(!timestamps.is_mixed());
+        let numbers = sniff_text("1.5\n-2\n3\n2021-04-15 06:25:31\n");
+        assert_eq!(numbers.kind, InputKind::Numbers);
+        assert_eq!((numbers.lines, numbers.timestamps), (3, 0));
+        let mixed = sniff_text("1.5\n2021-04-15 06:25:31\n3\n");
+        assert_eq!(mixed.kind, InputKind::Numbers);
+        assert!(mixed.is_mixed());
+        assert_eq!(sniff_text("").kind, InputKind::Numbers);
+    }
+}
@@ -1,9 +1,10 @@
-use std::io::BufRead;
+use std::cell::{Cell, RefCell};
 
-use chrono::{DateTime, FixedOffset};
+use chrono::{DateTime, Duration, FixedOffset};
 
 use crate::read::dateparser::LogDateParser;
-use crate::read::open_file;
+use crate::read::Timezone;
+use crate::read::{open_file, read_lines};
 
 #[derive(Default, Builder)]
 pub struct SplitTimeReader {
@@ -11,14 +12,90 @@ pub struct SplitTimeReader {
     matches: Vec<String>,
     #[builder(setter(strip_option), default)]
     ts_format: Option<String>,
+    // Shift timestamps to this timezone (see `Timezone`).
+    #[builder(setter(strip_option), default)]
+    timezone: Option<Timezone>,
+    #[builder(setter(skip))]
+    lines: Cell<usize>,
+    #[builder(setter(skip))]
+    gaps: RefCell<Vec<f64>>,
+    #[builder(setter(skip))]
+    events: RefCell<Vec<DateTime<FixedOffset>>>,
 }
 
 impl SplitTimeReader {
+    /// Returns the number of lines read by the last read, matching any of
+    /// the strings or not.
+    pub fn lines(&self) -> usize {
+        self.lines.get()
+    }
+
+    /// Returns the elapsed time, in seconds, between consecutive lines
+    /// matching any of the strings in the last read.  Gaps are negative if
+    /// timestamps go backwards in the input.  The vector is empty if less than
+    /// two lines matched.
+    pub fn gaps(&self) -> Vec<f64> {
+        self.gaps.borrow().clone()
+    }
+
+    /// Returns the number of lines matching any of the strings in the last
+    /// read per window of the given duration, along with the start of every
+    /// window, from the one of the earliest line to the one of the latest.
+    ///
+    /// Windows are aligned to multiples of the duration in the wall clock of
+    /// the earliest line, like the buckets of
+    /// `TimeHistogram::new_with_interval`.  The vector is empty if no line
+    /// matched or the duration is not positive.
+    pub fn rate(&self, window: Duration) -> Vec<(DateTime<FixedOffset>, usize)> {
+        let events = self.events.borrow();
+        let window_us = match window.num_microseconds() {
+            Some(us) if us > 0 => us,
+            _ => return Vec::new(),
+        };
+        let (first, last) = match (events.iter().min(), events.iter().max()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return Vec::new(),
+        };
+        let local_us = first.naive_local().timestamp_micros();
+        let start = first - Duration::microseconds(local_us.rem_euclid(window_us));
+        let slot = |ts: &DateTime<FixedOffset>| {
+            ((*ts - start).num_microseconds().unwrap() / window_us) as usize
+        };
+        let mut counts = vec![0; slot(&last) + 1];
+        for ts in events.iter() {
+            counts[slot(ts)] += 1;
+        }
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| (start + window * i as i32, count))
+            .collect()
+    }
+
+    /// Returns the windows of `rate` with more than `threshold` matching
+    /// lines, to spot bursts of events.
+    pub fn bursts(
+        &self,
+        window: Duration,
+        threshold: usize,
+    ) -> Vec<(DateTime<FixedOffset>, usize)> {
+        self.rate(window)
+            .into_iter()
+            .filter(|(_, count)| *count > threshold)
+            .collect()
+    }
+
     pub fn read(&self, path: &str) -> Vec<(DateTime<FixedOffset>, usize)> {
         let mut vec: Vec<(DateTime<FixedOffset>, usize)> = Vec::new();
-        let mut iterator = open_file(path).lines();
+        self.lines.set(0);
+        self.gaps.borrow_mut().clear();
+        self.events.borrow_mut().clear();
+        let mut iterator = read_lines(open_file(path));
         let first_line = match iterator.next() {
-            Some(Ok(as_string)) => as_string,
+            Some(Ok(as_string)) => {
+                self.lines.set(1);
+                as_string
+            }
             Some(Err(error)) => {
                 error!("{}", error);
                 return vec;
@@ -38,6 +115,7 @@ impl SplitTimeReader {
         for line in iterator {
             match line {
                 Ok(string) => {
+                    self.lines.set(self.lines.get() + 1);
                     if let Ok(x) = parser.parse(&string) {
                         self.push_conditionally(x, &mut vec, &string);
                     }
@@ -54,11 +132,26 @@ impl SplitTimeReader {
         vec: &mut Vec<(DateTime<FixedOffset>, usize)>,
         line: &str,
     ) {
+        let d = match self.timezone {
+            Some(tz) => tz.convert(d),
+            None => d,
+        };
+        let previous = vec.last().map(|(ts, _)| *ts);
+        let len = vec.len();
         for (i, s) in self.matches.iter().enumerate() {
             if line.contains(s) {
                 vec.push((d, i));
             }
         }
+        if vec.len() > len {
+            self.events.borrow_mut().push(d);
+        }
+        if let Some(previous) = previous {
+            if vec.len() > len {
+                let gap = (d - previous).num_microseconds().unwrap() as f64 / 1e6;
+                self.gaps.borrow_mut().push(gap);
+            }
+        }
     }
 }
 
@@ -115,6 +208,69 @@ mod tests {
         assert_eq!(ts[4].1, 0);
     }
 
+    #[test]
+    fn split_time_reader_with_timezone() {
+        let mut builder = SplitTimeReaderBuilder::default();
+        builder
+            .matches(vec!["foo".to_string()])
+            .timezone("-03:00".parse().unwrap());
+        let reader = builder.build().unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[2021-04-15T06:25:31+00:00] foo").unwrap();
+        let ts = reader.read(file.path().to_str().unwrap());
+        assert_eq!(ts.len(), 1);
+        assert_eq!(ts[0].0.to_rfc3339(), "2021-04-15T03:25:31-03:00");
+    }
+
+    #[test]
+    fn split_time_reader_gaps() {
+        let mut builder = SplitTimeReaderBuilder::default();
+        builder.matches(vec!["foo".to_string(), "bar".to_string()]);
+        let reader = builder.build().unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[2021-04-15T06:25:31+00:00] foo").unwrap();
+        writeln!(file, "[2021-04-15T06:25:32+00:00] none").unwrap();
+        writeln!(file, "[2021-04-15T06:25:33+00:00] foobar").unwrap();
+        writeln!(file, "[2021-04-15T06:26:33+00:00] bar").unwrap();
+        reader.read(file.path().to_str().unwrap());
+        assert_eq!(reader.gaps(), [2.0, 60.0]);
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[2021-04-15T06:25:31+00:00] foo").unwrap();
+        reader.read(file.path().to_str().unwrap());
+        assert_eq!(reader.gaps(), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn split_time_reader_rate() {
+        let mut builder = SplitTimeReaderBuilder::default();
+        builder.matches(vec!["foo".to_string(), "bar".to_string()]);
+        let reader = builder.build().unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[2021-04-15T06:25:31+00:00] foo").unwrap();
+        writeln!(file, "[2021-04-15T06:26:02+00:00] foobar").unwrap();
+        writeln!(file, "[2021-04-15T06:26:05+00:00] bar").unwrap();
+        writeln!(file, "[2021-04-15T06:26:05+00:00] none").unwrap();
+        writeln!(file, "[2021-04-15T06:26:59+00:00] foo").unwrap();
+        writeln!(file, "[2021-04-15T06:28:10+00:00] bar").unwrap();
+        reader.read(file.path().to_str().unwrap());
+        let minute = |m: u32| {
+            DateTime::parse_from_rfc3339(&format!("2021-04-15T06:{:02}:00+00:00", m)).unwrap()
+        };
+        let rate = reader.rate(Duration::minutes(1));
+        assert_eq!(
+            rate,
+            [
+                (minute(25), 1),
+                (minute(26), 3),
+                (minute(27), 0),
+                (minute(28), 1)
+            ]
+        );
+        assert_eq!(reader.bursts(Duration::minutes(1), 1), [(minute(26), 3)]);
+        assert_eq!(reader.rate(Duration::minutes(5)), [(minute(25), 5)]);
+        assert!(reader.rate(Duration::zero()).is_empty());
+    }
+
     #[test]
     fn split_time_no_matches() {
         let reader = SplitTimeReader::default();
@@ -1,10 +1,12 @@
-use std::io::BufRead;
+use std::cell::{Cell, RefCell};
 
 use chrono::{DateTime, Duration, FixedOffset};
 use regex::Regex;
 
-use crate::read::dateparser::LogDateParser;
-use crate::read::open_file;
+use crate::plot::{MatchBar, MatchBarRow, TimeHistogram};
+use crate::read::dateparser::{EpochUnit, LogDateParser};
+use crate::read::{open_file, read_lines};
+use crate::read::{CalendarUnit, Timezone};
 
 #[derive(Default, Builder)]
 pub struct TimeReader {
@@ -16,25 +18,101 @@ pub struct TimeReader {
     duration: Option<Duration>,
     #[builder(default)]
     early_stop: bool,
+    // Shift timestamps to this timezone (see `Timezone`).
+    #[builder(setter(strip_option), default)]
+    timezone: Option<Timezone>,
+    // Parse timestamps as Unix epochs of this unit, instead of guessing.
+    // Ignored if ts_format is set.
+    #[builder(setter(strip_option), default)]
+    epoch_unit: Option<EpochUnit>,
+    // Fixed interval of the buckets built by `histogram` (see
+    // `TimeHistogram::new_with_interval`).
+    #[builder(setter(strip_option), default)]
+    interval: Option<Duration>,
+    // Unit whose buckets timestamps are counted in by `read_grouped`.
+    #[builder(default)]
+    group_by: CalendarUnit,
+    // Record the offsets of timestamps from the first one (see `offsets`).
+    #[builder(default)]
+    relative: bool,
+    #[builder(setter(skip))]
+    skipped: Cell<usize>,
+    #[builder(setter(skip))]
+    lines: Cell<usize>,
+    #[builder(setter(skip))]
+    offsets: RefCell<Vec<f64>>,
+}
+
+impl TimeReaderBuilder {
+    /// Forces timestamps to be parsed with this `chrono` strftime format,
+    /// instead of guessing it from the first line.
+    pub fn format(&mut self, fmt: &str) -> &mut Self {
+        self.ts_format(fmt.to_string())
+    }
 }
 
 impl TimeReader {
+    /// Returns the number of lines skipped by the last read because no
+    /// timestamp could be parsed in them.
+    pub fn skipped(&self) -> usize {
+        self.skipped.get()
+    }
+
+    /// Returns the number of lines read by the last read, holding a timestamp
+    /// or not.
+    pub fn lines(&self) -> usize {
+        self.lines.get()
+    }
+
+    /// Returns the elapsed time, in seconds, from the first timestamp of the
+    /// last read to every one of them (in input order, so the first offset
+    /// is zero), for feeding a numerical histogram.  The vector is only
+    /// filled if the reader was built with `relative` set.
+    pub fn offsets(&self) -> Vec<f64> {
+        self.offsets.borrow().clone()
+    }
+
     pub fn read(&self, path: &str) -> Vec<DateTime<FixedOffset>> {
         let mut vec: Vec<DateTime<FixedOffset>> = Vec::new();
-        let mut iterator = open_file(path).lines();
-        let first_line = match iterator.next() {
-            Some(Ok(as_string)) => as_string,
-            Some(Err(error)) => {
-                error!("{}", error);
-                return vec;
-            }
-            _ => return vec,
-        };
-        let parser = match LogDateParser::new(&first_line, &self.ts_format) {
-            Ok(p) => p,
-            Err(error) => {
-                error!("Could not figure out parsing strategy: {}", error);
-                return vec;
+        self.skipped.set(0);
+        self.lines.set(0);
+        self.offsets.borrow_mut().clear();
+        let mut iterator = read_lines(open_file(path));
+        // When guessing, the first line decides the parsing strategy.  With
+        // an explicit format, lines are skipped until one matches it.
+        let (parser, first_line) = loop {
+            let line = match iterator.next() {
+                Some(Ok(as_string)) => {
+                    self.lines.set(self.lines.get() + 1);
+                    as_string
+                }
+                Some(Err(error)) => {
+                    error!("{}", error);
+                    return vec;
+                }
+                _ => {
+                    if let Some(ts_format) = &self.ts_format {
+                        error!("Could not locate a '{}' timestamp in input", ts_format);
+                    }
+                    return vec;
+                }
+            };
+            let parser = match self.epoch_unit {
+                Some(unit) if self.ts_format.is_none() => {
+                    LogDateParser::new_with_epoch(&line, unit)
+                }
+                _ => LogDateParser::new(&line, &self.ts_format),
+            };
+            match parser {
+                Ok(p) => break (p, line),
+                Err(error) if self.ts_format.is_some() => {
+                    debug!("{}", error);
+                    self.skipped.set(self.skipped.get() + 1);
+                }
+                Err(error) => {
+                    error!("Could not figure out parsing strategy: {}", error);
+                    return vec;
+                }
             }
         };
         let mut cut_datetime: Option<DateTime<FixedOffset>> = None;
@@ -49,10 +127,13 @@ impl TimeReader {
         for line in iterator {
             match line {
                 Ok(string) => {
+                    self.lines.set(self.lines.get() + 1);
                     if let Ok(x) = parser.parse(&string) {
                         if self.push_conditionally(x, &mut vec, &string, cut_datetime) {
                             break;
                         }
+                    } else {
+                        self.skipped.set(self.skipped.get() + 1);
                     }
                 }
                 Err(error) => error!("{}", error),
@@ -66,9 +147,57 @@ impl TimeReader {
                 }
             }
         }
+        if self.relative {
+            if let Some(first) = vec.first() {
+                *self.offsets.borrow_mut() = vec
+                    .iter()
+                    .map(|d| (*d - *first).num_microseconds().unwrap() as f64 / 1e6)
+                    .collect();
+            }
+        }
         vec
     }
 
+    /// Builds a `TimeHistogram` from timestamps: with buckets of the
+    /// configured `interval`, aligned to its multiples in the wall clock, if
+    /// any, or with `size` buckets spanning the timestamps otherwise.
+    ///
+    /// Fails for an interval that is not positive or that would need more
+    /// than `MAX_TIME_BUCKETS` buckets.
+    pub fn histogram(
+        &self,
+        size: usize,
+        vec: &[DateTime<FixedOffset>],
+    ) -> Result<TimeHistogram, String> {
+        match self.interval {
+            Some(interval) => TimeHistogram::new_with_interval(interval, vec),
+            None => Ok(TimeHistogram::new(size, vec)),
+        }
+    }
+
+    /// Reads timestamps as `read` does, and counts them per `group_by` unit
+    /// (see `group`).
+    pub fn read_grouped(&self, path: &str) -> MatchBar {
+        self.group(&self.read(path))
+    }
+
+    /// Counts timestamps per `group_by` unit, collapsing dates, so that there
+    /// is a row for every hour of day (or day of week), in order.  Timestamps
+    /// are taken in their offset, which is the one of the configured timezone
+    /// if any.
+    pub fn group(&self, vec: &[DateTime<FixedOffset>]) -> MatchBar {
+        let mut rows: Vec<MatchBarRow> = self
+            .group_by
+            .labels()
+            .iter()
+            .map(|label| MatchBarRow::new(label))
+            .collect();
+        for dt in vec {
+            rows[self.group_by.slot(dt)].count += 1;
+        }
+        MatchBar::new(rows)
+    }
+
     fn push_conditionally(
         &self,
         d: DateTime<FixedOffset>,
@@ -76,6 +205,10 @@ impl TimeReader {
         line: &str,
         cut_datetime: Option<DateTime<FixedOffset>>,
     ) -> bool {
+        let d = match self.timezone {
+            Some(tz) => tz.convert(d),
+            None => d,
+        };
         if let Some(cut) = cut_datetime {
             if cut < d {
                 return self.early_stop;
@@ -145,6 +278,145 @@ mod tests {
         );
     }
 
+    #[test]
+    fn time_reader_with_interval() {
+        let mut builder = TimeReaderBuilder::default();
+        builder.interval(Duration::minutes(1));
+        let reader = builder.build().unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[2021-04-15T06:25:31+00:00] foo").unwrap();
+        writeln!(file, "none").unwrap();
+        writeln!(file, "[2021-04-15T06:26:10+00:00] foo").unwrap();
+        writeln!(file, "[2021-04-15T06:26:50+00:00] foo").unwrap();
+        let ts = reader.read(file.path().to_str().unwrap());
+        assert_eq!(reader.skipped(), 1);
+        let tsv = reader.histogram(10, &ts).unwrap().to_tsv();
+        let rows: Vec<&str> = tsv.lines().collect();
+        assert_eq!(
+            rows,
+            [
+                "2021-04-15T06:25:00+00:00\t2021-04-15T06:26:00+00:00\t1",
+                "2021-04-15T06:26:00+00:00\t2021-04-15T06:27:00+00:00\t2"
+            ]
+        );
+        // Without an interval, the given number of buckets is used
+        let reader = TimeReaderBuilder::default().build().unwrap();
+        assert_eq!(
+            reader.histogram(10, &ts).unwrap().to_tsv().lines().count(),
+            10
+        );
+        let mut builder = TimeReaderBuilder::default();
+        builder.interval(Duration::zero());
+        assert!(builder.build().unwrap().histogram(10, &ts).is_err());
+    }
+
+    #[test]
+    fn time_reader_with_timezone() {
+        let mut builder = TimeReaderBuilder::default();
+        builder.timezone("Europe/Madrid".parse().unwrap());
+        let reader = builder.build().unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[2021-04-15T06:25:31+00:00] foo").unwrap();
+        writeln!(file, "[2021-04-15T03:26:31-03:00] foo").unwrap();
+        let ts = reader.read(file.path().to_str().unwrap());
+        assert_eq!(ts.len(), 2);
+        assert_eq!(ts[0].to_rfc3339(), "2021-04-15T08:25:31+02:00");
+        assert_eq!(ts[1].to_rfc3339(), "2021-04-15T08:26:31+02:00");
+        // Timestamps with no offset are taken as UTC
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "2021-01-15 06:26:31 foo").unwrap();
+        let ts = reader.read(file.path().to_str().unwrap());
+        assert_eq!(ts[0].to_rfc3339(), "2021-01-15T07:26:31+01:00");
+    }
+
+    #[test]
+    fn time_reader_relative() {
+        let reader = TimeReaderBuilder::default().relative(true).build().unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[2021-04-15T06:25:31.0+00:00] foo").unwrap();
+        writeln!(file, "[2021-04-15T06:25:33.5+00:00] foo").unwrap();
+        writeln!(file, "[2021-04-15T06:26:33.5+00:00] foo").unwrap();
+        let ts = reader.read(file.path().to_str().unwrap());
+        assert_eq!(ts.len(), 3);
+        assert_eq!(reader.offsets(), [0.0, 2.5, 62.5]);
+        let reader = TimeReader::default();
+        reader.read(file.path().to_str().unwrap());
+        assert_eq!(reader.offsets(), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn time_reader_grouped() {
+        let reader = TimeReaderBuilder::default()
+            .group_by(CalendarUnit::HourOfDay)
+            .timezone("+02:00".parse().unwrap())
+            .build()
+            .unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[2021-04-15T06:25:31+00:00] foo").unwrap();
+        writeln!(file, "[2021-04-16T06:59:00+00:00] foo").unwrap();
+        writeln!(file, "[2021-04-16T23:00:00+00:00] foo").unwrap();
+        let bars = reader.read_grouped(file.path().to_str().unwrap());
+        assert_eq!(bars.vec.len(), 24);
+        // Hours are the ones of the timezone
+        assert_eq!((bars.vec[8].label.as_str(), bars.vec[8].count), ("08", 2));
+        assert_eq!((bars.vec[1].label.as_str(), bars.vec[1].count), ("01", 1));
+        assert_eq!(bars.vec.iter().map(|row| row.count).sum::<usize>(), 3);
+        // The last one is a Saturday in the timezone, but a Friday in UTC
+        let reader = TimeReaderBuilder::default()
+            .group_by(CalendarUnit::DayOfWeek)
+            .timezone("+02:00".parse().unwrap())
+            .build()
+            .unwrap();
+        let bars = reader.read_grouped(file.path().to_str().unwrap());
+        let counts: Vec<usize> = bars.vec.iter().map(|row| row.count).collect();
+        assert_eq!(counts, [0, 0, 0, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn time_reader_with_forced_format() {
+        let reader = TimeReaderBuilder::default()
+            .format("%d/%m/%y %H:%M")
+            .build()
+            .unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "starting up").unwrap();
+        writeln!(file, "01/02/03 06:25 foo").unwrap();
+        writeln!(file, "garbage").unwrap();
+        writeln!(file, "02/02/03 06:25 foo").unwrap();
+        let ts = reader.read(file.path().to_str().unwrap());
+        assert_eq!(ts.len(), 2);
+        assert_eq!(
+            ts[0],
+            DateTime::parse_from_rfc3339("2003-02-01T06:25:00+00:00").unwrap()
+        );
+        assert_eq!(
+            ts[1],
+            DateTime::parse_from_rfc3339("2003-02-02T06:25:00+00:00").unwrap()
+        );
+        assert_eq!(reader.skipped(), 2);
+    }
+
+    #[test]
+    fn time_reader_with_epoch_unit() {
+        let reader = TimeReaderBuilder::default()
+            .epoch_unit(EpochUnit::Millis)
+            .build()
+            .unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "1609459200000 foo").unwrap();
+        writeln!(file, "1609459260000 foo").unwrap();
+        let ts = reader.read(file.path().to_str().unwrap());
+        assert_eq!(ts.len(), 2);
+        assert_eq!(
+            ts[0],
+            DateTime::parse_from_rfc3339("2021-01-01T00:00:00+00:00").unwrap()
+        );
+        assert_eq!(
+            ts[1],
+            DateTime::parse_from_rfc3339("2021-01-01T00:01:00+00:00").unwrap()
+        );
+    }
+
     #[test]
     fn time_with_duration() {
         let mut builder = TimeReaderBuilder::default();
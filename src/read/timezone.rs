@@ -0,0 +1,84 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, Offset, TimeZone};
+use chrono_tz::Tz;
+
+/// A timezone where timestamps can be shifted to, either a fixed offset from
+/// UTC or an IANA timezone (like `Europe/Madrid`).
+///
+/// Timestamps are converted from absolute instants (parsed timestamps with no
+/// explicit offset are taken as UTC), so converting is never ambiguous, even
+/// around DST transitions: every instant keeps the offset that was in effect
+/// at that moment in the timezone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timezone {
+    Fixed(FixedOffset),
+    Named(Tz),
+}
+
+impl Timezone {
+    /// Returns the same instant as `dt`, with the offset the timezone had at
+    /// that moment.
+    pub fn convert(&self, dt: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        match self {
+            Timezone::Fixed(offset) => dt.with_timezone(offset),
+            Timezone::Named(tz) => {
+                let offset = tz.offset_from_utc_datetime(&dt.naive_utc()).fix();
+                dt.with_timezone(&offset)
+            }
+        }
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = String;
+
+    /// Parses fixed offsets like `+02:00`, `-0530` or `Z`, and IANA names.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(offset) = s.parse::<FixedOffset>() {
+            return Ok(Timezone::Fixed(offset));
+        }
+        if s == "Z" {
+            return Ok(Timezone::Fixed(FixedOffset::east_opt(0).unwrap()));
+        }
+        match s.parse::<Tz>() {
+            Ok(tz) => Ok(Timezone::Named(tz)),
+            Err(_) => Err(format!("Unknown timezone '{}'", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_fixed_offset() {
+        let tz: Timezone = "+02:00".parse().unwrap();
+        let dt = DateTime::parse_from_rfc3339("2021-04-15T06:25:31+00:00").unwrap();
+        let converted = tz.convert(dt);
+        assert_eq!(converted, dt);
+        assert_eq!(converted.to_rfc3339(), "2021-04-15T08:25:31+02:00");
+        let tz: Timezone = "-0530".parse().unwrap();
+        assert_eq!(tz.convert(dt).to_rfc3339(), "2021-04-15T00:55:31-05:30");
+    }
+
+    #[test]
+    fn test_named() {
+        let tz: Timezone = "Europe/Madrid".parse().unwrap();
+        // Winter and summer time
+        let dt = DateTime::parse_from_rfc3339("2021-01-15T06:25:31+00:00").unwrap();
+        assert_eq!(tz.convert(dt).to_rfc3339(), "2021-01-15T07:25:31+01:00");
+        let dt = DateTime::parse_from_rfc3339("2021-04-15T06:25:31+00:00").unwrap();
+        assert_eq!(tz.convert(dt).to_rfc3339(), "2021-04-15T08:25:31+02:00");
+        // Explicit offsets are respected
+        let dt = DateTime::parse_from_rfc3339("2021-04-15T06:25:31-03:00").unwrap();
+        assert_eq!(tz.convert(dt).to_rfc3339(), "2021-04-15T11:25:31+02:00");
+    }
+
+    #[test]
+    fn test_unknown() {
+        assert!("Mars/Olympus".parse::<Timezone>().is_err());
+    }
+}
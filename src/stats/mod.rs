@@ -1,10 +1,16 @@
 use std::fmt;
 
+use serde::Serialize;
 use yansi::Color::Blue;
 
 use crate::format::F64Formatter;
 
-#[derive(Debug)]
+// Standard normal distribution, used for Q-Q plots
+pub(crate) mod normal;
+// Student's t-distribution, used for confidence intervals
+mod student;
+
+#[derive(Debug, Clone, Serialize)]
 /// A struct holding statistical data regarding a unsorted set of numerical
 /// values.
 pub struct Stats {
@@ -20,6 +26,7 @@ pub struct Stats {
     pub var: f64,
     /// Number of samples of the input values.
     pub samples: usize,
+    #[serde(skip)]
     precision: Option<usize>, // If None, then human friendly display will be used
 
     /// 50 percentile
@@ -30,18 +37,88 @@ pub struct Stats {
     pub p95: f64,
     /// 99 percentile
     pub p99: f64,
+    #[serde(skip)]
+    sorted: Vec<f64>,
+    #[serde(skip)]
+    m2: f64, // Sum of squared differences from the mean
+    #[serde(skip)]
+    m3: f64, // Sum of cubed differences from the mean
+    #[serde(skip)]
+    m4: f64, // Sum of differences from the mean to the fourth power
+    #[serde(skip)]
+    non_finite: usize, // Number of NaN or infinite values left out
+    #[serde(skip)]
+    show_shape: bool,
+    #[serde(skip)]
+    show_mad: bool,
+    #[serde(skip)]
+    mean_ci_level: Option<f64>, // Confidence level of the displayed interval
 }
 
-fn percentiles(vec: &mut [f64]) -> (f64, f64, f64, f64) {
-    vec.sort_by(|a, b| a.partial_cmp(b).unwrap());
+// Number of values, mean and sums of powers of their differences from it
+#[derive(Clone, Copy)]
+struct Moments {
+    samples: usize,
+    avg: f64,
+    m2: f64,
+    m3: f64,
+    m4: f64,
+}
 
-    let len = vec.len();
-    let p50 = vec[len / 2];
-    let p90 = vec[(len * 9) / 10];
-    let p95 = vec[(len * 95) / 100];
-    let p99 = vec[(len * 99) / 100];
+impl Moments {
+    // Combines the moments of two sets of values into the ones of their
+    // union, following Pébay's formulas.
+    fn combine(self, other: Moments) -> Moments {
+        if other.samples == 0 {
+            return self;
+        }
+        let (na, nb) = (self.samples as f64, other.samples as f64);
+        let samples = self.samples + other.samples;
+        let n = samples as f64;
+        let delta = other.avg - self.avg;
+        let delta_n = delta / n;
+        let m2 = self.m2 + other.m2 + delta * delta_n * na * nb;
+        let m3 = self.m3
+            + other.m3
+            + delta * delta_n * delta_n * na * nb * (na - nb)
+            + 3.0 * delta_n * (na * other.m2 - nb * self.m2);
+        let m4 = self.m4
+            + other.m4
+            + delta * delta_n * delta_n * delta_n * na * nb * (na * na - na * nb + nb * nb)
+            + 6.0 * delta_n * delta_n * (na * na * other.m2 + nb * nb * self.m2)
+            + 4.0 * delta_n * (na * other.m3 - nb * self.m3);
+        Moments {
+            samples,
+            avg: self.avg + delta_n * nb,
+            m2,
+            m3,
+            m4,
+        }
+    }
+}
+
+/// Returns the `q` quantile (in the `[0, 1]` range) of a sorted slice,
+/// interpolating linearly between the two closest values.  Returns NaN for an
+/// empty slice.
+pub(crate) fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let position = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+fn percentiles(vec: &mut [f64]) -> (f64, f64, f64, f64) {
+    vec.sort_by(|a, b| a.total_cmp(b));
 
-    (p50, p90, p95, p99)
+    (
+        quantile(vec, 0.5),
+        quantile(vec, 0.9),
+        quantile(vec, 0.95),
+        quantile(vec, 0.99),
+    )
 }
 
 impl Stats {
@@ -50,42 +127,287 @@ impl Stats {
     /// `precision` is an Option with the number of decimals to display.  If
     /// "None" is used, human units will be used, with an heuristic based on the
     /// input data for deciding the units and the decimal places.
+    ///
+    /// `vec` is sorted in place.  NaN and infinite values are left out of the
+    /// stats (they are sorted to the ends of `vec`).
     pub fn new(vec: &mut [f64], precision: Option<usize>) -> Self {
-        let mut max = vec[0];
-        let mut min = max;
-        let mut temp: f64 = 0.0;
-        let sum = vec.iter().sum::<f64>();
-        let avg = sum / vec.len() as f64;
+        let mut builder = StatsBuilder::new(precision);
         for val in vec.iter() {
-            max = max.max(*val);
-            min = min.min(*val);
-            temp += (avg - *val).powi(2);
+            builder.push(*val);
         }
-        let var = temp / vec.len() as f64;
-        let std = var.sqrt();
-        let (p50, p90, p95, p99) = percentiles(vec);
+        vec.sort_by(|a, b| a.total_cmp(b));
+        let mut sorted: Vec<f64> = vec.iter().copied().filter(|x| x.is_finite()).collect();
+        let (p50, p90, p95, p99) = percentiles(&mut sorted);
         Self {
-            min,
-            max,
-            avg,
-            std,
-            var,
-            samples: vec.len(),
-            precision,
             p50,
             p90,
             p95,
             p99,
+            sorted,
+            ..builder.finish()
+        }
+    }
+
+    /// Returns the number of NaN or infinite input values, that were left out
+    /// of the stats.
+    pub fn non_finite(&self) -> usize {
+        self.non_finite
+    }
+
+    /// Updates the stats so that they describe both their input values and
+    /// the ones of `other`.  Percentiles are kept only if both have them.
+    pub fn merge(&mut self, other: &Stats) {
+        self.non_finite += other.non_finite;
+        if other.samples == 0 {
+            return;
+        }
+        if self.samples == 0 {
+            *self = Self {
+                precision: self.precision,
+                non_finite: self.non_finite,
+                ..other.clone()
+            };
+            return;
+        }
+        let moments = self.moments().combine(other.moments());
+        self.samples = moments.samples;
+        self.avg = moments.avg;
+        self.m2 = moments.m2;
+        self.m3 = moments.m3;
+        self.m4 = moments.m4;
+        self.var = self.m2 / self.samples as f64;
+        self.std = self.var.sqrt();
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        if self.sorted.is_empty() || other.sorted.is_empty() {
+            self.sorted.clear();
+            self.p50 = f64::NAN;
+            self.p90 = f64::NAN;
+            self.p95 = f64::NAN;
+            self.p99 = f64::NAN;
+        } else {
+            self.sorted.extend_from_slice(&other.sorted);
+            (self.p50, self.p90, self.p95, self.p99) = percentiles(&mut self.sorted);
+        }
+    }
+
+    fn moments(&self) -> Moments {
+        Moments {
+            samples: self.samples,
+            avg: self.avg,
+            m2: self.m2,
+            m3: self.m3,
+            m4: self.m4,
         }
     }
+
+    /// Returns the skewness of the input values: positive when the right tail
+    /// of their distribution is longer, negative when the left one is, and
+    /// about zero for symmetric distributions.  NaN if all values are equal.
+    pub fn skewness(&self) -> f64 {
+        (self.samples as f64).sqrt() * self.m3 / self.m2.powf(1.5)
+    }
+
+    /// Returns the excess kurtosis of the input values: about zero for normal
+    /// distributions and positive for heavy-tailed ones.  NaN if all values
+    /// are equal.
+    pub fn kurtosis(&self) -> f64 {
+        self.samples as f64 * self.m4 / (self.m2 * self.m2) - 3.0
+    }
+
+    /// Returns a struct displaying only the given `fields`, in a single line
+    /// (e.g. "Min = 1.0; p99 = 9.9").  Displaying the stats themselves prints
+    /// the full summary.
+    pub fn display_fields<'a>(&'a self, fields: &'a [StatField]) -> StatsDisplay<'a> {
+        StatsDisplay {
+            stats: self,
+            fields,
+        }
+    }
+
+    /// Returns a JSON object with the summary of the input values: `samples`,
+    /// `min`, `max`, `mean`, `median`, `stddev` (population one), `variance`
+    /// and the `p90`, `p95` and `p99` percentiles.  These field names are
+    /// kept stable, and unavailable (NaN) values are null.
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "samples": self.samples,
+            "min": self.min,
+            "max": self.max,
+            "mean": self.avg,
+            "median": self.p50,
+            "stddev": self.std,
+            "variance": self.var,
+            "p90": self.p90,
+            "p95": self.p95,
+            "p99": self.p99,
+        })
+        .to_string()
+    }
+
+    fn formatter(&self) -> F64Formatter {
+        match self.precision {
+            None => F64Formatter::new_with_range(self.min..self.max),
+            Some(n) => F64Formatter::new(n),
+        }
+    }
+
+    // Returns the number of decimals used for displaying the spread and the
+    // shape of values: the ones of `precision` but no less than 3 of them, so
+    // that data with a small range is not rounded down to zero.
+    fn spread_decimals(&self) -> usize {
+        self.precision.map_or(3, |n| n.max(3))
+    }
+
+    /// Makes the display use `precision` decimals (see `new`) instead of the
+    /// ones given when the stats were created.
+    pub fn with_precision(mut self, precision: Option<usize>) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Makes the display include skewness and kurtosis, left out by default.
+    pub fn with_shape(mut self) -> Self {
+        self.show_shape = true;
+        self
+    }
+
+    /// Makes the display include the median absolute deviation (see `mad`),
+    /// left out by default.
+    pub fn with_mad(mut self) -> Self {
+        self.show_mad = true;
+        self
+    }
+
+    /// Makes the display include the confidence interval for the mean at the
+    /// given `level` (see `mean_ci`).
+    pub fn with_mean_ci(mut self, level: f64) -> Self {
+        self.mean_ci_level = Some(level);
+        self
+    }
+
+    /// Returns the confidence interval for the mean of the population the
+    /// input values were sampled from, at the given `level` (e.g. 0.95 for a
+    /// 95% interval).  It is computed with the t-distribution, so it assumes
+    /// that the population is about normal or that there are many samples.
+    ///
+    /// Both ends are NaN with less than two samples or with a level not in
+    /// the `(0, 1)` range.
+    pub fn mean_ci(&self, level: f64) -> (f64, f64) {
+        if self.samples < 2 || !(level > 0.0 && level < 1.0) {
+            return (f64::NAN, f64::NAN);
+        }
+        let error = self.std_dev(true) / (self.samples as f64).sqrt();
+        let t = student::t_quantile((1.0 + level) / 2.0, (self.samples - 1) as f64);
+        (self.avg - t * error, self.avg + t * error)
+    }
+
+    /// Returns the variance of the input values.  If `sample` is true, the
+    /// unbiased sample variance (dividing by `n - 1`) is returned, otherwise
+    /// the population variance is.
+    pub fn variance(&self, sample: bool) -> f64 {
+        if !sample {
+            return self.var;
+        }
+        if self.samples < 2 {
+            return 0.0;
+        }
+        self.m2 / (self.samples - 1) as f64
+    }
+
+    /// Returns the standard deviation of the input values.  See `variance`
+    /// for the meaning of `sample`.
+    pub fn std_dev(&self, sample: bool) -> f64 {
+        self.variance(sample).sqrt()
+    }
+
+    /// Returns the median of the input values, interpolating between the two
+    /// central values for even-length inputs.
+    pub fn median(&self) -> f64 {
+        self.percentile(50.0)
+    }
+
+    /// Returns the `p` percentile (in the `[0, 100]` range) of the input
+    /// values, interpolating linearly between the two closest values.
+    ///
+    /// Percentiles are not available (NaN) when built from a `StatsBuilder`.
+    pub fn percentile(&self, p: f64) -> f64 {
+        quantile(&self.sorted, p / 100.0)
+    }
+
+    /// Returns the mean of input values once the lowest and highest `fraction`
+    /// of them are discarded (e.g. 0.1 for a 10% trimmed mean), so that it is
+    /// not skewed by outliers.
+    ///
+    /// `fraction` is clamped to the `[0, 0.5)` range: at least one value (or
+    /// two for an even number of them) is always kept.  Like percentiles, the
+    /// trimmed mean is not available (NaN) when built from a `StatsBuilder`.
+    pub fn trimmed_mean(&self, fraction: f64) -> f64 {
+        let len = self.sorted.len();
+        if len == 0 {
+            return f64::NAN;
+        }
+        let trimmed = ((len as f64 * fraction.max(0.0)) as usize).min((len - 1) / 2);
+        let kept = &self.sorted[trimmed..len - trimmed];
+        kept.iter().sum::<f64>() / kept.len() as f64
+    }
+
+    /// Returns the median absolute deviation: the median of the absolute
+    /// deviations of input values from their median.  This is a spread
+    /// measure more robust than the standard deviation against outliers.
+    ///
+    /// Like percentiles, it is not available (NaN) when built from a
+    /// `StatsBuilder`.
+    pub fn mad(&self) -> f64 {
+        let median = self.median();
+        let mut deviations: Vec<f64> = self.sorted.iter().map(|x| (x - median).abs()).collect();
+        deviations.sort_by(|a, b| a.total_cmp(b));
+        quantile(&deviations, 0.5)
+    }
+
+    /// Returns the most frequent input value, or None if no value is repeated.
+    /// Ties are solved in favor of the lowest value.
+    ///
+    /// Values are grouped by bit-equality, so this is mostly meaningful for
+    /// discrete data: continuous data should be rounded upstream.  Like
+    /// percentiles, the mode is not available when built from a `StatsBuilder`.
+    pub fn mode(&self) -> Option<f64> {
+        self.mode_with_count().map(|(value, _)| value)
+    }
+
+    /// Returns the mode (see `mode`) along with its number of occurrences.
+    pub fn mode_with_count(&self) -> Option<(f64, usize)> {
+        let mut best: Option<(f64, usize)> = None;
+        let mut start = 0;
+        while start < self.sorted.len() {
+            let value = self.sorted[start];
+            let run = self.sorted[start..]
+                .iter()
+                .take_while(|x| x.to_bits() == value.to_bits())
+                .count();
+            if run > 1 && run > best.map_or(0, |(_, count)| count) {
+                best = Some((value, run));
+            }
+            start += run;
+        }
+        best
+    }
 }
 
 impl fmt::Display for Stats {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let formatter = match self.precision {
-            None => F64Formatter::new_with_range(self.min..self.max),
-            Some(n) => F64Formatter::new(n),
-        };
+        let formatter = self.formatter();
+        if self.samples == 0 {
+            writeln!(f, "Samples = {}", Blue.paint("0"))?;
+            if self.non_finite > 0 {
+                writeln!(
+                    f,
+                    "non-finite dropped: {}",
+                    Blue.paint(self.non_finite.to_string())
+                )?;
+            }
+            return Ok(());
+        }
         writeln!(
             f,
             "Samples = {len}; Min = {min}; Max = {max}",
@@ -93,13 +415,45 @@ impl fmt::Display for Stats {
             min = Blue.paint(formatter.format(self.min)),
             max = Blue.paint(formatter.format(self.max)),
         )?;
+        let spread = self.spread_decimals();
+        let ci = match self.mean_ci_level {
+            Some(level) if self.samples > 1 => {
+                let (low, high) = self.mean_ci(level);
+                format!(
+                    " ({}% CI [{}, {}])",
+                    (level * 1000.0).round() / 10.0,
+                    Blue.paint(formatter.format(low)),
+                    Blue.paint(formatter.format(high)),
+                )
+            }
+            _ => String::new(),
+        };
         writeln!(
             f,
-            "Average = {avg}; Variance = {var}; STD = {std}",
+            "Average = {avg}{ci}; Variance = {var}; STD = {std}; Sample STD = {sstd}",
             avg = Blue.paint(formatter.format(self.avg)),
-            var = Blue.paint(format!("{:.3}", self.var)),
-            std = Blue.paint(format!("{:.3}", self.std)),
+            var = Blue.paint(format!("{:.*}", spread, self.var)),
+            std = Blue.paint(format!("{:.*}", spread, self.std)),
+            sstd = Blue.paint(format!("{:.*}", spread, self.std_dev(true))),
         )?;
+        if self.show_shape {
+            writeln!(
+                f,
+                "Skewness = {skew}; Kurtosis = {kurt}",
+                skew = Blue.paint(format!("{:.*}", spread, self.skewness())),
+                kurt = Blue.paint(format!("{:.*}", spread, self.kurtosis())),
+            )?;
+        }
+        if self.non_finite > 0 {
+            writeln!(
+                f,
+                "non-finite dropped: {}",
+                Blue.paint(self.non_finite.to_string())
+            )?;
+        }
+        if self.sorted.is_empty() {
+            return Ok(());
+        }
         writeln!(
             f,
             "p50 = {p50}; p90 = {p90}; p95 = {p95}; p99 = {p99}",
@@ -107,7 +461,227 @@ impl fmt::Display for Stats {
             p90 = Blue.paint(formatter.format(self.p90)),
             p95 = Blue.paint(formatter.format(self.p95)),
             p99 = Blue.paint(formatter.format(self.p99)),
-        )
+        )?;
+        let mut fields = Vec::new();
+        if self.show_mad {
+            fields.push(format!(
+                "MAD = {}",
+                Blue.paint(formatter.format(self.mad()))
+            ));
+        }
+        if let Some((mode, count)) = self.mode_with_count() {
+            fields.push(format!(
+                "Mode = {mode} ({count} times)",
+                mode = Blue.paint(formatter.format(mode)),
+                count = Blue.paint(count.to_string()),
+            ));
+        }
+        if fields.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "{}", fields.join("; "))
+    }
+}
+
+/// A statistic that can be selected for display with `Stats::display_fields`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatField {
+    Samples,
+    Min,
+    Max,
+    Average,
+    Variance,
+    Std,
+    SampleStd,
+    P50,
+    P90,
+    P95,
+    P99,
+    Mad,
+    Mode,
+    Skewness,
+    Kurtosis,
+}
+
+impl StatField {
+    fn label(&self) -> &'static str {
+        match self {
+            StatField::Samples => "Samples",
+            StatField::Min => "Min",
+            StatField::Max => "Max",
+            StatField::Average => "Average",
+            StatField::Variance => "Variance",
+            StatField::Std => "STD",
+            StatField::SampleStd => "Sample STD",
+            StatField::P50 => "p50",
+            StatField::P90 => "p90",
+            StatField::P95 => "p95",
+            StatField::P99 => "p99",
+            StatField::Mad => "MAD",
+            StatField::Mode => "Mode",
+            StatField::Skewness => "Skewness",
+            StatField::Kurtosis => "Kurtosis",
+        }
+    }
+}
+
+/// A `Display` implementation for a subset of the fields of `Stats`, printed
+/// in a single line.  See `Stats::display_fields`.
+pub struct StatsDisplay<'a> {
+    stats: &'a Stats,
+    fields: &'a [StatField],
+}
+
+impl StatsDisplay<'_> {
+    fn value(&self, field: StatField, formatter: &F64Formatter) -> String {
+        let stats = self.stats;
+        match field {
+            StatField::Samples => stats.samples.to_string(),
+            StatField::Min => formatter.format(stats.min),
+            StatField::Max => formatter.format(stats.max),
+            StatField::Average => formatter.format(stats.avg),
+            StatField::Variance => format!("{:.*}", stats.spread_decimals(), stats.var),
+            StatField::Std => format!("{:.*}", stats.spread_decimals(), stats.std),
+            StatField::SampleStd => format!("{:.*}", stats.spread_decimals(), stats.std_dev(true)),
+            StatField::P50 => formatter.format(stats.p50),
+            StatField::P90 => formatter.format(stats.p90),
+            StatField::P95 => formatter.format(stats.p95),
+            StatField::P99 => formatter.format(stats.p99),
+            StatField::Mad => formatter.format(stats.mad()),
+            StatField::Mode => match stats.mode() {
+                Some(mode) => formatter.format(mode),
+                None => String::from("none"),
+            },
+            StatField::Skewness => format!("{:.*}", stats.spread_decimals(), stats.skewness()),
+            StatField::Kurtosis => format!("{:.*}", stats.spread_decimals(), stats.kurtosis()),
+        }
+    }
+}
+
+impl fmt::Display for StatsDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let formatter = self.stats.formatter();
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|field| {
+                format!(
+                    "{} = {}",
+                    field.label(),
+                    Blue.paint(self.value(*field, &formatter))
+                )
+            })
+            .collect();
+        writeln!(f, "{}", fields.join("; "))
+    }
+}
+
+/// Computes statistics from values received one at a time, in constant
+/// memory.
+///
+/// Only the statistics not depending on the order of the values (min, max,
+/// average, variance) are computed, so the percentiles of the resulting
+/// `Stats` are NaN and are not displayed.
+#[derive(Debug)]
+pub struct StatsBuilder {
+    min: f64,
+    max: f64,
+    avg: f64,
+    m2: f64,
+    m3: f64,
+    m4: f64,
+    samples: usize,
+    precision: Option<usize>,
+    non_finite: usize,
+}
+
+impl StatsBuilder {
+    /// Creates an empty StatsBuilder.  See `Stats::new` for the meaning of
+    /// `precision`.
+    pub fn new(precision: Option<usize>) -> Self {
+        Self {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            avg: 0.0,
+            m2: 0.0,
+            m3: 0.0,
+            m4: 0.0,
+            samples: 0,
+            precision,
+            non_finite: 0,
+        }
+    }
+
+    /// Accounts for a new value.
+    pub fn push(&mut self, x: f64) {
+        self.push_weighted(x, 1);
+    }
+
+    /// Accounts for a value observed `weight` times.  NaN and infinite
+    /// values are only counted (see `Stats::non_finite`).
+    pub fn push_weighted(&mut self, x: f64, weight: usize) {
+        if !x.is_finite() {
+            self.non_finite += weight;
+            return;
+        }
+        if weight == 0 {
+            return;
+        }
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+        // Combining moments one value at a time is a generalization of
+        // Welford's online algorithm, numerically stabler than accumulating
+        // sums of powers.
+        let current = Moments {
+            samples: self.samples,
+            avg: self.avg,
+            m2: self.m2,
+            m3: self.m3,
+            m4: self.m4,
+        };
+        let moments = current.combine(Moments {
+            samples: weight,
+            avg: x,
+            m2: 0.0,
+            m3: 0.0,
+            m4: 0.0,
+        });
+        self.samples = moments.samples;
+        self.avg = moments.avg;
+        self.m2 = moments.m2;
+        self.m3 = moments.m3;
+        self.m4 = moments.m4;
+    }
+
+    /// Returns the number of values pushed so far.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Consumes the builder, returning the computed Stats.
+    pub fn finish(self) -> Stats {
+        let var = self.m2 / self.samples as f64;
+        Stats {
+            min: self.min,
+            max: self.max,
+            avg: self.avg,
+            std: var.sqrt(),
+            var,
+            samples: self.samples,
+            precision: self.precision,
+            p50: f64::NAN,
+            p90: f64::NAN,
+            p95: f64::NAN,
+            p99: f64::NAN,
+            sorted: Vec::new(),
+            m2: self.m2,
+            m3: self.m3,
+            m4: self.m4,
+            non_finite: self.non_finite,
+            show_shape: false,
+            show_mad: false,
+            mean_ci_level: None,
+        }
     }
 }
 
@@ -129,6 +703,246 @@ mod tests {
         assert_float_eq!(stats.std, 0.8981, abs <= 0.0001);
     }
 
+    #[test]
+    fn test_mean_ci() {
+        // Mean is 5, the sample STD is 2.138 and t(0.975, 7) = 2.3646
+        let stats = Stats::new(&mut [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], Some(2));
+        let (low, high) = stats.mean_ci(0.95);
+        assert_float_eq!(low, 3.2126, abs <= 0.0001);
+        assert_float_eq!(high, 6.7874, abs <= 0.0001);
+        let (low, high) = stats.mean_ci(0.99);
+        assert!(low < 3.2126 && high > 6.7874);
+        assert!(Stats::new(&mut [1.0], None).mean_ci(0.95).0.is_nan());
+        Paint::disable();
+        let display = format!("{}", stats.with_mean_ci(0.95));
+        assert!(display.contains("Average = 5.00 (95% CI [3.21, 6.79]); Variance"));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let stats = Stats::new(&mut [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], None);
+        let json: serde_json::Value = serde_json::from_str(&stats.to_json()).unwrap();
+        assert_eq!(json["samples"], 8);
+        assert_eq!(json["min"], 2.0);
+        assert_eq!(json["max"], 9.0);
+        assert_eq!(json["mean"], 5.0);
+        assert_eq!(json["median"], 4.5);
+        assert_eq!(json["stddev"], 2.0);
+    }
+
+    #[test]
+    fn test_variance() {
+        // Mean is 5, squared differences add up to 32.
+        let stats = Stats::new(&mut [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], None);
+        assert_float_eq!(stats.variance(false), 4.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.std_dev(false), 2.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.variance(true), 32.0 / 7.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.std_dev(true), 2.1381, abs <= 0.0001);
+        let stats = Stats::new(&mut [1e9 + 4.0, 1e9 + 7.0, 1e9 + 13.0, 1e9 + 16.0], None);
+        assert_float_eq!(stats.variance(true), 30.0, rmax <= f64::EPSILON);
+        let stats = Stats::new(&mut [3.0], None);
+        assert_float_eq!(stats.variance(true), 0.0, rmax <= f64::EPSILON);
+    }
+
+    #[test]
+    fn test_builder() {
+        let mut builder = StatsBuilder::new(Some(3));
+        for x in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            builder.push(x);
+        }
+        assert_eq!(builder.samples(), 8);
+        let stats = builder.finish();
+        assert_eq!(stats.samples, 8);
+        assert_float_eq!(stats.min, 2.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.max, 9.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.avg, 5.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.std_dev(false), 2.0, rmax <= f64::EPSILON);
+        assert!(stats.median().is_nan());
+        Paint::disable();
+        let display = format!("{stats}");
+        assert!(display.contains("Average = 5.000"));
+        assert!(!display.contains("p50"));
+    }
+
+    #[test]
+    fn test_builder_weighted() {
+        let mut builder = StatsBuilder::new(Some(3));
+        for (x, weight) in [(2.0, 1), (4.0, 3), (5.0, 2), (6.0, 0), (7.0, 1), (9.0, 1)] {
+            builder.push_weighted(x, weight);
+        }
+        let stats = builder.finish();
+        assert_eq!(stats.samples, 8);
+        assert_float_eq!(stats.max, 9.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.avg, 5.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.std_dev(false), 2.0, rmax <= 1e-15);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut stats = Stats::new(&mut [2.0, 4.0, 4.0, 4.0], Some(3));
+        stats.merge(&Stats::new(&mut [5.0, 5.0, 7.0, 9.0], None));
+        let expected = Stats::new(&mut [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], Some(3));
+        assert_eq!(stats.samples, 8);
+        assert_float_eq!(stats.min, expected.min, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.max, expected.max, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.avg, expected.avg, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.var, expected.var, rmax <= 1e-15);
+        assert_float_eq!(stats.median(), expected.median(), rmax <= f64::EPSILON);
+        assert_float_eq!(stats.p90, expected.p90, rmax <= f64::EPSILON);
+        stats.merge(&StatsBuilder::new(None).finish());
+        assert_eq!(stats.samples, 8);
+        let mut streamed = StatsBuilder::new(None);
+        streamed.push(1.0);
+        stats.merge(&streamed.finish());
+        assert_eq!(stats.samples, 9);
+        assert!(stats.median().is_nan());
+    }
+
+    #[test]
+    fn test_non_finite() {
+        let mut vec = [3.0, f64::NAN, 1.0, f64::INFINITY, 2.0, f64::NEG_INFINITY];
+        let stats = Stats::new(&mut vec, Some(1));
+        assert_eq!(stats.samples, 3);
+        assert_eq!(stats.non_finite(), 3);
+        assert_float_eq!(stats.min, 1.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.max, 3.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.avg, 2.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.median(), 2.0, rmax <= f64::EPSILON);
+        assert_eq!(vec[1..4], [1.0, 2.0, 3.0]);
+        Paint::disable();
+        let display = format!("{stats}");
+        assert!(display.contains("\nnon-finite dropped: 3\n"));
+    }
+
+    #[test]
+    fn test_shape() {
+        let stats = Stats::new(&mut [1.0, 2.0, 3.0, 4.0, 5.0], None);
+        assert_float_eq!(stats.skewness(), 0.0, abs <= 1e-12);
+        // Excess kurtosis of a discrete uniform distribution of 5 values
+        assert_float_eq!(stats.kurtosis(), -1.3, rmax <= 1e-12);
+        let mut vec = vec![0.0; 20];
+        vec.extend([-10.0, 10.0]);
+        let stats = Stats::new(&mut vec, None);
+        assert_float_eq!(stats.skewness(), 0.0, abs <= 1e-12);
+        assert_float_eq!(stats.kurtosis(), 8.0, rmax <= 1e-12);
+        let stats = Stats::new(&mut [1.0, 1.0, 1.0, 2.0, 10.0], None);
+        assert!(stats.skewness() > 1.0);
+        assert!(stats.kurtosis() > 0.0);
+    }
+
+    #[test]
+    fn test_shape_merge() {
+        let mut vec = [1.0, 1.0, 1.0, 2.0, 10.0, 4.0, -3.0];
+        let expected = Stats::new(&mut vec.clone(), None);
+        let (a, b) = vec.split_at_mut(3);
+        let mut stats = Stats::new(a, None);
+        stats.merge(&Stats::new(b, None));
+        assert_float_eq!(stats.skewness(), expected.skewness(), rmax <= 1e-12);
+        assert_float_eq!(stats.kurtosis(), expected.kurtosis(), rmax <= 1e-12);
+    }
+
+    #[test]
+    fn test_shape_display() {
+        Paint::disable();
+        let stats = Stats::new(&mut [1.0, 2.0, 3.0, 4.0, 5.0], None);
+        assert!(!format!("{stats}").contains("Skewness"));
+        let display = format!("{}", stats.with_shape());
+        assert!(display.contains("\nSkewness = 0.000; Kurtosis = -1.300\n"));
+    }
+
+    #[test]
+    fn test_trimmed_mean() {
+        let mut vec: Vec<f64> = (1..=9).map(f64::from).collect();
+        vec.push(1000.0);
+        let stats = Stats::new(&mut vec, None);
+        assert_float_eq!(stats.avg, 104.5, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.trimmed_mean(0.1), 5.5, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.trimmed_mean(0.0), 104.5, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.trimmed_mean(-1.0), 104.5, rmax <= f64::EPSILON);
+        // Clamped, the two central values are kept
+        assert_float_eq!(stats.trimmed_mean(0.7), 5.5, rmax <= f64::EPSILON);
+        let stats = Stats::new(&mut [1.0, 2.0, 30.0], None);
+        assert_float_eq!(stats.trimmed_mean(0.5), 2.0, rmax <= f64::EPSILON);
+        assert!(StatsBuilder::new(None).finish().trimmed_mean(0.1).is_nan());
+    }
+
+    #[test]
+    fn test_mad() {
+        let stats = Stats::new(&mut [9.0, 1.0, 2.0, 6.0, 1.0, 4.0, 2.0], Some(1));
+        // Deviations from the median (2) are 0, 0, 1, 1, 2, 4 and 7
+        assert_float_eq!(stats.mad(), 1.0, rmax <= f64::EPSILON);
+        assert!(StatsBuilder::new(None).finish().mad().is_nan());
+        Paint::disable();
+        assert!(!format!("{stats}").contains("MAD"));
+        let display = format!("{}", stats.with_mad());
+        assert!(display.contains("\nMAD = 1.0; Mode = 1.0 (2 times)\n"));
+        let stats = Stats::new(&mut [1.0, 2.0, 3.0], Some(1)).with_mad();
+        assert!(format!("{stats}").ends_with("\nMAD = 1.0\n"));
+    }
+
+    #[test]
+    fn test_mode() {
+        let stats = Stats::new(&mut [3.0, 1.0, 2.0, 3.0, 1.0, 3.0], Some(1));
+        assert_eq!(stats.mode_with_count(), Some((3.0, 3)));
+        assert_eq!(stats.mode(), Some(3.0));
+        // Ties go to the lowest value
+        let stats = Stats::new(&mut [2.0, 1.0, 2.0, 1.0], Some(1));
+        assert_eq!(stats.mode_with_count(), Some((1.0, 2)));
+        let stats = Stats::new(&mut [1.0, 2.0, 3.0], Some(1));
+        assert_eq!(stats.mode(), None);
+        let mut builder = StatsBuilder::new(None);
+        builder.push(1.0);
+        builder.push(1.0);
+        assert_eq!(builder.finish().mode(), None);
+    }
+
+    #[test]
+    fn test_mode_display() {
+        Paint::disable();
+        let stats = Stats::new(&mut [3.0, 1.0, 2.0, 3.0], Some(1));
+        assert!(format!("{stats}").ends_with("; p99 = 3.0\nMode = 3.0 (2 times)\n"));
+        let stats = Stats::new(&mut [1.0, 2.0, 3.0], Some(1));
+        assert!(!format!("{stats}").contains("Mode"));
+    }
+
+    #[test]
+    fn test_precision_display() {
+        Paint::disable();
+        let stats = Stats::new(&mut [1.0, 2.0, 4.0], None);
+        assert!(format!("{stats}").starts_with("Samples = 3; Min = 1.000; Max = 4.000\n"));
+        let stats = stats.with_precision(Some(0));
+        let display = format!("{stats}");
+        assert!(
+            display.starts_with("Samples = 3; Min = 1; Max = 4\nAverage = 2; Variance = 1.556;")
+        );
+        let stats = Stats::new(&mut [0.00011, 0.00012, 0.00016], None).with_precision(Some(6));
+        let display = format!("{stats}");
+        assert!(display.contains("Average = 0.000130; Variance = 0.000000; STD = 0.000022;"));
+    }
+
+    #[test]
+    fn test_display_fields() {
+        Paint::disable();
+        let mut vec: Vec<f64> = (1..=100).map(f64::from).collect();
+        let stats = Stats::new(&mut vec, Some(1));
+        let display = stats.display_fields(&[StatField::Min, StatField::P99]);
+        assert_eq!(format!("{display}"), "Min = 1.0; p99 = 99.0\n");
+        let display = stats.display_fields(&[StatField::Samples, StatField::Mode, StatField::Std]);
+        assert_eq!(
+            format!("{display}"),
+            "Samples = 100; Mode = none; STD = 28.866\n"
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let stats = Stats::new(&mut [], None);
+        assert_eq!(stats.samples, 0);
+        assert!(stats.median().is_nan());
+        Paint::disable();
+        assert_eq!(format!("{stats}"), "Samples = 0\n");
+    }
+
     #[test]
     fn test_display() {
         let stats = Stats::new(&mut [1.1, 3.3, 2.2], Some(3));
@@ -138,6 +952,7 @@ mod tests {
         assert!(display.contains("Min = 1.100"));
         assert!(display.contains("Max = 3.300"));
         assert!(display.contains("Average = 2.200"));
+        assert!(display.contains("Sample STD = 1.100"));
     }
 
     #[test]
@@ -150,6 +965,29 @@ mod tests {
         assert!(display.contains("Max = 123456789.123"));
     }
 
+    #[test]
+    fn test_quantile() {
+        let vec = [1.0, 2.0, 4.0, 8.0];
+        assert_float_eq!(quantile(&vec, 0.0), 1.0, rmax <= f64::EPSILON);
+        assert_float_eq!(quantile(&vec, 0.5), 3.0, rmax <= f64::EPSILON);
+        assert_float_eq!(quantile(&vec, 0.75), 5.0, rmax <= f64::EPSILON);
+        assert_float_eq!(quantile(&vec, 1.0), 8.0, rmax <= f64::EPSILON);
+        assert_float_eq!(quantile(&[3.0], 0.3), 3.0, rmax <= f64::EPSILON);
+    }
+
+    #[test]
+    fn test_median_and_percentile() {
+        let stats = Stats::new(&mut [4.0, 1.0, 3.0, 2.0], None);
+        assert_float_eq!(stats.median(), 2.5, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.p50, stats.median(), rmax <= f64::EPSILON);
+        assert_float_eq!(stats.percentile(0.0), 1.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.percentile(25.0), 1.75, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.percentile(100.0), 4.0, rmax <= f64::EPSILON);
+        let stats = Stats::new(&mut [5.0, 1.0, 3.0], None);
+        assert_float_eq!(stats.median(), 3.0, rmax <= f64::EPSILON);
+        assert_float_eq!(stats.p50, 3.0, rmax <= f64::EPSILON);
+    }
+
     #[test]
     fn test_percentile() {
         let mut vec: Vec<f64> = (0..100).map(|i| i as f64).collect();
@@ -158,9 +996,12 @@ mod tests {
         Paint::disable();
         let display = format!("{stats}");
         println!("{}", display);
-        assert!(display.contains("p50 = 50.0"));
-        assert!(display.contains("p90 = 90.0"));
-        assert!(display.contains("p95 = 95.0"));
-        assert!(display.contains("p99 = 99.0"));
+        assert!(display.contains("p50 = 49.5"));
+        assert!(display.contains("p90 = 89.1"));
+        assert!(display.contains("p95 = 94.0"));
+        assert!(display.contains("p99 = 98.0"));
+        assert_float_eq!(stats.p90, stats.percentile(90.0), rmax <= f64::EPSILON);
+        assert_float_eq!(stats.p95, stats.percentile(95.0), rmax <= f64::EPSILON);
+        assert_float_eq!(stats.p99, stats.percentile(99.0), rmax <= f64::EPSILON);
     }
 }
@@ -0,0 +1,79 @@
+// Coefficients of the rational approximations of Acklam's algorithm for the
+// quantile function of the standard normal distribution.
+const A: [f64; 6] = [
+    -3.969_683_028_665_376e1,
+    2.209_460_984_245_205e2,
+    -2.759_285_104_469_687e2,
+    1.383_577_518_672_69e2,
+    -3.066_479_806_614_716e1,
+    2.506_628_277_459_239,
+];
+const B: [f64; 5] = [
+    -5.447_609_879_822_406e1,
+    1.615_858_368_580_409e2,
+    -1.556_989_798_598_866e2,
+    6.680_131_188_771_972e1,
+    -1.328_068_155_288_572e1,
+];
+const C: [f64; 6] = [
+    -7.784_894_002_430_293e-3,
+    -3.223_964_580_411_365e-1,
+    -2.400_758_277_161_838,
+    -2.549_732_539_343_734,
+    4.374_664_141_464_968,
+    2.938_163_982_698_783,
+];
+const D: [f64; 4] = [
+    7.784_695_709_041_462e-3,
+    3.224_671_290_700_398e-1,
+    2.445_134_137_142_996,
+    3.754_408_661_907_416,
+];
+
+// Below this probability (and above its complement) the tail approximation
+// is used.
+const P_LOW: f64 = 0.02425;
+
+// Evaluates a polynomial with the given coefficients, from the highest degree.
+fn polynomial(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().fold(0.0, |acc, c| acc.mul_add(x, *c))
+}
+
+// Returns the value of the tail approximation for the lower tail.
+fn lower_tail(p: f64) -> f64 {
+    let q = (-2.0 * p.ln()).sqrt();
+    polynomial(&C, q) / polynomial(&D, q).mul_add(q, 1.0)
+}
+
+/// Returns the value below which a fraction `p` (in `(0, 1)`) of a standard
+/// normal distribution falls, with a relative error below 1.2e-9.
+pub(crate) fn quantile(p: f64) -> f64 {
+    if !(p > 0.0 && p < 1.0) {
+        return f64::NAN;
+    }
+    if p < P_LOW {
+        return lower_tail(p);
+    }
+    if p > 1.0 - P_LOW {
+        return -lower_tail(1.0 - p);
+    }
+    let q = p - 0.5;
+    let r = q * q;
+    polynomial(&A, r) * q / polynomial(&B, r).mul_add(r, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn test_quantile() {
+        assert_float_eq!(quantile(0.5), 0.0, abs <= 1e-12);
+        assert_float_eq!(quantile(0.975), 1.959_964, abs <= 1e-6);
+        assert_float_eq!(quantile(0.01), -2.326_348, abs <= 1e-6);
+        assert_float_eq!(quantile(0.999), 3.090_232, abs <= 1e-6);
+        assert_float_eq!(quantile(0.2), -quantile(0.8), abs <= 1e-12);
+        assert!(quantile(0.0).is_nan());
+    }
+}
@@ -0,0 +1,150 @@
+// Coefficients of the Lanczos approximation of the gamma function (g = 7).
+const LANCZOS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+// Returns the natural logarithm of the gamma function, for positive `x`.
+fn ln_gamma(x: f64) -> f64 {
+    if x < 0.5 {
+        // Reflection formula
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut sum = LANCZOS[0];
+    for (i, coefficient) in LANCZOS.iter().enumerate().skip(1) {
+        sum += coefficient / (x + i as f64);
+    }
+    let t = x + 7.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+// Evaluates the continued fraction of the incomplete beta function with the
+// modified Lentz's method.
+fn beta_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut fraction = d;
+    for m in 1..300 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1.0 + numerator / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            fraction *= d * c;
+        }
+        if (d * c - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    fraction
+}
+
+// Returns the regularized incomplete beta function I_x(a, b).
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The continued fraction converges quickly only on this side
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Returns the cumulative distribution function at `t` of a t-distribution
+/// with `df` degrees of freedom.
+pub(crate) fn t_cdf(t: f64, df: f64) -> f64 {
+    let tail = 0.5 * incomplete_beta(df / 2.0, 0.5, df / (df + t * t));
+    if t > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// Returns the value below which a fraction `p` (in `(0, 1)`) of a
+/// t-distribution with `df` degrees of freedom falls.
+pub(crate) fn t_quantile(p: f64, df: f64) -> f64 {
+    if !(p > 0.0 && p < 1.0) || df <= 0.0 {
+        return f64::NAN;
+    }
+    if p < 0.5 {
+        return -t_quantile(1.0 - p, df);
+    }
+    let mut high = 1.0;
+    while t_cdf(high, df) < p {
+        high *= 2.0;
+    }
+    let mut low = 0.0;
+    for _ in 0..200 {
+        let middle = (low + high) / 2.0;
+        if t_cdf(middle, df) < p {
+            low = middle;
+        } else {
+            high = middle;
+        }
+        if high - low <= f64::EPSILON * high {
+            break;
+        }
+    }
+    (low + high) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn test_ln_gamma() {
+        assert_float_eq!(ln_gamma(1.0), 0.0, abs <= 1e-12);
+        assert_float_eq!(ln_gamma(5.0), 24.0_f64.ln(), abs <= 1e-12);
+        assert_float_eq!(
+            ln_gamma(0.5),
+            std::f64::consts::PI.sqrt().ln(),
+            abs <= 1e-12
+        );
+    }
+
+    #[test]
+    fn test_t_quantile() {
+        // Values of the usual tables of critical values
+        assert_float_eq!(t_quantile(0.975, 1.0), 12.7062, abs <= 1e-4);
+        assert_float_eq!(t_quantile(0.975, 7.0), 2.3646, abs <= 1e-4);
+        assert_float_eq!(t_quantile(0.975, 10.0), 2.2281, abs <= 1e-4);
+        assert_float_eq!(t_quantile(0.995, 30.0), 2.7500, abs <= 1e-4);
+        assert_float_eq!(t_quantile(0.95, 1e6), 1.6449, abs <= 1e-4);
+        assert_float_eq!(t_quantile(0.025, 10.0), -2.2281, abs <= 1e-4);
+        assert_float_eq!(t_quantile(0.5, 3.0), 0.0, abs <= 1e-12);
+        assert!(t_quantile(1.0, 3.0).is_nan());
+    }
+}
@@ -37,15 +37,14 @@ fn test_timehist() {
     let mut cmd = Command::cargo_bin("lowcharts").unwrap();
     cmd.arg("timehist")
         .assert()
-        .success()
-        .stderr(predicate::str::contains("Not enough data to process"));
+        .code(3)
+        .stderr(predicate::str::contains("No data points found"));
     // Stdin is garbage
     let mut cmd = Command::cargo_bin("lowcharts").unwrap();
     cmd.arg("timehist")
         .write_stdin("foo\n")
         .assert()
-        .success()
-        .stderr(predicate::str::contains("Not enough data to process"))
+        .code(1)
         .stderr(predicate::str::contains(
             "Could not figure out parsing strategy",
         ));
@@ -103,6 +102,40 @@ fn test_hist_human() {
         .stdout(predicate::str::contains("\n[33.0 M .. 42.0 M] [1] ∎\n"));
 }
 
+#[test]
+fn test_hist_auto_intervals() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.arg("hist")
+        .arg("--intervals")
+        .arg("auto")
+        .write_stdin("1\n2\n3\n4\n5\n6\n7\n8\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[1.000 .. 4.500] [4] ∎∎∎∎\n"))
+        .stdout(predicate::str::contains("[4.500 .. 8.000] [4] ∎∎∎∎\n"));
+}
+
+#[test]
+fn test_hist_geometric() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.arg("hist")
+        .arg("--geometric")
+        .arg("--intervals")
+        .arg("2")
+        .write_stdin("1\n10\n100\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[  1.000 ..  10.000] [1] ∎\n"))
+        .stdout(predicate::str::contains("[ 10.000 .. 100.000] [2] ∎∎\n"));
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.arg("hist")
+        .arg("--geometric")
+        .write_stdin("0\n10\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("need positive values"));
+}
+
 #[test]
 fn test_matchbar() {
     let mut cmd = Command::cargo_bin("lowcharts").unwrap();
@@ -153,6 +186,48 @@ fn test_splittime() {
         .stdout(predicate::str::contains("[00:18:48.388165] [1/0/0] ∎\n"));
 }
 
+#[test]
+fn test_no_color_env() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.env("NO_COLOR", "1")
+        .arg("hist")
+        .write_stdin("1\n2\n3\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Samples = 3;"))
+        .stdout(predicate::str::contains("\u{1b}[").not());
+}
+
+#[test]
+fn test_color_yes_overrides_no_color_env() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.env("NO_COLOR", "1")
+        .arg("--color")
+        .arg("yes")
+        .arg("hist")
+        .write_stdin("1\n2\n3\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\u{1b}["));
+}
+
+#[test]
+fn test_columns_env_ignored_when_piped() {
+    let input: String = (0..500).map(|x| format!("{}\n", x % 5)).collect();
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+        let output = cmd
+            .env("COLUMNS", "40")
+            .args(args)
+            .write_stdin(input.clone())
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(run(&["hist"]), run(&["hist", "--width", "110"]));
+    assert_ne!(run(&["hist"]), run(&["hist", "--width", "40"]));
+}
+
 #[test]
 fn test_plot() {
     let mut cmd = Command::cargo_bin("lowcharts").unwrap();
@@ -260,3 +335,697 @@ fn test_common() {
         Err(_) => panic!("Could not create temp file"),
     }
 }
+
+#[test]
+fn test_hist_field() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.arg("--color")
+        .arg("no")
+        .arg("hist")
+        .arg("--field")
+        .arg("1")
+        .arg("--intervals")
+        .arg("2")
+        .write_stdin("a 1\nb 2 x\nc\nd 4\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Samples = 3; Min = 1.000; Max = 4.000\n",
+        ))
+        .stdout(predicate::str::contains("\n[1.000 .. 2.500] [2] ∎∎\n"))
+        .stderr(predicate::str::contains(
+            "1 lines did not have a numeric field",
+        ));
+}
+
+#[test]
+fn test_hist_strip_separators() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.arg("--color")
+        .arg("no")
+        .arg("hist")
+        .arg("--strip-separators")
+        .write_stdin("1,234.5\n2,000\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Samples = 2; Min = 1234.50; Max = 2000.00\n",
+        ));
+}
+
+#[test]
+fn test_hist_sample() {
+    let input: String = (0..1000).map(|x| format!("{}\n", x)).collect();
+    let run = || {
+        let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+        let assert = cmd
+            .args(["--color", "no", "hist", "--sample", "100", "--seed", "7"])
+            .write_stdin(input.clone())
+            .assert()
+            .success()
+            .stdout(predicate::str::starts_with(
+                "Sampled 100 of 1000 values\nSamples = 100;",
+            ));
+        assert.get_output().stdout.clone()
+    };
+    assert_eq!(run(), run());
+}
+
+#[test]
+fn test_hist_weighted() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "hist", "--weighted", "--intervals", "2"])
+        .write_stdin("1 1000000\n2 5\n4 3\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Samples = 1000008; Min = 1.000; Max = 4.000\n",
+        ))
+        .stdout(predicate::str::contains("\n[1.000 .. 2.500] [1000005] ∎"))
+        .stdout(predicate::str::contains("\n[2.500 .. 4.000] [      3] \n"));
+}
+
+#[test]
+fn test_hist_no_decimals() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--precision",
+        "0",
+        "--intervals",
+        "2",
+    ])
+    .write_stdin("1\n2\n9\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("Samples = 3; Min = 1; Max = 9\n"))
+    .stdout(predicate::str::contains("\n[1 .. 5] [2] ∎∎\n"));
+}
+
+#[test]
+fn test_hist_several_files() {
+    let mut file_a = NamedTempFile::new().unwrap();
+    writeln!(file_a, "1").unwrap();
+    let mut file_b = NamedTempFile::new().unwrap();
+    writeln!(file_b, "2").unwrap();
+    writeln!(file_b, "3").unwrap();
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.arg("hist")
+        .arg(file_a.path().to_str().unwrap())
+        .arg("-")
+        .arg(file_b.path().to_str().unwrap())
+        .write_stdin("4\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Samples = 4; Min = 1.000; Max = 4.000\n",
+        ));
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.arg("hist")
+        .arg(file_a.path().to_str().unwrap())
+        .arg("/no/good")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Could not open /no/good"));
+}
+
+#[test]
+fn test_no_data_points() {
+    for subcommand in ["hist", "plot", "boxplot", "ecdf"] {
+        let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+        cmd.arg(subcommand)
+            .write_stdin("\n\n")
+            .assert()
+            .code(3)
+            .stderr(predicate::str::contains("No data points found"));
+    }
+    let subcommands: [&[&str]; 4] = [
+        &["timehist"],
+        &["split-timehist", "foo"],
+        &["matches", "foo"],
+        &["common-terms"],
+    ];
+    for args in subcommands {
+        let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+        cmd.args(args)
+            .write_stdin("")
+            .assert()
+            .code(3)
+            .stderr(predicate::str::contains("No data points found"));
+    }
+}
+
+#[test]
+fn test_nothing_parsed() {
+    for subcommand in ["hist", "plot", "boxplot", "ecdf"] {
+        let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+        cmd.arg(subcommand)
+            .write_stdin("foo\n\nbar baz\n\x01\x02\n")
+            .assert()
+            .code(1)
+            .stderr(predicate::str::contains(
+                "0 of 3 lines parsed as numbers; wrong column or format?",
+            ));
+    }
+    for args in [&["timehist"][..], &["split-timehist", "foo"]] {
+        let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+        cmd.args(args)
+            .write_stdin("garbage\n")
+            .assert()
+            .code(1)
+            .stderr(predicate::str::contains(
+                "0 of 1 lines parsed as timestamps; wrong format or matches?",
+            ));
+    }
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["common-terms", "--regex", "id=(\\d+)"])
+        .write_stdin("foo\nbar\n")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("0 of 2 lines matched the regex"));
+}
+
+#[test]
+fn test_hist_shape() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "hist", "--shape"])
+        .write_stdin("1\n2\n3\n4\n5\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\nSkewness = 0.000; Kurtosis = -1.300\n",
+        ));
+}
+
+#[test]
+fn test_hist_mad() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "hist", "--precision", "1"])
+        .write_stdin("5\n5\n5\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\nMode = 5.0 (3 times)\n"))
+        .stdout(predicate::str::contains("MAD").not());
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "hist", "--precision", "1", "--mad"])
+        .write_stdin("5\n5\n5\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\nMAD = 0.0; Mode = 5.0 (3 times)\n",
+        ));
+}
+
+#[test]
+fn test_progress_silent_when_piped() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "hist", "--progress"])
+        .write_stdin("1\n2\n3\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Samples = 3"))
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn test_hist_durations() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "hist", "--durations", "--precision", "2"])
+        .write_stdin("1m30s\n250ms\n2m\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Samples = 3; Min = 0.25; Max = 120.00\n",
+        ));
+}
+
+#[test]
+fn test_hist_radix_and_units() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "hist", "--radix", "16", "--precision", "0"])
+        .write_stdin("0x1f\nff\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Samples = 2; Min = 31; Max = 255\n",
+        ));
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "hist", "--strip-units", "--precision", "0"])
+        .write_stdin("123ms\n7ms\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Samples = 2; Min = 7; Max = 123\n",
+        ));
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["hist", "--radix", "40"])
+        .write_stdin("1\n")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Radix should be between 2 and 36"));
+}
+
+#[test]
+fn test_hist_stream() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--stream",
+        "--min",
+        "0",
+        "--max",
+        "10",
+        "--intervals",
+        "2",
+        "--precision",
+        "1",
+    ])
+    .write_stdin("1\n2\n7\n20\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+        "Samples = 3; Min = 0.0; Max = 10.0\n",
+    ))
+    .stdout(predicate::str::contains("\n[ 0.0 ..  5.0] [2] ∎∎\n"))
+    .stdout(predicate::str::contains("\n[ 5.0 .. 10.0] [1] ∎\n"));
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["hist", "--stream", "--min", "0"])
+        .write_stdin("1\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--max"));
+}
+
+#[test]
+fn test_hist_density() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--density",
+        "--intervals",
+        "2",
+        "--precision",
+        "1",
+    ])
+    .write_stdin("0\n1\n3\n4\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+        "Each ∎ represents a density of 0.1250\n",
+    ))
+    .stdout(predicate::str::contains("\n[0.0 .. 2.0] [0.2500] ∎∎\n"));
+}
+
+#[test]
+fn test_hist_markers() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--markers",
+        "--intervals",
+        "2",
+        "--precision",
+        "0",
+    ])
+    .write_stdin("1\n2\n3\n4\n10\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+        "Markers: │ p50 = 3; ┃ p90 = 8; ║ p99 = 10\n",
+    ))
+    .stdout(predicate::str::contains(
+        "\n[ 1 ..  6] [4] ∎│∎∎\n[ 6 .. 10] [1] ║\n",
+    ));
+}
+
+#[test]
+fn test_hist_tsv() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    let output = cmd
+        .args([
+            "--color",
+            "yes",
+            "hist",
+            "--output-format",
+            "tsv",
+            "--intervals",
+            "2",
+        ])
+        .write_stdin("1\n2\n3\n4\n")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout, "1\t2.5\t2\n2.5\t4\t2\n");
+    for line in stdout.lines() {
+        assert_eq!(line.split('\t').count(), 3);
+    }
+}
+
+#[test]
+fn test_hist_theme() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "yes",
+        "hist",
+        "--theme",
+        "monochrome",
+        "--intervals",
+        "2",
+    ])
+    .write_stdin("1\n2\n3\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("\n[1.000 .. 2.000] [1] ∎\n"))
+    .stdout(predicate::str::contains("\n[2.000 .. 3.000] [2] ∎∎\n"));
+}
+
+#[test]
+fn test_hist_intensity() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "hist", "--intensity", "--intervals", "2"])
+        .write_stdin("1\n2\n3\n3\n3\n3\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\n[1.000 .. 2.000] [1] ░\n"))
+        .stdout(predicate::str::contains("\n[2.000 .. 3.000] [5] █████\n"));
+}
+
+#[test]
+fn test_hist_zero_axis() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--zero-axis",
+        "--intervals",
+        "2",
+        "--precision",
+        "0",
+        "--width",
+        "30",
+    ])
+    .write_stdin("-1\n-1\n-0.5\n1\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("\n[-1 ..  0] [3]      ∎∎∎┆\n"))
+    .stdout(predicate::str::contains("\n[ 0 ..  1] [1]         ┆∎\n"));
+}
+
+#[test]
+fn test_auto() {
+    let mut numbers = NamedTempFile::new().unwrap();
+    writeln!(numbers, "1\n2\n3").unwrap();
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "auto", numbers.path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Input holds numbers"))
+        .stdout(predicate::str::contains(
+            "Samples = 3; Min = 1.000; Max = 3.000\n",
+        ));
+    let mut timestamps = NamedTempFile::new().unwrap();
+    writeln!(
+        timestamps,
+        "[2021-04-15 06:25:31] a\n[2021-04-15 06:25:32] b"
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "auto", timestamps.path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Input holds timestamps"))
+        .stdout(predicate::str::contains("Matches: 2.\n"));
+    let mut mixed = NamedTempFile::new().unwrap();
+    writeln!(mixed, "1\n2021-04-15 06:25:31\n3").unwrap();
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "auto", mixed.path().to_str().unwrap()])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "1 of the first 3 lines hold timestamps, those will be skipped",
+        ))
+        .stdout(predicate::str::contains("Samples = 2;"));
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["auto", "-"]).assert().failure().code(2);
+}
+
+#[test]
+fn test_hist_vertical() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--vertical",
+        "--height",
+        "2",
+        "--intervals",
+        "2",
+        "--precision",
+        "0",
+    ])
+    .write_stdin("1\n2\n3\n3\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+        "\n3 │    ███\n  │███ ███\n0 └───────\n   1     3\n",
+    ));
+}
+
+#[test]
+fn test_hist_nice() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "hist", "--nice", "--intervals", "5"])
+        .write_stdin("0.13\n0.2\n0.35\n0.71\n1.58\n2.4718\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\n[0.000 .. 0.500] [3] ∎∎∎\n"))
+        .stdout(predicate::str::contains("\n[2.000 .. 2.500] [1] ∎\n"));
+}
+
+#[test]
+fn test_hist_min_bar_len() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--min-bar-len",
+        "1",
+        "--intervals",
+        "2",
+    ])
+    .write_stdin("1\n".repeat(1000) + "2\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("\n[1.500 .. 2.000] [   1] ∎\n"));
+}
+
+#[test]
+fn test_zero_width() {
+    for subcommand in ["plot", "matches", "common-terms", "hist"] {
+        let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+        let mut args = vec!["--color", "no", subcommand, "-w", "0"];
+        if subcommand == "matches" {
+            args.push("1");
+        }
+        cmd.args(args).write_stdin("1\n2\n3\n").assert().success();
+    }
+}
+
+#[test]
+fn test_hist_hide_legend_and_stats() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "hist", "--intervals", "2"])
+        .args(["--hide-legend", "--hide-stats"])
+        .write_stdin("1\n2\n3\n4\n5\n")
+        .assert()
+        .success()
+        .stdout("[1.000 .. 3.000] [2] ∎∎\n[3.000 .. 5.000] [3] ∎∎∎\n");
+}
+
+#[test]
+fn test_hist_threshold() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--intervals",
+        "2",
+        "--threshold",
+        "2",
+    ])
+    .write_stdin("1\n2\n3\n4\n5\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("\n20.0% ≤ 2.000\n"))
+    .stdout(predicate::str::contains(
+        "\n[1.000 .. 3.000] [2] ∎∎\n┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈ 2.000\n",
+    ));
+}
+
+#[test]
+fn test_hist_max_rows() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--intervals",
+        "8",
+        "--max-rows",
+        "3",
+    ])
+    .write_stdin("1\n2\n3\n4\n5\n6\n7\n8\n9\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("Every row merges 4 buckets\n"))
+    .stdout(predicate::str::contains("\n[5.000 .. 9.000] [5] ∎∎∎∎∎\n"));
+}
+
+#[test]
+fn test_timehist_group_by() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "timehist", "--group-by", "weekday"])
+        .arg("--timezone=-05:00")
+        .write_stdin("2021-04-15 06:25:31 a\n2021-04-16 02:00:00 b\n2021-04-16 10:00:00 c\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Matches: 3."))
+        .stdout(predicate::str::contains("\n[Thu] [2] ∎∎\n"))
+        .stdout(predicate::str::contains("\n[Fri] [1] ∎\n"))
+        .stdout(predicate::str::contains("\n[Sun] [0] \n"));
+}
+
+#[test]
+fn test_hist_integer() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["--color", "no", "hist", "--integer", "--intervals", "2"])
+        .write_stdin("1\n2\n3\n4\n4\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\n[1 .. 3] [2] ∎∎\n"))
+        .stdout(predicate::str::contains("\n[3 .. 5] [3] ∎∎∎\n"));
+}
+
+#[test]
+fn test_hist_interval_notation() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--interval-notation",
+        "--intervals",
+        "2",
+    ])
+    .write_stdin("1\n2\n3\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("\n[1.000, 2.000) [1] ∎\n"))
+    .stdout(predicate::str::contains("\n[2.000, 3.000] [2] ∎∎\n"));
+}
+
+#[test]
+fn test_hist_clip() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    let input: String = (1..=8).map(|i| format!("{i}\n")).collect();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--clip",
+        "10",
+        "90",
+        "--intervals",
+        "2",
+    ])
+    .write_stdin(input + "-1000\n1000\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("\n[-99.10 ..   4.05] [4] "))
+    .stdout(predicate::str::contains(
+        "\nbelow range: 1\nabove range: 1\n",
+    ));
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["hist", "--clip", "90", "10"])
+        .write_stdin("1\n2\n")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Invalid percentiles"));
+}
+
+#[test]
+fn test_hist_mean_ci() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--mean-ci",
+        "0.95",
+        "--precision",
+        "2",
+    ])
+    .write_stdin("2\n4\n4\n4\n5\n5\n7\n9\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+        "Average = 5.00 (95% CI [3.21, 6.79]); ",
+    ));
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args(["hist", "--mean-ci", "95"])
+        .write_stdin("1\n2\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("between 0 and 1"));
+}
+
+#[test]
+fn test_hist_highlight() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--intervals",
+        "2",
+        "--highlight",
+        "1.2",
+    ])
+    .write_stdin("1\n2\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("\n[1.000 .. 1.500] [1] ∎ ◀\n"))
+    .stdout(predicate::str::contains("\n[1.500 .. 2.000] [1] ∎\n"));
+}
+
+#[test]
+fn test_hist_human_counts() {
+    let mut cmd = Command::cargo_bin("lowcharts").unwrap();
+    cmd.args([
+        "--color",
+        "no",
+        "hist",
+        "--human-counts",
+        "--intervals",
+        "2",
+    ])
+    .write_stdin("1\n".repeat(1500) + "2\n")
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("\n[1.000 .. 1.500] [1.5k] "))
+    .stdout(predicate::str::contains("\n[1.500 .. 2.000] [   1] "));
+}